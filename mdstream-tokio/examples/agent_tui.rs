@@ -454,7 +454,7 @@ fn render_block(kind: BlockKind, text: &str, width: u16, pending: bool) -> Vec<S
     out.push(header);
 
     match kind {
-        BlockKind::CodeFence | BlockKind::Table => {
+        BlockKind::CodeFence | BlockKind::IndentedCode | BlockKind::Table => {
             for line in text.lines() {
                 out.push(line.to_string());
             }
@@ -390,6 +390,9 @@ impl CoalescingReceiver {
 ///
 /// This is useful when your consumer cannot keep `MdStream` on the UI thread, or when you want to
 /// isolate parsing work from rendering.
+///
+/// Every update is forwarded in order, so streams built with `Options::pending_delta` work as
+/// long as the receiver applies each one (e.g. with `DocumentState::apply`).
 pub fn spawn_mdstream_actor(
     mut stream: MdStream,
     rx: mpsc::Receiver<String>,
@@ -470,4 +473,42 @@ mod tests {
         assert_eq!(s.flush().await.unwrap(), SendOutcome::Sent);
         assert_eq!(rx.recv().await.as_deref(), Some("abcd"));
     }
+
+    #[tokio::test]
+    async fn actor_pending_deltas_rebuild_the_full_pending_block() {
+        use mdstream::{DocumentState, Options};
+
+        let text =
+            "# Title\n\nSome **bold text and [a link](http://x) here\n\n```rust\nfn main() {}\n";
+        let opts = CoalesceOptions {
+            flush_on_newline: false,
+            max_bytes: 1,
+            ..Default::default()
+        };
+        let run = |pending_delta: bool| async move {
+            let (tx, rx) = mpsc::channel::<String>(64);
+            let stream = MdStream::new(Options {
+                pending_delta,
+                ..Default::default()
+            });
+            let mut out = spawn_mdstream_actor(stream, rx, opts);
+            let mut state = DocumentState::new();
+            let mut snapshots = Vec::new();
+            for ch in text.chars() {
+                tx.send(ch.to_string()).await.unwrap();
+                let u = out.recv().await.unwrap();
+                assert!(!pending_delta || u.pending.is_none() || u.pending_delta.is_none());
+                state.apply(u);
+                snapshots.push(state.pending().cloned());
+            }
+            drop(tx);
+            state.apply(out.recv().await.unwrap());
+            snapshots.push(state.pending().cloned());
+            (snapshots, state.committed().to_vec())
+        };
+
+        let full = run(false).await;
+        let delta = run(true).await;
+        assert_eq!(delta, full);
+    }
 }
@@ -2,7 +2,7 @@ use std::collections::btree_map::Entry;
 use std::collections::{BTreeMap, HashMap};
 
 use crate::reference;
-use crate::types::{Block, BlockId, Update};
+use crate::types::{Block, BlockId, BlockKind, Update};
 
 use pulldown_cmark::{Event, Options as PulldownOptions, Parser};
 
@@ -40,6 +40,10 @@ pub struct PulldownAdapter {
     reference_definitions: BTreeMap<String, String>,
     reference_definitions_text: String,
     reference_definitions_dirty: bool,
+    /// Committed footnote definition blocks, injected after each parsed block when
+    /// `ENABLE_FOOTNOTES` is set.
+    footnote_definitions: BTreeMap<BlockId, String>,
+    footnote_definitions_text: String,
     parse_scratch: ParseScratch,
 }
 
@@ -52,6 +56,8 @@ impl PulldownAdapter {
             reference_definitions: BTreeMap::new(),
             reference_definitions_text: String::new(),
             reference_definitions_dirty: false,
+            footnote_definitions: BTreeMap::new(),
+            footnote_definitions_text: String::new(),
             parse_scratch: Default::default(),
         }
     }
@@ -62,6 +68,8 @@ impl PulldownAdapter {
         self.reference_definitions.clear();
         self.reference_definitions_text.clear();
         self.reference_definitions_dirty = false;
+        self.footnote_definitions.clear();
+        self.footnote_definitions_text.clear();
         #[cfg(not(feature = "sync"))]
         {
             self.parse_scratch.borrow_mut().clear();
@@ -78,10 +86,14 @@ impl PulldownAdapter {
         if update.reset {
             self.clear();
         }
+        if !update.removed.is_empty() {
+            self.remove_blocks(&update.removed);
+        }
         for block in &update.committed {
             self.committed_raw.insert(block.id, block.raw.clone());
             self.collect_reference_definitions(&block.raw);
             self.refresh_reference_definitions_text();
+            self.collect_footnote_definition(block);
             let events = self.parse_with_definitions(&block.raw);
             self.committed_cache.insert(block.id, events);
         }
@@ -111,18 +123,18 @@ impl PulldownAdapter {
     }
 
     fn parse_with_definitions(&self, raw: &str) -> Vec<Event<'static>> {
-        if self.reference_definitions_text.is_empty() {
+        let footnotes = !self.footnote_definitions_text.is_empty()
+            && self
+                .opts
+                .pulldown
+                .contains(PulldownOptions::ENABLE_FOOTNOTES);
+        if self.reference_definitions_text.is_empty() && !footnotes {
             return parse_events_static(raw, self.opts.pulldown);
         }
         #[cfg(not(feature = "sync"))]
         {
             let mut scratch = self.parse_scratch.borrow_mut();
-            scratch.clear();
-            scratch.reserve(self.reference_definitions_text.len() + 2 + raw.len());
-            scratch.push_str(&self.reference_definitions_text);
-            scratch.push_str("\n\n");
-            scratch.push_str(raw);
-            parse_events_static(&scratch, self.opts.pulldown)
+            self.parse_in_scratch(&mut scratch, raw, footnotes)
         }
         #[cfg(feature = "sync")]
         {
@@ -130,35 +142,116 @@ impl PulldownAdapter {
                 .parse_scratch
                 .lock()
                 .expect("mdstream: pulldown parse scratch mutex poisoned");
-            scratch.clear();
-            scratch.reserve(self.reference_definitions_text.len() + 2 + raw.len());
+            self.parse_in_scratch(&mut scratch, raw, footnotes)
+        }
+    }
+
+    fn parse_in_scratch(
+        &self,
+        scratch: &mut String,
+        raw: &str,
+        footnotes: bool,
+    ) -> Vec<Event<'static>> {
+        scratch.clear();
+        if !self.reference_definitions_text.is_empty() {
             scratch.push_str(&self.reference_definitions_text);
             scratch.push_str("\n\n");
-            scratch.push_str(raw);
-            parse_events_static(&scratch, self.opts.pulldown)
+        }
+        scratch.push_str(raw);
+        let raw_end = scratch.len();
+        if !footnotes {
+            return parse_events_static(scratch, self.opts.pulldown);
+        }
+        // Footnote definitions go after the block; they only resolve references, so their own
+        // events are dropped.
+        scratch.push_str("\n\n");
+        scratch.push_str(&self.footnote_definitions_text);
+        Parser::new_ext(scratch, self.opts.pulldown)
+            .into_offset_iter()
+            .filter(|(_, range)| range.start < raw_end)
+            .map(|(e, _)| e.into_static())
+            .collect()
+    }
+
+    fn remove_blocks(&mut self, ids: &[BlockId]) {
+        let mut footnotes_changed = false;
+        for id in ids {
+            self.committed_raw.remove(id);
+            self.committed_cache.remove(id);
+            footnotes_changed |= self.footnote_definitions.remove(id).is_some();
+        }
+        if footnotes_changed {
+            self.refresh_footnote_definitions_text();
+        }
+
+        // Definitions from removed blocks must not leak into later parses.
+        let mut remaining: Vec<(BlockId, String)> = self
+            .committed_raw
+            .iter()
+            .map(|(id, raw)| (*id, raw.clone()))
+            .collect();
+        remaining.sort_by_key(|(id, _)| id.0);
+        let before = std::mem::take(&mut self.reference_definitions);
+        for (_, raw) in &remaining {
+            self.collect_reference_definitions(raw);
+        }
+        if self.reference_definitions != before || footnotes_changed {
+            self.reference_definitions_dirty = true;
+            self.refresh_reference_definitions_text();
+            for (id, raw) in &remaining {
+                let events = self.parse_with_definitions(raw);
+                self.committed_cache.insert(*id, events);
+            }
+        } else {
+            self.reference_definitions_dirty = false;
         }
     }
 
     fn collect_reference_definitions(&mut self, raw: &str) {
-        // Best-effort: extract single-line reference definitions and keep the latest per label.
-        for line in raw.split('\n') {
-            if let Some((label, def_line)) = reference::extract_reference_definition_line(line) {
-                match self.reference_definitions.entry(label) {
-                    Entry::Vacant(v) => {
-                        v.insert(def_line);
+        // Best-effort: keep the latest definition per label, including multi-line ones.
+        for def in reference::extract_reference_definitions(raw) {
+            let source = def.source.trim_start();
+            match self.reference_definitions.entry(def.label) {
+                Entry::Vacant(v) => {
+                    v.insert(source.to_string());
+                    self.reference_definitions_dirty = true;
+                }
+                Entry::Occupied(mut o) => {
+                    if o.get() != source {
+                        o.insert(source.to_string());
                         self.reference_definitions_dirty = true;
                     }
-                    Entry::Occupied(mut o) => {
-                        if o.get() != &def_line {
-                            o.insert(def_line);
-                            self.reference_definitions_dirty = true;
-                        }
-                    }
                 }
             }
         }
     }
 
+    fn collect_footnote_definition(&mut self, block: &Block) {
+        if block.kind != BlockKind::FootnoteDefinition {
+            return;
+        }
+        let starts_definition = block
+            .raw
+            .lines()
+            .next()
+            .and_then(reference::extract_footnote_definition_label)
+            .is_some();
+        if starts_definition {
+            self.footnote_definitions
+                .insert(block.id, block.raw.trim_end().to_string());
+            self.refresh_footnote_definitions_text();
+        }
+    }
+
+    fn refresh_footnote_definitions_text(&mut self) {
+        self.footnote_definitions_text = self
+            .footnote_definitions
+            .values()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n\n");
+    }
+
     fn refresh_reference_definitions_text(&mut self) {
         if !self.reference_definitions_dirty {
             return;
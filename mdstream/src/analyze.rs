@@ -1,6 +1,11 @@
 use std::collections::HashMap;
 
-use crate::syntax::{is_code_fence_closing_line, parse_code_fence_header_from_block};
+use crate::boundary::parse_attributes;
+use crate::json::{PartialJsonValue, json_block_body, parse_partial_json};
+use crate::syntax::{
+    AlertKind, FrontMatterFormat, is_code_fence_closing_line, is_front_matter_close,
+    parse_alert_marker, parse_code_fence_header_from_block, parse_front_matter_open,
+};
 use crate::types::BlockStatus;
 use crate::types::{Block, BlockId, BlockKind, Update};
 use crate::{MdStream, Options};
@@ -170,7 +175,7 @@ pub struct CodeFenceMeta {
 pub struct CodeFenceAnalyzer;
 
 impl CodeFenceAnalyzer {
-    fn classify_language(language: Option<&str>) -> CodeFenceClass {
+    pub(crate) fn classify_language(language: Option<&str>) -> CodeFenceClass {
         let Some(lang) = language else {
             return CodeFenceClass::Other;
         };
@@ -199,6 +204,49 @@ impl BlockAnalyzer for CodeFenceAnalyzer {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialJsonMeta {
+    /// Best-effort value tree of the JSON seen so far.
+    pub value: PartialJsonValue,
+    /// Whether the whole value has arrived.
+    pub complete: bool,
+}
+
+/// Parses the body of JSON code fences (```` ```json ````, `jsonc`, ...) and of custom blocks
+/// named in `block_names` (e.g. `<tool_call>`) into a [`PartialJsonValue`], so structured
+/// arguments can be rendered while they stream. Text after the first value, such as the closing
+/// fence or tag, is ignored.
+#[derive(Debug, Default, Clone)]
+pub struct PartialJsonAnalyzer {
+    /// Names of [`BlockKind::Custom`] blocks whose content is JSON.
+    pub block_names: Vec<String>,
+}
+
+impl PartialJsonAnalyzer {
+    pub fn with_block_name(mut self, name: impl Into<String>) -> Self {
+        self.block_names.push(name.into());
+        self
+    }
+}
+
+impl BlockAnalyzer for PartialJsonAnalyzer {
+    type Meta = PartialJsonMeta;
+
+    fn analyze_block(&mut self, block: &Block) -> Option<Self::Meta> {
+        let body = json_block_body(
+            block.kind,
+            &block.raw,
+            block.custom.as_ref(),
+            &self.block_names,
+        )?;
+        let value = parse_partial_json(body)?;
+        Some(PartialJsonMeta {
+            complete: value.is_complete(),
+            value,
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MathMeta {
     pub balanced: bool,
@@ -240,6 +288,152 @@ impl BlockAnalyzer for MathAnalyzer {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontMatterMeta {
+    pub format: FrontMatterFormat,
+    pub closed: bool,
+    /// Top-level `key: value` (YAML) or `key = value` (TOML) pairs, in document order.
+    ///
+    /// Values are unquoted but otherwise kept as written; nested YAML structures are skipped.
+    /// TOML keys under a `[table]` header are prefixed with the table name (`table.key`).
+    /// While pending, an incomplete last line is ignored.
+    pub entries: Vec<(String, String)>,
+}
+
+impl FrontMatterMeta {
+    /// Value of the first entry named `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct FrontMatterAnalyzer;
+
+fn unquote(s: &str) -> &str {
+    let s = s.trim();
+    for q in ['"', '\''] {
+        if s.len() >= 2 && s.starts_with(q) && s.ends_with(q) {
+            return &s[1..s.len() - 1];
+        }
+    }
+    s
+}
+
+fn parse_yaml_entry(line: &str) -> Option<(String, String)> {
+    // Only top-level scalar mappings; indented lines, sequences and comments are skipped.
+    if line.starts_with([' ', '\t', '-', '#']) {
+        return None;
+    }
+    let (key, value) = match line.find(": ") {
+        Some(i) => (&line[..i], &line[i + 2..]),
+        None => (line.strip_suffix(':')?, ""),
+    };
+    let value = match value.find(" #") {
+        Some(i) if !value.trim_start().starts_with(['"', '\'']) => &value[..i],
+        _ => value,
+    };
+    Some((unquote(key).to_string(), unquote(value).to_string()))
+}
+
+fn parse_toml_entry(line: &str, table: &mut String) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    if let Some(name) = line.strip_prefix('[') {
+        *table = name.trim_matches(['[', ']']).trim().to_string();
+        return None;
+    }
+    let (key, value) = line.split_once('=')?;
+    let key = unquote(key);
+    let key = if table.is_empty() {
+        key.to_string()
+    } else {
+        format!("{table}.{key}")
+    };
+    Some((key, unquote(value).to_string()))
+}
+
+impl BlockAnalyzer for FrontMatterAnalyzer {
+    type Meta = FrontMatterMeta;
+
+    fn analyze_block(&mut self, block: &Block) -> Option<Self::Meta> {
+        if block.kind != BlockKind::FrontMatter {
+            return None;
+        }
+        let mut lines = block.raw.split_inclusive('\n');
+        let format = parse_front_matter_open(lines.next()?)?;
+
+        let mut closed = false;
+        let mut table = String::new();
+        let mut entries = Vec::new();
+        for line in lines {
+            let line = match line.strip_suffix('\n') {
+                Some(line) => line,
+                // Incomplete tail of a pending block: the key or delimiter may still grow.
+                None if block.status == BlockStatus::Pending => break,
+                None => line,
+            };
+            if is_front_matter_close(line, format) {
+                closed = true;
+                break;
+            }
+            let entry = match format {
+                FrontMatterFormat::Yaml => parse_yaml_entry(line),
+                FrontMatterFormat::Toml => parse_toml_entry(line, &mut table),
+            };
+            entries.extend(entry);
+        }
+        Some(FrontMatterMeta {
+            format,
+            closed,
+            entries,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertMeta {
+    pub kind: AlertKind,
+    /// Custom title after the marker (`> [!TIP] Title`), if any.
+    pub title: Option<String>,
+    /// `false` while the blockquote is still pending.
+    pub closed: bool,
+}
+
+/// Classifies GitHub alert blockquotes (`> [!NOTE]`, `> [!WARNING]`, ...).
+///
+/// Meta is reported once the first line is complete. Pair with
+/// [`crate::AlertMarkerTransformer`] to keep the pending display from showing the marker as
+/// plain quote text before that.
+#[derive(Debug, Default, Clone)]
+pub struct AlertAnalyzer;
+
+impl BlockAnalyzer for AlertAnalyzer {
+    type Meta = AlertMeta;
+
+    fn analyze_block(&mut self, block: &Block) -> Option<Self::Meta> {
+        if block.kind != BlockKind::BlockQuote {
+            return None;
+        }
+        let first_line = match block.raw.split_once('\n') {
+            Some((line, _)) => line,
+            None if block.status == BlockStatus::Committed => block.raw.as_str(),
+            None => return None,
+        };
+        let (kind, title) = parse_alert_marker(first_line)?;
+        Some(AlertMeta {
+            kind,
+            title: title.map(str::to_string),
+            closed: block.status == BlockStatus::Committed,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BlockHintMeta {
     pub flags: u32,
@@ -293,10 +487,8 @@ impl BlockAnalyzer for BlockHintAnalyzer {
         }
 
         match block.kind {
-            BlockKind::CodeFence => {
-                if !code_fence_is_closed(&block.raw) {
-                    flags |= BlockHintMeta::UNCLOSED_CODE_FENCE;
-                }
+            BlockKind::CodeFence if !code_fence_is_closed(&block.raw) => {
+                flags |= BlockHintMeta::UNCLOSED_CODE_FENCE;
             }
             BlockKind::MathBlock => {
                 let count = count_double_dollars_unescaped(&block.raw);
@@ -314,7 +506,8 @@ impl BlockAnalyzer for BlockHintAnalyzer {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TaggedBlockMeta {
     pub tag: String,
-    pub attributes: Option<String>,
+    /// Attributes of the opening tag, in source order. Valueless attributes map to `""`.
+    pub attributes: Vec<(String, String)>,
     pub closed: bool,
     /// Raw content between the opening/closing tag lines.
     ///
@@ -343,10 +536,7 @@ fn custom_tag_name_char(b: u8) -> bool {
     b.is_ascii_alphanumeric() || b == b'-' || b == b'_' || b == b':'
 }
 
-fn parse_custom_opening_tag(
-    line: &str,
-    case_insensitive: bool,
-) -> Option<(String, Option<String>)> {
+fn parse_custom_opening_tag(line: &str, case_insensitive: bool) -> Option<(String, &str)> {
     let s = line.trim_start();
     if !s.starts_with('<') || s.starts_with("</") {
         return None;
@@ -365,13 +555,7 @@ fn parse_custom_opening_tag(
     if case_insensitive {
         name = name.to_ascii_lowercase();
     }
-    let attrs = inside[name_end..].trim();
-    let attrs = if attrs.is_empty() {
-        None
-    } else {
-        Some(attrs.to_string())
-    };
-    Some((name, attrs))
+    Some((name, &inside[name_end..]))
 }
 
 fn is_custom_closing_tag(line: &str, tag: &str, case_insensitive: bool) -> bool {
@@ -439,7 +623,15 @@ impl BlockAnalyzer for TaggedBlockAnalyzer {
     fn analyze_block(&mut self, block: &Block) -> Option<Self::Meta> {
         // Only consider blocks whose first line looks like an opening custom tag.
         let first_line = block.raw.split('\n').next().unwrap_or(&block.raw);
-        let (tag, attrs) = parse_custom_opening_tag(first_line, self.case_insensitive)?;
+        let (literal, raw_attrs) = parse_custom_opening_tag(first_line, self.case_insensitive)?;
+        // Blocks declared by a boundary plugin already carry their name and attributes.
+        let (tag, attrs) = match &block.custom {
+            Some(custom) if self.case_insensitive => {
+                (custom.name.to_ascii_lowercase(), custom.attributes.clone())
+            }
+            Some(custom) => (custom.name.clone(), custom.attributes.clone()),
+            None => (literal.clone(), parse_attributes(raw_attrs)),
+        };
 
         if let Some(allowed) = &self.allowed_tags {
             if !allowed.iter().any(|t| {
@@ -453,7 +645,8 @@ impl BlockAnalyzer for TaggedBlockAnalyzer {
             }
         }
 
-        let (closed, content) = split_tag_block_content(&block.raw, &tag, self.case_insensitive);
+        let (closed, content) =
+            split_tag_block_content(&block.raw, &literal, self.case_insensitive);
         Some(TaggedBlockMeta {
             tag,
             attributes: attrs,
@@ -1,3 +1,5 @@
+use crate::types::CustomBlock;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BoundaryUpdate {
     Continue,
@@ -18,6 +20,14 @@ pub trait BoundaryPlugin: Send + Sync {
     /// This method must not mutate internal state.
     fn matches_start(&self, line: &str) -> bool;
 
+    /// Kind name and attributes of a block starting at `line`, once `matches_start` accepted it.
+    ///
+    /// Returning `Some` reports the block as [`crate::BlockKind::Custom`]; the default keeps it
+    /// [`crate::BlockKind::Unknown`]. Like `matches_start`, this must not mutate internal state.
+    fn custom_block(&self, _line: &str) -> Option<CustomBlock> {
+        None
+    }
+
     /// Called exactly once when the current block is determined to start at `line`.
     fn start(&mut self, line: &str);
 
@@ -27,6 +37,23 @@ pub trait BoundaryPlugin: Send + Sync {
     fn update(&mut self, line: &str) -> BoundaryUpdate;
 
     fn reset(&mut self) {}
+
+    /// Capture internal state for [`crate::MdStream::checkpoint`].
+    ///
+    /// Return `None` (the default) for stateless plugins; they are `reset` on restore. Snapshots
+    /// may outlive the process, so a state format that can change should carry a version that
+    /// `restore` checks (the built-in plugins start theirs with `v1`).
+    fn checkpoint(&self) -> Option<String> {
+        None
+    }
+
+    /// Restore state previously returned by [`BoundaryPlugin::checkpoint`].
+    ///
+    /// Return `false` if `state` cannot be parsed; [`crate::MdStream::restore`] then fails with
+    /// [`crate::RestoreError::Corrupt`].
+    fn restore(&mut self, _state: &str) -> bool {
+        true
+    }
 }
 
 #[cfg(not(feature = "sync"))]
@@ -36,6 +63,14 @@ pub trait BoundaryPlugin: Send {
     /// This method must not mutate internal state.
     fn matches_start(&self, line: &str) -> bool;
 
+    /// Kind name and attributes of a block starting at `line`, once `matches_start` accepted it.
+    ///
+    /// Returning `Some` reports the block as [`crate::BlockKind::Custom`]; the default keeps it
+    /// [`crate::BlockKind::Unknown`]. Like `matches_start`, this must not mutate internal state.
+    fn custom_block(&self, _line: &str) -> Option<CustomBlock> {
+        None
+    }
+
     /// Called exactly once when the current block is determined to start at `line`.
     fn start(&mut self, line: &str);
 
@@ -45,10 +80,29 @@ pub trait BoundaryPlugin: Send {
     fn update(&mut self, line: &str) -> BoundaryUpdate;
 
     fn reset(&mut self) {}
+
+    /// Capture internal state for [`crate::MdStream::checkpoint`].
+    ///
+    /// Return `None` (the default) for stateless plugins; they are `reset` on restore. Snapshots
+    /// may outlive the process, so a state format that can change should carry a version that
+    /// `restore` checks (the built-in plugins start theirs with `v1`).
+    fn checkpoint(&self) -> Option<String> {
+        None
+    }
+
+    /// Restore state previously returned by [`BoundaryPlugin::checkpoint`].
+    ///
+    /// Return `false` if `state` cannot be parsed; [`crate::MdStream::restore`] then fails with
+    /// [`crate::RestoreError::Corrupt`].
+    fn restore(&mut self, _state: &str) -> bool {
+        true
+    }
 }
 
 type MatchStartFn = dyn Fn(&str) -> bool + Send + Sync;
 
+type CustomBlockFn = dyn Fn(&str) -> Option<CustomBlock> + Send + Sync;
+
 #[cfg(feature = "sync")]
 type StartFn = dyn FnMut(&str) + Send + Sync;
 #[cfg(not(feature = "sync"))]
@@ -72,6 +126,7 @@ type ResetFn = dyn FnMut() + Send;
 /// - If you need state, capture it in the `FnMut` closures.
 pub struct FnBoundaryPlugin {
     matches_start: Box<MatchStartFn>,
+    custom_block: Option<Box<CustomBlockFn>>,
     start: Option<Box<StartFn>>,
     update: Box<UpdateFn>,
     reset: Option<Box<ResetFn>>,
@@ -86,6 +141,7 @@ impl FnBoundaryPlugin {
     {
         Self {
             matches_start: Box::new(matches_start),
+            custom_block: None,
             start: None,
             update: Box::new(update),
             reset: None,
@@ -100,12 +156,22 @@ impl FnBoundaryPlugin {
     {
         Self {
             matches_start: Box::new(matches_start),
+            custom_block: None,
             start: None,
             update: Box::new(update),
             reset: None,
         }
     }
 
+    /// Declare a kind for the blocks this plugin claims (see [`BoundaryPlugin::custom_block`]).
+    pub fn with_custom_block<C>(mut self, custom_block: C) -> Self
+    where
+        C: Fn(&str) -> Option<CustomBlock> + Send + Sync + 'static,
+    {
+        self.custom_block = Some(Box::new(custom_block));
+        self
+    }
+
     #[cfg(not(feature = "sync"))]
     pub fn with_start<S>(mut self, start: S) -> Self
     where
@@ -148,6 +214,10 @@ impl BoundaryPlugin for FnBoundaryPlugin {
         (self.matches_start)(line)
     }
 
+    fn custom_block(&self, line: &str) -> Option<CustomBlock> {
+        self.custom_block.as_ref().and_then(|f| (f)(line))
+    }
+
     fn start(&mut self, line: &str) {
         if let Some(f) = self.start.as_mut() {
             (f)(line);
@@ -175,6 +245,80 @@ fn strip_up_to_three_leading_spaces(line: &str) -> &str {
     s
 }
 
+/// Format version leading the checkpointed state of the built-in plugins.
+const STATE_VERSION: &str = "v1";
+
+/// Space-separated fields of a checkpointed state, or `None` if it has another format version.
+fn state_fields(state: &str) -> Option<std::str::Split<'_, char>> {
+    let mut parts = state.split(' ');
+    (parts.next() == Some(STATE_VERSION)).then_some(parts)
+}
+
+/// Parse a checkpointed `Option<usize>`, written as `-` for `None`.
+fn parse_optional_len(s: Option<&str>) -> Option<Option<usize>> {
+    match s? {
+        "-" => Some(None),
+        n => n.parse().ok().map(Some),
+    }
+}
+
+/// Parse a checkpointed flag, written as `0` or `1`.
+fn parse_flag(s: Option<&str>) -> Option<bool> {
+    match s? {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    }
+}
+
+/// Parse `name="value" name='value' name=value name` attribute lists. A list wrapped in braces
+/// (`{...}`, as in directives) is unwrapped first.
+pub(crate) fn parse_attributes(s: &str) -> Vec<(String, String)> {
+    let s = s.trim();
+    let s = s
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .unwrap_or(s);
+    let is_name_end = |c: char| c.is_whitespace() || c == '=' || c == '/' || c == '>';
+    let mut attributes = Vec::new();
+    let mut rest = s;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            break;
+        }
+        let name_end = rest.find(is_name_end).unwrap_or(rest.len());
+        if name_end == 0 {
+            // A stray `=` or `>`.
+            rest = &rest[1..];
+            continue;
+        }
+        let name = &rest[..name_end];
+        rest = rest[name_end..].trim_start();
+        let Some(after_eq) = rest.strip_prefix('=') else {
+            attributes.push((name.to_string(), String::new()));
+            continue;
+        };
+        rest = after_eq.trim_start();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let body = &rest[1..];
+                let end = body.find(quote).unwrap_or(body.len());
+                rest = body.get(end + 1..).unwrap_or("");
+                &body[..end]
+            }
+            _ => {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let value = &rest[..end];
+                rest = &rest[end..];
+                value
+            }
+        };
+        attributes.push((name.to_string(), value.to_string()));
+    }
+    attributes
+}
+
 /// A simple fence-like container plugin.
 ///
 /// Typical usage is directives such as:
@@ -255,6 +399,23 @@ impl BoundaryPlugin for FenceBoundaryPlugin {
         self.fence_len_at_start(line) >= self.min_len
     }
 
+    /// `:::warning title="Careful"` is reported as `warning` with a `title` attribute; a bare
+    /// fence has no kind.
+    fn custom_block(&self, line: &str) -> Option<CustomBlock> {
+        let len = self.fence_len_at_start(line);
+        let info = strip_up_to_three_leading_spaces(line)[len..].trim();
+        let name_end = info
+            .find(|c: char| c.is_whitespace() || c == '{')
+            .unwrap_or(info.len());
+        if name_end == 0 {
+            return None;
+        }
+        Some(CustomBlock {
+            name: info[..name_end].to_string(),
+            attributes: parse_attributes(&info[name_end..]),
+        })
+    }
+
     fn start(&mut self, line: &str) {
         let len = self.fence_len_at_start(line);
         if len >= self.min_len {
@@ -285,6 +446,32 @@ impl BoundaryPlugin for FenceBoundaryPlugin {
         self.opened_len = None;
         self.just_started = false;
     }
+
+    fn checkpoint(&self) -> Option<String> {
+        let opened = self
+            .opened_len
+            .map_or_else(|| "-".to_string(), |n| n.to_string());
+        Some(format!(
+            "{STATE_VERSION} {opened} {}",
+            u8::from(self.just_started)
+        ))
+    }
+
+    fn restore(&mut self, state: &str) -> bool {
+        let Some(mut parts) = state_fields(state) else {
+            return false;
+        };
+        let (Some(opened_len), Some(just_started), None) = (
+            parse_optional_len(parts.next()),
+            parse_flag(parts.next()),
+            parts.next(),
+        ) else {
+            return false;
+        };
+        self.opened_len = opened_len;
+        self.just_started = just_started;
+        true
+    }
 }
 
 /// A paired-tag container plugin.
@@ -339,23 +526,22 @@ impl TagBoundaryPlugin {
         }
     }
 
-    fn matches_opening(&self, line: &str) -> bool {
+    /// Returns the attribute text of an opening tag line.
+    fn parse_opening<'a>(&self, line: &'a str) -> Option<&'a str> {
         let s = strip_up_to_three_leading_spaces(line).trim_end();
         if !s.starts_with('<') {
-            return false;
+            return None;
         }
         // Require the tag to be complete on this line.
-        let Some(gt) = s.find('>') else {
-            return false;
-        };
+        let gt = s.find('>')?;
         let inside = &s[1..gt];
         if inside.starts_with('/') || inside.starts_with('!') || inside.starts_with('?') {
-            return false;
+            return None;
         }
 
         let bytes = inside.as_bytes();
         if bytes.is_empty() || !bytes[0].is_ascii_alphabetic() {
-            return false;
+            return None;
         }
         let mut name_end = 1usize;
         while name_end < bytes.len() && Self::is_tag_name_char(bytes[name_end]) {
@@ -365,17 +551,14 @@ impl TagBoundaryPlugin {
         let name = self.norm_tag(name);
         let want = self.norm_tag(self.tag.as_str());
         if name != want {
-            return false;
+            return None;
         }
 
         let rest = inside[name_end..].trim();
-        if rest.is_empty() {
-            return true;
-        }
-        if !self.allow_attributes {
-            return false;
+        if !rest.is_empty() && !self.allow_attributes {
+            return None;
         }
-        true
+        Some(rest)
     }
 
     fn matches_closing(&self, line: &str) -> bool {
@@ -410,7 +593,16 @@ impl TagBoundaryPlugin {
 
 impl BoundaryPlugin for TagBoundaryPlugin {
     fn matches_start(&self, line: &str) -> bool {
-        self.matches_opening(line)
+        self.parse_opening(line).is_some()
+    }
+
+    /// Reported under the configured `tag`, with the opening tag's attributes.
+    fn custom_block(&self, line: &str) -> Option<CustomBlock> {
+        let attributes = self.parse_opening(line)?;
+        Some(CustomBlock {
+            name: self.tag.clone(),
+            attributes: parse_attributes(attributes),
+        })
     }
 
     fn start(&mut self, _line: &str) {
@@ -431,12 +623,29 @@ impl BoundaryPlugin for TagBoundaryPlugin {
     fn reset(&mut self) {
         self.active = false;
     }
+
+    fn checkpoint(&self) -> Option<String> {
+        Some(format!("{STATE_VERSION} {}", u8::from(self.active)))
+    }
+
+    fn restore(&mut self, state: &str) -> bool {
+        let Some(mut parts) = state_fields(state) else {
+            return false;
+        };
+        let (Some(active), None) = (parse_flag(parts.next()), parts.next()) else {
+            return false;
+        };
+        self.active = active;
+        true
+    }
 }
 
 #[derive(Debug, Clone)]
-struct ContainerMatch {
+struct ContainerMatch<'a> {
     marker_length: usize,
     is_end: bool,
+    name: &'a str,
+    attributes: &'a str,
 }
 
 fn is_container_name_start(b: u8) -> bool {
@@ -486,7 +695,7 @@ impl ContainerBoundaryPlugin {
         }
     }
 
-    fn detect_container(&self, line: &str) -> Option<ContainerMatch> {
+    fn detect_container<'a>(&self, line: &'a str) -> Option<ContainerMatch<'a>> {
         // Equivalent to Incremark's:
         // ^(\s*)(:{3,})(?:\s+(\w[\w-]*))?(?:\s+(.*))?\s*$
         let s = line.trim_end();
@@ -506,6 +715,8 @@ impl ContainerBoundaryPlugin {
             return Some(ContainerMatch {
                 marker_length,
                 is_end: true,
+                name: "",
+                attributes: "",
             });
         }
 
@@ -532,11 +743,7 @@ impl ContainerBoundaryPlugin {
             }
         }
 
-        let name = if name_end > 0 {
-            rest[..name_end].to_string()
-        } else {
-            String::new()
-        };
+        let name = &rest[..name_end];
 
         let attrs = rest[name_end..].trim();
         let has_attrs = !attrs.is_empty();
@@ -547,7 +754,7 @@ impl ContainerBoundaryPlugin {
         let is_end = name.is_empty() && !has_attrs;
         if !is_end {
             if let Some(allowed) = &self.allowed_names {
-                if !allowed.is_empty() && !allowed.iter().any(|n| n == &name) {
+                if !allowed.is_empty() && !allowed.iter().any(|n| n == name) {
                     return None;
                 }
             }
@@ -556,6 +763,8 @@ impl ContainerBoundaryPlugin {
         Some(ContainerMatch {
             marker_length,
             is_end,
+            name,
+            attributes: attrs,
         })
     }
 }
@@ -565,6 +774,19 @@ impl BoundaryPlugin for ContainerBoundaryPlugin {
         self.detect_container(line).is_some_and(|m| !m.is_end)
     }
 
+    /// `::: warning {title="Careful"}` is reported as `warning` with a `title` attribute; a
+    /// container without a name has no kind.
+    fn custom_block(&self, line: &str) -> Option<CustomBlock> {
+        let m = self.detect_container(line)?;
+        if m.is_end || m.name.is_empty() {
+            return None;
+        }
+        Some(CustomBlock {
+            name: m.name.to_string(),
+            attributes: parse_attributes(m.attributes),
+        })
+    }
+
     fn start(&mut self, line: &str) {
         let Some(m) = self.detect_container(line) else {
             self.base_marker_length = None;
@@ -616,4 +838,33 @@ impl BoundaryPlugin for ContainerBoundaryPlugin {
         self.depth = 0;
         self.just_started = false;
     }
+
+    fn checkpoint(&self) -> Option<String> {
+        let base = self
+            .base_marker_length
+            .map_or_else(|| "-".to_string(), |n| n.to_string());
+        Some(format!(
+            "{STATE_VERSION} {base} {} {}",
+            self.depth,
+            u8::from(self.just_started)
+        ))
+    }
+
+    fn restore(&mut self, state: &str) -> bool {
+        let Some(mut parts) = state_fields(state) else {
+            return false;
+        };
+        let (Some(base_marker_length), Some(Ok(depth)), Some(just_started), None) = (
+            parse_optional_len(parts.next()),
+            parts.next().map(str::parse),
+            parse_flag(parts.next()),
+            parts.next(),
+        ) else {
+            return false;
+        };
+        self.base_marker_length = base_marker_length;
+        self.depth = depth;
+        self.just_started = just_started;
+        true
+    }
 }
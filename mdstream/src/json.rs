@@ -0,0 +1,471 @@
+//! Best-effort handling of JSON that is still being streamed (e.g. tool call arguments).
+//!
+//! [`repair_partial_json`] closes what is still open so the text parses; [`parse_partial_json`]
+//! turns the same prefix into a [`PartialJsonValue`] tree that records which parts are complete.
+//! Only the first JSON value in the text is considered; anything after it is ignored.
+
+use crate::analyze::{CodeFenceAnalyzer, CodeFenceClass};
+use crate::syntax::parse_code_fence_header_from_block;
+use crate::types::{BlockKind, CustomBlock};
+
+/// A JSON value parsed from a possibly incomplete prefix.
+///
+/// `complete` is `false` for strings, arrays and objects whose closing delimiter has not
+/// arrived yet. Object members whose value has not started yet are left out, and a trailing
+/// partial literal is completed (`tr` → `true`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartialJsonValue {
+    Null,
+    Bool(bool),
+    /// The number as written, e.g. `-1.5e3`.
+    Number(String),
+    String {
+        value: String,
+        complete: bool,
+    },
+    Array {
+        items: Vec<PartialJsonValue>,
+        complete: bool,
+    },
+    Object {
+        members: Vec<(String, PartialJsonValue)>,
+        complete: bool,
+    },
+}
+
+impl PartialJsonValue {
+    /// Whether this value and everything inside it is complete.
+    pub fn is_complete(&self) -> bool {
+        match self {
+            Self::Null | Self::Bool(_) | Self::Number(_) => true,
+            Self::String { complete, .. } => *complete,
+            Self::Array { items, complete } => *complete && items.iter().all(Self::is_complete),
+            Self::Object { members, complete } => {
+                *complete && members.iter().all(|(_, v)| v.is_complete())
+            }
+        }
+    }
+
+    /// Value of the first member called `key`, for objects.
+    pub fn get(&self, key: &str) -> Option<&PartialJsonValue> {
+        match self {
+            Self::Object { members, .. } => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Body of a block holding JSON: a code fence whose language classifies as JSON, or a custom
+/// block named in `block_names`. The body is everything after the first (complete) line.
+pub(crate) fn json_block_body<'a>(
+    kind: BlockKind,
+    raw: &'a str,
+    custom: Option<&CustomBlock>,
+    block_names: &[String],
+) -> Option<&'a str> {
+    let is_json = match kind {
+        BlockKind::CodeFence => parse_code_fence_header_from_block(raw).is_some_and(|header| {
+            CodeFenceAnalyzer::classify_language(header.language) == CodeFenceClass::Json
+        }),
+        BlockKind::Custom => custom.is_some_and(|c| block_names.contains(&c.name)),
+        _ => false,
+    };
+    if !is_json {
+        return None;
+    }
+    let newline = raw.find('\n')?;
+    Some(&raw[newline + 1..])
+}
+
+/// Close the open strings, arrays and objects of the first JSON value in `text`.
+///
+/// Dangling commas, object members without a value and incomplete escapes are dropped, and a
+/// trailing partial literal or number is completed or trimmed. Text holding a complete value (or
+/// no value at all) is returned unchanged.
+pub fn repair_partial_json(text: &str) -> String {
+    match scan(text) {
+        Some(scan) if !scan.complete => {
+            let mut out = String::with_capacity(scan.end + scan.suffix.len());
+            out.push_str(&text[..scan.end]);
+            out.push_str(&scan.suffix);
+            out
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Parse the first JSON value in `text`, which may be cut off anywhere.
+///
+/// Returns `None` if `text` does not start with a JSON value (after whitespace).
+pub fn parse_partial_json(text: &str) -> Option<PartialJsonValue> {
+    let scan = scan(text)?;
+    let mut repaired = String::with_capacity(scan.end - scan.start + scan.suffix.len());
+    repaired.push_str(&text[scan.start..scan.end]);
+    repaired.push_str(&scan.suffix);
+    let mut parser = Parser {
+        text: &repaired,
+        pos: 0,
+        original_len: scan.end - scan.start,
+    };
+    parser.value()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Object: before a key (or the closing brace).
+    Key,
+    /// Object: after a key.
+    Colon,
+    /// Before a value: after a colon, or inside an array.
+    Value,
+    AfterValue,
+}
+
+#[derive(Debug)]
+struct Frame {
+    object: bool,
+    state: State,
+    /// Where the current member starts, including its leading comma: cutting here drops it.
+    member_start: usize,
+}
+
+#[derive(Debug)]
+struct Scan {
+    /// Start of the value.
+    start: usize,
+    /// Bytes of `text` to keep.
+    end: usize,
+    /// Text that closes what is still open after `end`.
+    suffix: String,
+    /// The value ended within `text` (`end` is right after it).
+    complete: bool,
+}
+
+fn scan(text: &str) -> Option<Scan> {
+    let bytes = text.as_bytes();
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace())?;
+    let complete = |end: usize| {
+        Some(Scan {
+            start,
+            end,
+            suffix: String::new(),
+            complete: true,
+        })
+    };
+
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut in_string = false;
+    let mut string_is_key = false;
+    let mut string_start = 0usize;
+    let mut escape = false;
+    let mut scalar_start: Option<usize> = None;
+
+    let mut i = start;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if in_string {
+            if escape {
+                escape = false;
+            } else if b == b'\\' {
+                escape = true;
+            } else if b == b'"' {
+                in_string = false;
+                match frames.last_mut() {
+                    Some(top) if string_is_key => top.state = State::Colon,
+                    Some(top) => top.state = State::AfterValue,
+                    None => return complete(i + 1),
+                }
+            }
+            i += 1;
+            continue;
+        }
+        if scalar_start.is_some() {
+            if !is_delimiter(b) {
+                i += 1;
+                continue;
+            }
+            scalar_start = None;
+            match frames.last_mut() {
+                Some(top) => top.state = State::AfterValue,
+                None => return complete(i),
+            }
+        }
+        match b {
+            b' ' | b'\t' | b'\r' | b'\n' => {}
+            b'{' | b'[' => frames.push(Frame {
+                object: b == b'{',
+                state: if b == b'{' { State::Key } else { State::Value },
+                member_start: i + 1,
+            }),
+            b'}' | b']' => {
+                frames.pop();
+                match frames.last_mut() {
+                    Some(top) => top.state = State::AfterValue,
+                    None => return complete(i + 1),
+                }
+            }
+            b'"' => {
+                in_string = true;
+                string_start = i;
+                string_is_key = frames
+                    .last()
+                    .is_some_and(|top| top.object && top.state == State::Key);
+            }
+            b':' => {
+                if let Some(top) = frames.last_mut() {
+                    top.state = State::Value;
+                }
+            }
+            b',' => {
+                if let Some(top) = frames.last_mut() {
+                    top.state = if top.object { State::Key } else { State::Value };
+                    top.member_start = i;
+                }
+            }
+            _ => scalar_start = Some(i),
+        }
+        i += 1;
+    }
+
+    // The text ends inside the value: decide what to keep of the last member, then close.
+    let mut end = bytes.len();
+    let mut suffix = String::new();
+    let mut drop_member = false;
+    if in_string {
+        if string_is_key {
+            drop_member = true;
+        } else {
+            end = string_end_without_partial_escape(text, string_start, escape);
+            suffix.push('"');
+        }
+    } else if let Some(s0) = scalar_start {
+        let token = &text[s0..];
+        if let Some(literal) = ["true", "false", "null"]
+            .into_iter()
+            .find(|l| l.starts_with(token))
+        {
+            suffix.push_str(&literal[token.len()..]);
+        } else {
+            let number = token.trim_end_matches(['-', '+', '.', 'e', 'E']);
+            if number.bytes().any(|b| b.is_ascii_digit()) {
+                end = s0 + number.len();
+            } else {
+                drop_member = true;
+            }
+        }
+    } else if frames
+        .last()
+        .is_some_and(|top| top.state != State::AfterValue)
+    {
+        drop_member = true;
+    }
+    if drop_member {
+        end = frames.last()?.member_start;
+    }
+    for frame in frames.iter().rev() {
+        suffix.push(if frame.object { '}' } else { ']' });
+    }
+    Some(Scan {
+        start,
+        end,
+        suffix,
+        complete: false,
+    })
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n' | b',' | b'}' | b']' | b':')
+}
+
+/// End of an unterminated string's text, without a trailing backslash or `\u` escape that is
+/// still missing hex digits.
+fn string_end_without_partial_escape(text: &str, string_start: usize, escape: bool) -> usize {
+    let len = text.len();
+    if escape {
+        return len - 1;
+    }
+    let body = &text.as_bytes()[string_start + 1..];
+    let Some(u) = body.iter().rposition(|&b| b == b'u') else {
+        return len;
+    };
+    let hex = &body[u + 1..];
+    if hex.len() >= 4 || !hex.iter().all(u8::is_ascii_hexdigit) {
+        return len;
+    }
+    let backslashes = body[..u].iter().rev().take_while(|&&b| b == b'\\').count();
+    if backslashes % 2 == 1 {
+        string_start + 1 + u - 1
+    } else {
+        len
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    /// Bytes of `text` taken from the input; the rest was added by the repair.
+    original_len: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consume the closing delimiter at `pos`; returns whether it came from the input.
+    fn close(&mut self) -> bool {
+        let original = self.pos < self.original_len;
+        self.pos += 1;
+        original
+    }
+
+    fn value(&mut self) -> Option<PartialJsonValue> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'{' => self.object(),
+            b'[' => self.array(),
+            b'"' => {
+                let (value, complete) = self.string()?;
+                Some(PartialJsonValue::String { value, complete })
+            }
+            b't' => self.literal("true", PartialJsonValue::Bool(true)),
+            b'f' => self.literal("false", PartialJsonValue::Bool(false)),
+            b'n' => self.literal("null", PartialJsonValue::Null),
+            _ => self.number(),
+        }
+    }
+
+    fn object(&mut self) -> Option<PartialJsonValue> {
+        self.pos += 1;
+        let mut members = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                b'}' => {
+                    let complete = self.close();
+                    return Some(PartialJsonValue::Object { members, complete });
+                }
+                b',' => self.pos += 1,
+                b'"' => {
+                    let (key, _) = self.string()?;
+                    self.skip_whitespace();
+                    if self.peek()? != b':' {
+                        return None;
+                    }
+                    self.pos += 1;
+                    let value = self.value()?;
+                    members.push((key, value));
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self) -> Option<PartialJsonValue> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                b']' => {
+                    let complete = self.close();
+                    return Some(PartialJsonValue::Array { items, complete });
+                }
+                b',' => self.pos += 1,
+                _ => items.push(self.value()?),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<(String, bool)> {
+        self.pos += 1;
+        let mut out = String::new();
+        let mut chars = self.text[self.pos..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += offset;
+                    let complete = self.close();
+                    return Some((out, complete));
+                }
+                '\\' => {
+                    let (_, escaped) = chars.next()?;
+                    match escaped {
+                        'n' => out.push('\n'),
+                        't' => out.push('\t'),
+                        'r' => out.push('\r'),
+                        'b' => out.push('\u{8}'),
+                        'f' => out.push('\u{c}'),
+                        'u' => {
+                            let mut unit = hex_unit(&mut chars)?;
+                            if (0xD800..0xDC00).contains(&unit) {
+                                // A surrogate pair spans two escapes.
+                                let mut lookahead = chars.clone();
+                                if let (Some((_, '\\')), Some((_, 'u'))) =
+                                    (lookahead.next(), lookahead.next())
+                                {
+                                    if let Some(low @ 0xDC00..0xE000) = hex_unit(&mut lookahead) {
+                                        unit = 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00);
+                                        chars = lookahead;
+                                    }
+                                }
+                            }
+                            out.push(char::from_u32(unit).unwrap_or(char::REPLACEMENT_CHARACTER));
+                        }
+                        other => out.push(other),
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+        None
+    }
+
+    fn literal(&mut self, word: &str, value: PartialJsonValue) -> Option<PartialJsonValue> {
+        if !self.text[self.pos..].starts_with(word) {
+            return None;
+        }
+        self.pos += word.len();
+        Some(value)
+    }
+
+    fn number(&mut self) -> Option<PartialJsonValue> {
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return None;
+        }
+        self.pos += len;
+        Some(PartialJsonValue::Number(rest[..len].to_string()))
+    }
+}
+
+fn hex_unit(chars: &mut std::str::CharIndices<'_>) -> Option<u32> {
+    let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+    if hex.len() != 4 {
+        return None;
+    }
+    u32::from_str_radix(&hex, 16).ok()
+}
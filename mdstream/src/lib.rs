@@ -1,11 +1,15 @@
 pub mod analyze;
 pub mod boundary;
+pub mod json;
+pub mod observer;
 pub mod options;
 pub mod pending;
 mod reference;
+pub mod session;
 pub mod state;
 pub mod stream;
 pub mod syntax;
+pub mod testing;
 pub mod transform;
 pub mod types;
 
@@ -14,7 +18,10 @@ pub mod adapters;
 
 pub use analyze::*;
 pub use boundary::*;
+pub use json::*;
+pub use observer::*;
 pub use options::*;
+pub use session::*;
 pub use state::*;
 pub use stream::*;
 pub use syntax::*;
@@ -0,0 +1,55 @@
+use crate::types::{Block, BlockId, PendingBlockRef};
+
+/// Side-effect hooks called by [`crate::MdStream`] while it processes input.
+///
+/// Observers see the same events as the returned [`crate::Update`]s, without diffing them:
+/// logging, analytics, or starting expensive work (e.g. diagram rendering) as soon as a block
+/// commits. All methods default to no-ops; borrowed arguments are only valid during the call.
+///
+/// `truncate_to` notifies observers once it is done, from its returned `Update`: dropped blocks
+/// arrive through `on_remove`, their replacements through `on_commit`.
+#[cfg(feature = "sync")]
+pub trait StreamObserver: Send + Sync {
+    /// A block was committed; it will not change again (until a reset or truncation).
+    fn on_commit(&mut self, _block: &Block) {}
+
+    /// New input changed the pending block.
+    fn on_pending_changed(&mut self, _pending: PendingBlockRef<'_>) {}
+
+    /// Committed blocks that should be re-parsed (see [`crate::Update::invalidated`]).
+    fn on_invalidate(&mut self, _ids: &[BlockId]) {}
+
+    /// Committed blocks dropped by [`crate::MdStream::truncate_to`] (see
+    /// [`crate::Update::removed`]); cancel any work started for them in `on_commit`.
+    fn on_remove(&mut self, _ids: &[BlockId]) {}
+
+    /// All committed blocks were dropped (see [`crate::Update::reset`]), or
+    /// [`crate::MdStream::reset`] was called.
+    fn on_reset(&mut self) {}
+
+    /// `finalize` committed the remaining input.
+    fn on_finalize(&mut self) {}
+}
+
+#[cfg(not(feature = "sync"))]
+pub trait StreamObserver: Send {
+    /// A block was committed; it will not change again (until a reset or truncation).
+    fn on_commit(&mut self, _block: &Block) {}
+
+    /// New input changed the pending block.
+    fn on_pending_changed(&mut self, _pending: PendingBlockRef<'_>) {}
+
+    /// Committed blocks that should be re-parsed (see [`crate::Update::invalidated`]).
+    fn on_invalidate(&mut self, _ids: &[BlockId]) {}
+
+    /// Committed blocks dropped by [`crate::MdStream::truncate_to`] (see
+    /// [`crate::Update::removed`]); cancel any work started for them in `on_commit`.
+    fn on_remove(&mut self, _ids: &[BlockId]) {}
+
+    /// All committed blocks were dropped (see [`crate::Update::reset`]), or
+    /// [`crate::MdStream::reset`] was called.
+    fn on_reset(&mut self) {}
+
+    /// `finalize` committed the remaining input.
+    fn on_finalize(&mut self) {}
+}
@@ -20,6 +20,18 @@ pub enum ReferenceDefinitionsMode {
     Invalidate,
 }
 
+/// How [`crate::MdStream::append_bytes`] handles bytes that are not valid UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidUtf8Policy {
+    /// Replace each invalid sequence with U+FFFD, like `String::from_utf8_lossy`.
+    #[default]
+    Replace,
+    /// Reject the chunk with [`crate::InvalidUtf8Error`], leaving the stream unchanged.
+    Error,
+    /// Skip invalid sequences.
+    Drop,
+}
+
 #[derive(Debug, Clone)]
 pub struct Options {
     pub footnotes: FootnotesMode,
@@ -28,6 +40,53 @@ pub struct Options {
     pub terminator_window_bytes: usize,
     /// Optional hard cap for the internal buffer.
     pub max_buffer_bytes: Option<usize>,
+    /// Commit each top-level list item as its own block once the next sibling marker arrives.
+    ///
+    /// Items carry [`crate::BlockFragment::ListItem`] metadata so renderers can stitch them back
+    /// into one list. Off by default: the whole list is a single block.
+    pub commit_list_items: bool,
+    /// Commit a table's header + delimiter rows as a head block, then each body row on its own.
+    ///
+    /// Blocks carry [`crate::BlockFragment::TableHead`] / [`crate::BlockFragment::TableRow`]
+    /// metadata tied to the same table id. Off by default: the whole table is a single block.
+    pub commit_table_rows: bool,
+    /// Recognize YAML (`---`) / TOML (`+++`) front matter on the first line of the stream.
+    ///
+    /// The block stays pending until its closing delimiter and is committed as
+    /// [`crate::BlockKind::FrontMatter`]. Off by default, since a leading `---` is otherwise a
+    /// thematic break.
+    pub front_matter: bool,
+    /// Split an unclosed fenced code block once its pending part reaches this many bytes.
+    ///
+    /// Completed lines are committed as [`crate::BlockKind::CodeFence`] segments tagged with
+    /// [`crate::BlockFragment::CodeFenceSegment`], so the pending block stays small. Combine with
+    /// `max_buffer_bytes` to also bound the buffer. `None` (the default) never splits.
+    pub max_pending_bytes: Option<usize>,
+    /// Handling of invalid UTF-8 passed to [`crate::MdStream::append_bytes`].
+    pub invalid_utf8: InvalidUtf8Policy,
+    /// Send the pending block as a [`crate::PendingDelta`] against the previous update's pending
+    /// block whenever it is still the same block, instead of resending its full text.
+    ///
+    /// Consumers must apply every update in order (e.g. via [`crate::DocumentState::apply`]).
+    /// Off by default: `Update::pending` always carries the full block.
+    pub pending_delta: bool,
+    /// Split the inside of [`crate::BoundaryPlugin`] containers (e.g. `<thinking>`, `::: details`)
+    /// into blocks, instead of keeping each container as one block until it closes.
+    ///
+    /// The start line is committed right away as a [`crate::BlockFragment::ContainerOpen`] block,
+    /// the content is block-split like top-level input with [`crate::Block::parent`] set to the
+    /// open block's id, and the end line follows as a [`crate::BlockFragment::ContainerClose`]
+    /// block; a container still open at finalize gets an empty close block at the end of the
+    /// input. Containers of different plugins nest. Off by default.
+    pub container_scopes: bool,
+    /// Close elements and comments still open in a pending HTML block, and hide a trailing
+    /// partial tag (`<di`), in its `display`. Uses the tag stack tracked by the block splitter,
+    /// so it is not part of [`TerminatorOptions`]. Off by default.
+    pub close_html_blocks: bool,
+    /// Append a closing fence to the `display` of a pending code fence in
+    /// [`crate::MdStream::append_ref`] updates. When off, the display is the raw text, as in
+    /// [`crate::MdStream::append`] updates. On by default.
+    pub close_code_fences: bool,
 }
 
 impl Default for Options {
@@ -38,6 +97,15 @@ impl Default for Options {
             terminator: TerminatorOptions::default(),
             terminator_window_bytes: 16 * 1024,
             max_buffer_bytes: None,
+            commit_list_items: false,
+            commit_table_rows: false,
+            front_matter: false,
+            max_pending_bytes: None,
+            invalid_utf8: InvalidUtf8Policy::Replace,
+            pending_delta: false,
+            container_scopes: false,
+            close_html_blocks: false,
+            close_code_fences: true,
         }
     }
 }
@@ -7,6 +7,17 @@ pub struct TerminatorOptions {
     pub inline_code: bool,
     pub strikethrough: bool,
     pub katex_block: bool,
+    /// Close unterminated inline math (`$...$`, `\(...\)`). Off by default (remend leaves
+    /// inline math alone); `$` followed by a digit or space is treated as currency.
+    pub inline_math: bool,
+    /// Close `==highlight==`. Off by default, like the other extended syntaxes below.
+    pub highlight: bool,
+    /// Close `~subscript~` (a single `~`; `~~` is strikethrough).
+    pub subscript: bool,
+    /// Close `^superscript^`.
+    pub superscript: bool,
+    /// Close `[[wiki links]]`.
+    pub wiki_links: bool,
     pub incomplete_link_url: String,
     /// Tail-only scan window for termination logic.
     pub window_bytes: usize,
@@ -22,6 +33,11 @@ impl Default for TerminatorOptions {
             inline_code: true,
             strikethrough: true,
             katex_block: true,
+            inline_math: false,
+            highlight: false,
+            subscript: false,
+            superscript: false,
+            wiki_links: false,
             incomplete_link_url: "streamdown:incomplete-link".to_string(),
             window_bytes: 16 * 1024,
         }
@@ -889,6 +905,154 @@ fn balance_strikethrough(text: &str) -> String {
     text.to_string()
 }
 
+/// [`balance_strikethrough`] for when single `~` runs are subscript: only runs of two or more
+/// tildes count, so an already closed `~sub~` does not hide the open `~~`.
+fn balance_strikethrough_with_subscript(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut runs = Vec::new();
+    let mut i = 0usize;
+    while i < bytes.len() {
+        if bytes[i] != b'~' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && bytes[i] == b'~' {
+            i += 1;
+        }
+        if i - start >= 2 {
+            runs.push((start, i - start));
+        }
+    }
+    let Some(&(marker_idx, len)) = runs.last() else {
+        return text.to_string();
+    };
+    if len != 2 {
+        return text.to_string();
+    }
+    let content_after = &text[marker_idx + 2..];
+    if content_after.is_empty() || whitespace_or_markers_only(content_after) {
+        return text.to_string();
+    }
+    if runs.len() % 2 == 1 {
+        let mut out = String::with_capacity(text.len() + 2);
+        out.push_str(text);
+        out.push_str("~~");
+        return out;
+    }
+    text.to_string()
+}
+
+/// Start of the unclosed span delimited by a run of exactly `len` `marker` bytes (`==`, `~`,
+/// `^`) at the end of `text`. Like emphasis, an opener must be followed by non-whitespace and a
+/// closer preceded by it. Code spans, math (`$...$`, `\(...\)`), link URLs and escaped markers
+/// are skipped; with `no_spaces` (sub/superscript) whitespace ends a candidate span.
+fn find_unclosed_marker(text: &str, marker: u8, len: usize, no_spaces: bool) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut open = None;
+    let mut in_code = false;
+    let mut in_paren_math = false;
+    let mut i = 0usize;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b == b'`' && !is_part_of_triple_backtick(text, i) {
+            in_code = !in_code;
+            i += 1;
+            continue;
+        }
+        if in_code {
+            i += 1;
+            continue;
+        }
+        if b == b'\\' {
+            // `\(...\)` is math, like `$...$`.
+            match bytes.get(i + 1) {
+                Some(b'(') => in_paren_math = true,
+                Some(b')') => in_paren_math = false,
+                _ => {}
+            }
+            i += 2;
+            continue;
+        }
+        if in_paren_math {
+            i += 1;
+            continue;
+        }
+        if b == b'\n' && text[i + 1..].starts_with('\n') {
+            open = None;
+        }
+        if no_spaces && b.is_ascii_whitespace() {
+            open = None;
+        }
+        if b != marker {
+            i += 1;
+            continue;
+        }
+        let run_end = bytes[i..]
+            .iter()
+            .position(|&c| c != marker)
+            .map_or(bytes.len(), |n| i + n);
+        let skip = run_end - i != len
+            // `[^1]` is a footnote reference.
+            || (marker == b'^' && i > 0 && bytes[i - 1] == b'[')
+            || (text.contains('$') && is_within_math_block(text, i))
+            || is_within_link_or_image_url(text, i);
+        if !skip {
+            // Setext heading protection leaves a zero-width space after `==`.
+            let is_blank = |c: char| c.is_whitespace() || c == '\u{200B}';
+            let prev = text[..i].chars().next_back();
+            let next = text[run_end..].chars().next();
+            match open {
+                None if next.is_some_and(|c| !is_blank(c)) => open = Some(i),
+                Some(_) if prev.is_some_and(|c| !is_blank(c)) => open = None,
+                _ => {}
+            }
+        }
+        i = run_end;
+    }
+    open
+}
+
+fn balance_marker(text: &str, marker: &str, no_spaces: bool) -> String {
+    if is_inside_code_block(text, text.len()) {
+        return text.to_string();
+    }
+    let Some(open) = find_unclosed_marker(text, marker.as_bytes()[0], marker.len(), no_spaces)
+    else {
+        return text.to_string();
+    };
+    if whitespace_or_markers_only(&text[open + marker.len()..]) {
+        return text.to_string();
+    }
+    let body = text.trim_end_matches([' ', '\t']);
+    let mut out = String::with_capacity(body.len() + marker.len());
+    out.push_str(body);
+    out.push_str(marker);
+    out
+}
+
+fn balance_wiki_link(text: &str) -> String {
+    // /\[\[([^\[\]\n]+)\]?$/
+    let Some(open) = text.rfind("[[") else {
+        return text.to_string();
+    };
+    let target = &text[open + 2..];
+    let (target, close) = match target.strip_suffix(']') {
+        Some(t) => (t, "]"),
+        None => (target, "]]"),
+    };
+    if target.trim().is_empty()
+        || target.contains(['[', ']', '\n'])
+        || is_inside_code_block(text, open)
+    {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len() + close.len());
+    out.push_str(text);
+    out.push_str(close);
+    out
+}
+
 fn balance_katex_block(text: &str) -> String {
     // Streamdown counts $$ pairs outside inline code (`...`), ignoring triple backticks.
     let bytes = text.as_bytes();
@@ -929,6 +1093,98 @@ fn balance_katex_block(text: &str) -> String {
     out
 }
 
+/// Opening delimiter of the unclosed inline math span at the end of `text`, if any.
+fn open_inline_math(text: &str) -> Option<&'static str> {
+    let bytes = text.as_bytes();
+    let mut in_code = false;
+    let mut in_block = false;
+    let mut open: Option<&'static str> = None;
+    let mut i = 0usize;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b == b'`' && !is_part_of_triple_backtick(text, i) && open.is_none() {
+            in_code = !in_code;
+            i += 1;
+            continue;
+        }
+        if in_code {
+            i += 1;
+            continue;
+        }
+        if b == b'\\' && i + 1 < bytes.len() {
+            match (bytes[i + 1], open) {
+                (b'(', None) if !in_block && !is_within_link_or_image_url(text, i) => {
+                    open = Some("\\(");
+                }
+                (b')', Some("\\(")) => open = None,
+                _ => {}
+            }
+            i += 2;
+            continue;
+        }
+        if b == b'$' {
+            if i + 1 < bytes.len() && bytes[i + 1] == b'$' {
+                // Display math is balanced by `katex_block`.
+                if open.is_none() {
+                    in_block = !in_block;
+                }
+                i += 2;
+                continue;
+            }
+            match open {
+                Some("$") => open = None,
+                None if !in_block
+                    && is_inline_math_opener(text, i)
+                    && !is_within_link_or_image_url(text, i) =>
+                {
+                    open = Some("$");
+                }
+                _ => {}
+            }
+        }
+        // Inline math does not continue past a blank line.
+        if b == b'\n' && text[i + 1..].starts_with('\n') {
+            open = None;
+            in_code = false;
+        }
+        i += 1;
+    }
+    open
+}
+
+/// A `$` opens inline math when it is not part of a price: it must be followed by a character
+/// other than whitespace or a digit (`$5`, `$ 5`) and not directly follow a word (`US$`).
+fn is_inline_math_opener(text: &str, index: usize) -> bool {
+    let next = text[index + 1..].chars().next();
+    let prev = text[..index].chars().next_back();
+    next.is_some_and(|c| !c.is_whitespace() && !c.is_ascii_digit())
+        && !prev.is_some_and(is_word_char)
+}
+
+fn balance_inline_math(text: &str) -> String {
+    if is_inside_code_block(text, text.len()) {
+        return text.to_string();
+    }
+    let Some(open) = open_inline_math(text) else {
+        return text.to_string();
+    };
+    let close = if open == "$" { "$" } else { "\\)" };
+    // A closing `$` must directly follow the math.
+    let mut body = text.trim_end_matches([' ', '\t']);
+    // A trailing unescaped `\` would escape the closer; drop it until the next chunk arrives.
+    let backslashes = body.len() - body.trim_end_matches('\\').len();
+    if backslashes % 2 == 1 {
+        body = body[..body.len() - 1].trim_end_matches([' ', '\t']);
+        if body.ends_with(open) {
+            return text.to_string();
+        }
+    }
+    let mut out = String::with_capacity(body.len() + close.len());
+    out.push_str(body);
+    out.push_str(close);
+    out
+}
+
 /// Terminate a streaming Markdown tail to avoid partial rendering artifacts.
 ///
 /// This function is intentionally conservative and only modifies the pending tail.
@@ -956,6 +1212,11 @@ pub fn terminate_markdown(text: &str, opts: &TerminatorOptions) -> String {
         return out;
     }
 
+    if opts.wiki_links {
+        // Before links, which would treat `[[page` as an incomplete link.
+        tail = balance_wiki_link(&tail);
+    }
+
     if opts.links || opts.images {
         if let Some(processed) =
             fix_incomplete_link_or_image(&tail, &opts.incomplete_link_url, opts.links, opts.images)
@@ -980,12 +1241,35 @@ pub fn terminate_markdown(text: &str, opts: &TerminatorOptions) -> String {
     if opts.inline_code {
         tail = balance_inline_code(&tail);
     }
+    // Innermost first: sub/superscript cannot contain spaces, strikethrough and highlight can.
+    if opts.subscript {
+        // A `~` closer followed by the `~~` strikethrough closer would read as one `~~~` run, so
+        // inside an open strikethrough the subscript is left open.
+        let closes_strikethrough =
+            opts.strikethrough && balance_strikethrough_with_subscript(&tail).len() > tail.len();
+        if !closes_strikethrough {
+            tail = balance_marker(&tail, "~", true);
+        }
+    }
     if opts.strikethrough {
-        tail = balance_strikethrough(&tail);
+        tail = if opts.subscript {
+            balance_strikethrough_with_subscript(&tail)
+        } else {
+            balance_strikethrough(&tail)
+        };
+    }
+    if opts.superscript {
+        tail = balance_marker(&tail, "^", true);
+    }
+    if opts.highlight {
+        tail = balance_marker(&tail, "==", false);
     }
     if opts.katex_block {
         tail = balance_katex_block(&tail);
     }
+    if opts.inline_math {
+        tail = balance_inline_math(&tail);
+    }
 
     let mut out = String::with_capacity(prefix.len() + tail.len());
     out.push_str(prefix);
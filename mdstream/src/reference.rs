@@ -34,34 +34,241 @@ pub(crate) fn normalize_reference_label(label: &str) -> Option<String> {
     if out.is_empty() { None } else { Some(out) }
 }
 
-pub(crate) fn extract_reference_definition_label(line: &str) -> Option<String> {
-    // CommonMark-ish reference definition, single line only:
-    // up to 3 leading spaces, then "[label]:"
-    //
-    // We purposely keep this lightweight and streaming-friendly; multi-line definitions
-    // can be supported later via a dedicated block mode.
-    let s = strip_up_to_three_leading_spaces(line);
-    let bytes = s.as_bytes();
-    if bytes.len() < 4 || bytes[0] != b'[' {
-        return None;
+/// A link reference definition (`[label]: destination "title"`) found in committed text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ReferenceDefinition<'a> {
+    /// Normalized label.
+    pub(crate) label: String,
+    /// The definition's source text, which may span several lines, without the final newline.
+    #[cfg_attr(not(feature = "pulldown"), allow(dead_code))]
+    pub(crate) source: &'a str,
+}
+
+/// Reference definitions in `text`, following CommonMark: the destination may sit on the line
+/// after the label, and the title on the line after the destination or across several lines.
+///
+/// Definitions are looked for at the start of every line, not only at the start of a paragraph;
+/// a missed invalidation costs more than a spurious one.
+pub(crate) fn extract_reference_definitions(text: &str) -> Vec<ReferenceDefinition<'_>> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        match parse_reference_definition(text, pos) {
+            Some((def, next)) => {
+                out.push(def);
+                pos = next;
+            }
+            None => pos = next_line_start(text, pos),
+        }
     }
-    let close = s.find(']')?;
-    if close == 1 {
+    out
+}
+
+/// Parse a definition starting at line start `start`; returns it with the start of the line
+/// after it.
+fn parse_reference_definition(
+    text: &str,
+    start: usize,
+) -> Option<(ReferenceDefinition<'_>, usize)> {
+    let bytes = text.as_bytes();
+    let mut i = text.len() - strip_up_to_three_leading_spaces(&text[start..]).len();
+    if bytes.get(i) != Some(&b'[') {
         return None;
     }
-    if s.as_bytes().get(close + 1) != Some(&b':') {
-        return None;
+    i += 1;
+    let label_start = i;
+    loop {
+        match *bytes.get(i)? {
+            b'\\' => i += 2,
+            b'[' => return None,
+            b']' => break,
+            b'\n' if line_end_if_blank(text, i + 1).is_some() => return None,
+            _ => i += 1,
+        }
+        if i - label_start > 999 {
+            return None;
+        }
     }
-    let label = &s[1..close];
+    let label = text.get(label_start..i)?;
     // Exclude footnote definitions like "[^1]:"
     if label.starts_with('^') {
         return None;
     }
+    let label = normalize_reference_label(label)?;
+    if bytes.get(i + 1) != Some(&b':') {
+        return None;
+    }
+
+    let dest_end = parse_link_destination(text, skip_whitespace_with_newline(text, i + 2))?;
+    let title_start = skip_whitespace_with_newline(text, dest_end);
+    let end = (title_start > dest_end)
+        .then(|| parse_link_title(text, title_start))
+        .flatten()
+        .and_then(|title_end| line_end_if_blank(text, title_end))
+        // A title that is not alone on its line is dropped when it started on the next line.
+        .or_else(|| line_end_if_blank(text, dest_end))?;
+    let source = text[start..end].trim_end_matches(['\n', '\r']);
+    Some((ReferenceDefinition { label, source }, end))
+}
+
+fn next_line_start(text: &str, pos: usize) -> usize {
+    text[pos..].find('\n').map_or(text.len(), |i| pos + i + 1)
+}
+
+/// Spaces and tabs, then at most one line ending followed by more spaces and tabs.
+fn skip_whitespace_with_newline(text: &str, mut i: usize) -> usize {
+    let bytes = text.as_bytes();
+    let skip_blanks = |i: &mut usize| {
+        while matches!(bytes.get(*i), Some(b' ' | b'\t')) {
+            *i += 1;
+        }
+    };
+    skip_blanks(&mut i);
+    if bytes.get(i) == Some(&b'\r') {
+        i += 1;
+    }
+    if bytes.get(i) == Some(&b'\n') {
+        i += 1;
+        skip_blanks(&mut i);
+    }
+    i
+}
+
+/// If only spaces and tabs follow `i` on its line, the start of the next line.
+fn line_end_if_blank(text: &str, i: usize) -> Option<usize> {
+    let next = next_line_start(text, i);
+    text[i..next].trim().is_empty().then_some(next)
+}
+
+fn parse_link_destination(text: &str, start: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut i = start;
+    if bytes.get(i) == Some(&b'<') {
+        i += 1;
+        loop {
+            match *bytes.get(i)? {
+                b'\\' => i += 2,
+                b'>' => return Some(i + 1),
+                b'<' | b'\n' | b'\r' => return None,
+                _ => i += 1,
+            }
+        }
+    }
+    let mut depth = 0usize;
+    while let Some(&b) = bytes.get(i) {
+        match b {
+            b'\\' if bytes.get(i + 1).is_some_and(u8::is_ascii_punctuation) => i += 2,
+            b'(' => {
+                depth += 1;
+                if depth > 32 {
+                    return None;
+                }
+                i += 1;
+            }
+            b')' if depth == 0 => return None,
+            b')' => {
+                depth -= 1;
+                i += 1;
+            }
+            b if b <= b' ' || b == 0x7f => break,
+            _ => i += 1,
+        }
+    }
+    (i > start && depth == 0).then_some(i)
+}
+
+fn parse_link_title(text: &str, start: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let close = match *bytes.get(start)? {
+        b'"' => b'"',
+        b'\'' => b'\'',
+        b'(' => b')',
+        _ => return None,
+    };
+    let mut i = start + 1;
+    loop {
+        match *bytes.get(i)? {
+            b'\\' => i += 2,
+            b if b == close => return Some(i + 1),
+            b'(' if close == b')' => return None,
+            // A title cannot contain a blank line.
+            b'\n' if line_end_if_blank(text, i + 1).is_some() => return None,
+            _ => i += 1,
+        }
+    }
+}
+
+/// Label of a footnote definition line (`[^id]: ...`), normalized like reference labels.
+pub(crate) fn extract_footnote_definition_label(line: &str) -> Option<String> {
+    let s = strip_up_to_three_leading_spaces(line);
+    let rest = s.strip_prefix("[^")?;
+    let close = rest.find(']')?;
+    if rest.as_bytes().get(close + 1) != Some(&b':') {
+        return None;
+    }
+    let label = &rest[..close];
+    if label.is_empty() || label.contains(char::is_whitespace) {
+        return None;
+    }
     normalize_reference_label(label)
 }
 
-#[cfg(feature = "pulldown")]
-pub(crate) fn extract_reference_definition_line(line: &str) -> Option<(String, String)> {
-    let label = extract_reference_definition_label(line)?;
-    Some((label, line.trim_end().to_string()))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(text: &str) -> Vec<(String, &str)> {
+        extract_reference_definitions(text)
+            .into_iter()
+            .map(|d| (d.label, d.source))
+            .collect()
+    }
+
+    #[test]
+    fn multi_line_definitions() {
+        let text =
+            "[Foo]:\n  /url\n  \"the\ntitle\"\n[bar]: </my url> 'x'\n[baz]: /u\n(title)\nafter\n";
+        assert_eq!(
+            labels(text),
+            vec![
+                ("foo".to_string(), "[Foo]:\n  /url\n  \"the\ntitle\""),
+                ("bar".to_string(), "[bar]: </my url> 'x'"),
+                ("baz".to_string(), "[baz]: /u\n(title)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn labels_may_span_lines() {
+        assert_eq!(
+            labels("[multi\n  line]: /url\n"),
+            vec![("multi line".to_string(), "[multi\n  line]: /url")]
+        );
+    }
+
+    #[test]
+    fn title_on_the_next_line_is_dropped_when_followed_by_text() {
+        assert_eq!(
+            labels("[a]: /url\n\"title\" ok\n"),
+            vec![("a".to_string(), "[a]: /url")]
+        );
+    }
+
+    #[test]
+    fn invalid_definitions() {
+        for text in [
+            "[a]:\n",
+            "[a]:\n\n/url\n",
+            "[a]: /url \"title\" trailing\n",
+            "[a]: /url \"unterminated\n\nnext\"\n",
+            "[a]: <bad\n",
+            "[a]: /ur(l\n",
+            "[]: /url\n",
+            "[^1]: note\n",
+            "    [a]: /url\n",
+            "[a\n\nb]: /url\n",
+        ] {
+            assert!(labels(text).is_empty(), "{text:?}");
+        }
+    }
 }
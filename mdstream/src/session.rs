@@ -0,0 +1,267 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::boundary::BoundaryPlugin;
+use crate::options::Options;
+use crate::state::DocumentState;
+use crate::stream::MdStream;
+use crate::transform::PendingTransformer;
+use crate::types::{Block, Update};
+
+#[cfg(feature = "sync")]
+type StreamSetupFn = dyn Fn(&mut MdStream) + Send + Sync;
+#[cfg(not(feature = "sync"))]
+type StreamSetupFn = dyn Fn(&mut MdStream) + Send;
+
+/// Error returned by [`MdSession`] operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionError {
+    /// The message was already finalized; it only keeps its [`DocumentState`] until evicted.
+    Finalized,
+    /// No message with this key (never appended to, removed, or evicted).
+    UnknownKey,
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Finalized => write!(f, "message is already finalized"),
+            Self::UnknownKey => write!(f, "unknown message key"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// An [`Update`] tagged with the message it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionUpdate<K> {
+    pub key: K,
+    pub update: Update,
+    /// Finalized messages dropped to stay within the memory budget, oldest first.
+    pub evicted: Vec<K>,
+}
+
+struct Entry {
+    /// `None` once finalized: only the rendered state is kept.
+    stream: Option<MdStream>,
+    state: DocumentState,
+    /// Size of the committed blocks in `state`, kept up to date per update.
+    committed_bytes: usize,
+    bytes: usize,
+}
+
+impl Entry {
+    /// Apply `update` to the state and refresh `bytes` without re-measuring every block.
+    fn apply(&mut self, update: &Update) {
+        if update.reset {
+            self.committed_bytes = 0;
+        } else if !update.removed.is_empty() {
+            let removed: usize = self
+                .state
+                .committed()
+                .iter()
+                .filter(|b| update.removed.contains(&b.id))
+                .map(block_bytes)
+                .sum();
+            self.committed_bytes -= removed;
+        }
+        self.committed_bytes += update.committed.iter().map(block_bytes).sum::<usize>();
+        self.state.apply(update.clone());
+
+        let pending = self.state.pending().map_or(0, block_bytes);
+        let buffer = self.stream.as_ref().map_or(0, |s| s.buffer().len());
+        self.bytes = self.committed_bytes + pending + buffer;
+    }
+}
+
+fn block_bytes(block: &Block) -> usize {
+    block.raw.len() + block.display.as_ref().map_or(0, String::len)
+}
+
+/// Many concurrent [`MdStream`]s (one per streaming message), keyed by a caller-chosen id.
+///
+/// Every stream is created on first append with the shared [`Options`] and a fresh set of
+/// boundary plugins / pending transformers from the registered factories. Each message also
+/// keeps a [`DocumentState`] with the updates applied.
+///
+/// Finalized messages drop their stream and keep only their state. With a memory budget, the
+/// oldest finalized messages are evicted once the (approximate) total size exceeds it; streams
+/// that are still active are never evicted.
+pub struct MdSession<K> {
+    opts: Options,
+    setup: Vec<Box<StreamSetupFn>>,
+    entries: HashMap<K, Entry>,
+    finalized: VecDeque<K>,
+    memory_budget: Option<usize>,
+    memory_usage: usize,
+}
+
+impl<K> MdSession<K>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new(opts: Options) -> Self {
+        Self {
+            opts,
+            setup: Vec::new(),
+            entries: HashMap::new(),
+            finalized: VecDeque::new(),
+            memory_budget: None,
+            memory_usage: 0,
+        }
+    }
+
+    /// Register a factory for a boundary plugin installed on every new stream.
+    #[cfg(not(feature = "sync"))]
+    pub fn with_boundary_plugin<F, T>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> T + Send + 'static,
+        T: BoundaryPlugin + 'static,
+    {
+        self.setup
+            .push(Box::new(move |s| s.push_boundary_plugin(factory())));
+        self
+    }
+
+    /// Register a factory for a boundary plugin installed on every new stream.
+    #[cfg(feature = "sync")]
+    pub fn with_boundary_plugin<F, T>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+        T: BoundaryPlugin + 'static,
+    {
+        self.setup
+            .push(Box::new(move |s| s.push_boundary_plugin(factory())));
+        self
+    }
+
+    /// Register a factory for a pending transformer installed on every new stream.
+    #[cfg(not(feature = "sync"))]
+    pub fn with_pending_transformer<F, T>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> T + Send + 'static,
+        T: PendingTransformer + 'static,
+    {
+        self.setup
+            .push(Box::new(move |s| s.push_pending_transformer(factory())));
+        self
+    }
+
+    /// Register a factory for a pending transformer installed on every new stream.
+    #[cfg(feature = "sync")]
+    pub fn with_pending_transformer<F, T>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+        T: PendingTransformer + 'static,
+    {
+        self.setup
+            .push(Box::new(move |s| s.push_pending_transformer(factory())));
+        self
+    }
+
+    /// Evict finalized messages once the total size of all messages exceeds `bytes`.
+    pub fn with_memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = Some(bytes);
+        self
+    }
+
+    /// Append a delta to message `key`, starting a new stream if the key is not known.
+    pub fn append(&mut self, key: K, chunk: &str) -> Result<SessionUpdate<K>, SessionError> {
+        if !self.entries.contains_key(&key) {
+            let mut stream = MdStream::new(self.opts.clone());
+            for setup in &self.setup {
+                setup(&mut stream);
+            }
+            let entry = Entry {
+                stream: Some(stream),
+                state: DocumentState::new(),
+                committed_bytes: 0,
+                bytes: 0,
+            };
+            self.entries.insert(key.clone(), entry);
+        }
+        let entry = self.entries.get_mut(&key).expect("entry exists");
+        let stream = entry.stream.as_mut().ok_or(SessionError::Finalized)?;
+        let update = stream.append(chunk);
+        Ok(self.apply(key, update))
+    }
+
+    /// Finalize message `key`. Its stream is dropped; the document state stays available.
+    pub fn finalize(&mut self, key: &K) -> Result<SessionUpdate<K>, SessionError> {
+        let entry = self.entries.get_mut(key).ok_or(SessionError::UnknownKey)?;
+        let mut stream = entry.stream.take().ok_or(SessionError::Finalized)?;
+        let update = stream.finalize();
+        self.finalized.push_back(key.clone());
+        Ok(self.apply(key.clone(), update))
+    }
+
+    /// Drop message `key` right away, returning its document state.
+    pub fn remove(&mut self, key: &K) -> Option<DocumentState> {
+        let entry = self.entries.remove(key)?;
+        self.memory_usage -= entry.bytes;
+        self.finalized.retain(|k| k != key);
+        Some(entry.state)
+    }
+
+    pub fn document(&self, key: &K) -> Option<&DocumentState> {
+        self.entries.get(key).map(|e| &e.state)
+    }
+
+    /// The live stream for `key`, or `None` if unknown or already finalized.
+    pub fn stream(&self, key: &K) -> Option<&MdStream> {
+        self.entries.get(key).and_then(|e| e.stream.as_ref())
+    }
+
+    pub fn is_finalized(&self, key: &K) -> bool {
+        self.entries.get(key).is_some_and(|e| e.stream.is_none())
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Approximate bytes held by all messages (stream buffers plus block text).
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    fn apply(&mut self, key: K, update: Update) -> SessionUpdate<K> {
+        let entry = self.entries.get_mut(&key).expect("entry exists");
+        let before = entry.bytes;
+        entry.apply(&update);
+        self.memory_usage = self.memory_usage - before + entry.bytes;
+
+        let evicted = self.evict_over_budget();
+        SessionUpdate {
+            key,
+            update,
+            evicted,
+        }
+    }
+
+    fn evict_over_budget(&mut self) -> Vec<K> {
+        let mut evicted = Vec::new();
+        let Some(budget) = self.memory_budget else {
+            return evicted;
+        };
+        while self.memory_usage > budget {
+            let Some(key) = self.finalized.pop_front() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&key) {
+                self.memory_usage -= entry.bytes;
+                evicted.push(key);
+            }
+        }
+        evicted
+    }
+}
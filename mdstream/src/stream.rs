@@ -1,24 +1,56 @@
 use std::collections::{HashMap, HashSet};
 
+mod builder;
+mod checkpoint;
 mod compaction;
+mod delta;
+mod fences;
 mod footnotes;
 mod html;
 mod lines;
+mod lists;
+mod observers;
 mod refs;
-
-use self::footnotes::{detect_footnotes, is_footnote_continuation, is_footnote_definition_start};
+mod scopes;
+mod stats;
+mod tables;
+mod truncate;
+mod utf8;
+
+pub use self::builder::{BuildError, MdStreamBuilder};
+pub use self::checkpoint::{MdStreamSnapshot, RestoreError};
+pub use self::stats::StreamStats;
+pub use self::truncate::TruncateError;
+pub use self::utf8::InvalidUtf8Error;
+
+use self::delta::SentPending;
+use self::footnotes::{
+    detect_footnotes, extract_footnote_usages, is_footnote_continuation,
+    is_footnote_definition_start,
+};
 use self::html::{html_block_start_state, update_html_block_state};
 use self::lines::{Line, take_prefix_at_char_boundary, update_tail};
 use self::refs::extract_reference_usages;
+use self::scopes::Scope;
+use self::stats::Timer;
 
 use crate::boundary::{BoundaryPlugin, BoundaryUpdate};
+use crate::observer::StreamObserver;
 use crate::options::{FootnotesMode, Options, ReferenceDefinitionsMode};
 use crate::pending::terminate_markdown;
-use crate::reference::extract_reference_definition_label;
+use crate::reference::{extract_footnote_definition_label, extract_reference_definitions};
+use crate::syntax::{FrontMatterFormat, is_front_matter_close, parse_front_matter_open};
 use crate::transform::{PendingTransformInput, PendingTransformer};
-use crate::types::{Block, BlockId, BlockKind, BlockStatus, PendingBlockRef, Update, UpdateRef};
+use crate::types::{
+    Block, BlockFragment, BlockId, BlockKind, BlockStatus, CodeFenceFragment, CustomBlock,
+    PendingBlockRef, SourceSpan, Update, UpdateRef,
+};
+
+/// Bytes of recent input kept to detect footnote syntax split across chunks.
+const FOOTNOTE_SCAN_TAIL_BYTES: usize = 256;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum BlockMode {
     Unknown,
     Paragraph,
@@ -27,10 +59,15 @@ enum BlockMode {
     CodeFence {
         fence_char: char,
         fence_len: usize,
+        /// `(fence_id, index)` of the current segment once `max_pending_bytes` split the fence.
+        segment: Option<(BlockId, usize)>,
     },
+    IndentedCode,
     CustomBoundary {
         plugin_index: usize,
         started: bool,
+        /// Kind declared by the plugin for this block, if any.
+        custom: Option<CustomBlock>,
     },
     List,
     BlockQuote,
@@ -39,16 +76,37 @@ enum BlockMode {
         in_comment: bool,
     },
     Table,
+    /// One body row of a row-committed table (see `Options::commit_table_rows`).
+    TableRow,
     MathBlock {
         open_count: usize,
     },
     FootnoteDefinition,
+    FrontMatter {
+        format: FrontMatterFormat,
+    },
 }
 
 fn is_empty_line(line: &str) -> bool {
     line.trim().is_empty()
 }
 
+/// A non-blank line indented by 4+ columns: indented code, unless it continues another block.
+fn is_indented_code_line(line: &str) -> bool {
+    let mut columns = 0usize;
+    for b in line.bytes() {
+        match b {
+            b' ' => columns += 1,
+            b'\t' => columns += 4 - columns % 4,
+            _ => return false,
+        }
+        if columns >= 4 {
+            return !is_empty_line(line);
+        }
+    }
+    false
+}
+
 fn is_heading(line: &str) -> bool {
     let trimmed = line.trim_start();
     trimmed.starts_with('#') && trimmed[1..].starts_with([' ', '\t', '#'])
@@ -281,6 +339,10 @@ pub struct MdStream {
     opts: Options,
     buffer: String,
     lines: Vec<Line>,
+    /// Bytes dropped from the front of `buffer` by compaction.
+    base_offset: usize,
+    /// Lines dropped from the front of `lines` by compaction.
+    base_line: usize,
 
     committed: Vec<Block>,
     processed_line: usize,
@@ -293,19 +355,32 @@ pub struct MdStream {
     pending_display_cache_suffix: Option<String>,
     pending_transformers: Vec<Box<dyn PendingTransformer>>,
     boundary_plugins: Vec<Box<dyn BoundaryPlugin>>,
+    observers: Vec<Box<dyn StreamObserver>>,
     active_boundary_plugin: Option<usize>,
+    /// Open containers, innermost last (`Options::container_scopes`).
+    scopes: Vec<Scope>,
     footnotes_detected: bool,
     footnote_scan_tail: String,
     pending_cr: bool,
     last_finalized_buffer_len: usize,
+    /// Incomplete UTF-8 sequence at the end of the last `append_bytes` chunk.
+    utf8_tail: Vec<u8>,
+    /// Pending block last sent in an `Update`, when `Options::pending_delta` is on.
+    sent_pending: Option<SentPending>,
 
     reference_usage_index: HashMap<String, HashSet<BlockId>>,
+    /// Committed blocks per referenced footnote label (`FootnotesMode::Invalidate` only).
+    footnote_usage_index: HashMap<String, HashSet<BlockId>>,
+
+    stats: StreamStats,
 }
 
 struct AppendCtx<'a> {
     committed_out: Option<&'a mut Vec<Block>>,
     invalidated: Vec<BlockId>,
     reset: bool,
+    /// New input reached the pending block; observers are told once the caller has its display.
+    pending_changed: bool,
 }
 
 impl<'a> AppendCtx<'a> {
@@ -314,6 +389,7 @@ impl<'a> AppendCtx<'a> {
             committed_out,
             invalidated: Vec::new(),
             reset: false,
+            pending_changed: false,
         }
     }
 
@@ -328,7 +404,10 @@ impl<'a> AppendCtx<'a> {
 struct PendingInfo {
     id: BlockId,
     kind: BlockKind,
+    fragment: Option<BlockFragment>,
+    parent: Option<BlockId>,
     raw_start: usize,
+    start_line: usize,
 }
 
 impl std::fmt::Debug for MdStream {
@@ -336,6 +415,8 @@ impl std::fmt::Debug for MdStream {
         f.debug_struct("MdStream")
             .field("buffer_len", &self.buffer.len())
             .field("lines_len", &self.lines.len())
+            .field("base_offset", &self.base_offset)
+            .field("base_line", &self.base_line)
             .field("committed_len", &self.committed.len())
             .field("processed_line", &self.processed_line)
             .field("current_block_start_line", &self.current_block_start_line)
@@ -351,7 +432,9 @@ impl std::fmt::Debug for MdStream {
             )
             .field("pending_transformers_len", &self.pending_transformers.len())
             .field("boundary_plugins_len", &self.boundary_plugins.len())
+            .field("observers_len", &self.observers.len())
             .field("active_boundary_plugin", &self.active_boundary_plugin)
+            .field("scopes_len", &self.scopes.len())
             .field("footnotes_detected", &self.footnotes_detected)
             .field("last_finalized_buffer_len", &self.last_finalized_buffer_len)
             .finish()
@@ -371,6 +454,8 @@ impl MdStream {
                 end: 0,
                 has_newline: false,
             }],
+            base_offset: 0,
+            base_line: 0,
             committed: Vec::new(),
             processed_line: 0,
             current_block_start_line: 0,
@@ -381,12 +466,18 @@ impl MdStream {
             pending_display_cache_suffix: None,
             pending_transformers: Vec::new(),
             boundary_plugins: Vec::new(),
+            observers: Vec::new(),
             active_boundary_plugin: None,
+            scopes: Vec::new(),
             footnotes_detected: false,
             footnote_scan_tail: String::new(),
             pending_cr: false,
             last_finalized_buffer_len: 0,
+            utf8_tail: Vec::new(),
+            sent_pending: None,
             reference_usage_index: HashMap::new(),
+            footnote_usage_index: HashMap::new(),
+            stats: StreamStats::default(),
         }
     }
 
@@ -395,25 +486,14 @@ impl MdStream {
     /// This keeps the built-in terminator for emphasis/inline code/etc, but delegates incomplete
     /// link/image handling to the built-in pending transformers.
     pub fn streamdown_defaults() -> Self {
-        // Use the transformers for link/image behavior so consumers can swap them out.
-        let opts = Options {
-            terminator: crate::pending::TerminatorOptions {
-                links: false,
-                images: false,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
+        MdStreamBuilder::streamdown()
+            .build()
+            .expect("streamdown preset is valid")
+    }
 
-        let mut s = MdStream::new(opts.clone());
-        s.push_pending_transformer(crate::transform::IncompleteLinkPlaceholderTransformer {
-            incomplete_link_url: opts.terminator.incomplete_link_url,
-            window_bytes: opts.terminator_window_bytes,
-        });
-        s.push_pending_transformer(crate::transform::IncompleteImageDropTransformer {
-            window_bytes: opts.terminator_window_bytes,
-        });
-        s
+    /// Start building a stream from [`Options::default`]; see [`MdStreamBuilder`] for presets.
+    pub fn builder() -> MdStreamBuilder {
+        MdStreamBuilder::new()
     }
 
     pub fn push_pending_transformer<T>(&mut self, transformer: T)
@@ -450,6 +530,22 @@ impl MdStream {
         self
     }
 
+    /// Register an observer; it is called during `append`, `finalize` and `truncate_to`.
+    pub fn push_observer<T>(&mut self, observer: T)
+    where
+        T: StreamObserver + 'static,
+    {
+        self.observers.push(Box::new(observer));
+    }
+
+    pub fn with_observer<T>(mut self, observer: T) -> Self
+    where
+        T: StreamObserver + 'static,
+    {
+        self.push_observer(observer);
+        self
+    }
+
     pub fn buffer(&self) -> &str {
         &self.buffer
     }
@@ -464,17 +560,24 @@ impl MdStream {
         blocks
     }
 
+    /// First boundary plugin that can start a block at `line`. A plugin holding an open container
+    /// tracks its own nesting, so it cannot start another one.
+    fn boundary_plugin_for_start(&self, line: &str) -> Option<usize> {
+        (0..self.boundary_plugins.len())
+            .find(|&i| !self.in_scope_of(i) && self.boundary_plugins[i].matches_start(line))
+    }
+
     fn start_mode_for_line(&self, line: &str) -> BlockMode {
-        if let Some(idx) = self
-            .boundary_plugins
-            .iter()
-            .position(|p| p.matches_start(line))
-        {
+        if let Some(idx) = self.boundary_plugin_for_start(line) {
             return BlockMode::CustomBoundary {
                 plugin_index: idx,
                 started: false,
+                custom: self.boundary_plugins[idx].custom_block(line),
             };
         }
+        if is_indented_code_line(line) {
+            return BlockMode::IndentedCode;
+        }
         if is_heading(line) {
             return BlockMode::Heading;
         }
@@ -485,6 +588,7 @@ impl MdStream {
             return BlockMode::CodeFence {
                 fence_char: ch,
                 fence_len: len,
+                segment: None,
             };
         }
         if is_footnote_definition_start(line) {
@@ -507,19 +611,95 @@ impl MdStream {
         BlockMode::Paragraph
     }
 
+    /// Like `start_mode_for_line`, but aware of the blocks committed right before `line_index`.
+    fn start_mode_for_block(&self, line_index: usize) -> BlockMode {
+        // A later segment of a split code fence has no opening line of its own.
+        if self.in_code_fence_continuation() && line_index == self.current_block_start_line {
+            return self.current_mode.clone();
+        }
+        if self.opts.front_matter && self.base_line + line_index == 0 {
+            if let Some(format) = parse_front_matter_open(self.line_str(line_index)) {
+                return BlockMode::FrontMatter { format };
+            }
+        }
+        if self.opts.commit_table_rows && self.continues_table(line_index) {
+            return BlockMode::TableRow;
+        }
+        self.start_mode_for_line(self.line_str(line_index))
+    }
+
+    /// Fragment metadata for the block starting at `current_block_start_line`.
+    fn current_block_fragment(&self) -> Option<BlockFragment> {
+        let start_mode;
+        let mode = if matches!(self.current_mode, BlockMode::Unknown) {
+            start_mode = self.start_mode_for_block(self.current_block_start_line);
+            &start_mode
+        } else {
+            &self.current_mode
+        };
+        match mode {
+            BlockMode::List if self.opts.commit_list_items => self.list_item_fragment(),
+            BlockMode::Table if self.opts.commit_table_rows => self.table_head_fragment(),
+            BlockMode::TableRow => self.table_row_fragment(),
+            BlockMode::CodeFence {
+                segment: Some((fence_id, index)),
+                ..
+            } => Some(BlockFragment::CodeFenceSegment(CodeFenceFragment {
+                fence_id: *fence_id,
+                index: *index,
+                last: false,
+            })),
+            _ => None,
+        }
+    }
+
+    /// Like `current_block_fragment`, for the block being committed by its own closing line (or
+    /// by the end of the stream).
+    fn committed_block_fragment(&self) -> Option<BlockFragment> {
+        match self.current_block_fragment() {
+            Some(BlockFragment::CodeFenceSegment(segment)) => {
+                Some(BlockFragment::CodeFenceSegment(CodeFenceFragment {
+                    last: true,
+                    ..segment
+                }))
+            }
+            fragment => fragment,
+        }
+    }
+
+    /// Plugin-declared kind of the current block (see `BoundaryPlugin::custom_block`).
+    fn current_block_custom(&self) -> Option<&CustomBlock> {
+        Self::custom_for_kind(&self.current_mode, BlockKind::Custom)
+    }
+
+    /// Like `current_block_custom`, for a pending block reported as `kind`.
+    fn custom_for_kind(mode: &BlockMode, kind: BlockKind) -> Option<&CustomBlock> {
+        match mode {
+            BlockMode::CustomBoundary { custom, .. } if kind == BlockKind::Custom => {
+                custom.as_ref()
+            }
+            _ => None,
+        }
+    }
+
     fn kind_for_mode(mode: &BlockMode) -> BlockKind {
         match mode {
             BlockMode::Paragraph => BlockKind::Paragraph,
             BlockMode::Heading => BlockKind::Heading,
             BlockMode::ThematicBreak => BlockKind::ThematicBreak,
             BlockMode::CodeFence { .. } => BlockKind::CodeFence,
-            BlockMode::CustomBoundary { .. } => BlockKind::Unknown,
+            BlockMode::IndentedCode => BlockKind::IndentedCode,
+            BlockMode::CustomBoundary {
+                custom: Some(_), ..
+            } => BlockKind::Custom,
+            BlockMode::CustomBoundary { custom: None, .. } => BlockKind::Unknown,
             BlockMode::List => BlockKind::List,
             BlockMode::BlockQuote => BlockKind::BlockQuote,
             BlockMode::HtmlBlock { .. } => BlockKind::HtmlBlock,
-            BlockMode::Table => BlockKind::Table,
+            BlockMode::Table | BlockMode::TableRow => BlockKind::Table,
             BlockMode::MathBlock { .. } => BlockKind::MathBlock,
             BlockMode::FootnoteDefinition => BlockKind::FootnoteDefinition,
+            BlockMode::FrontMatter { .. } => BlockKind::FrontMatter,
             BlockMode::Unknown => BlockKind::Unknown,
         }
     }
@@ -549,11 +729,16 @@ impl MdStream {
             self.pending_display_cache_suffix = None;
             return;
         }
+        let kind = Self::kind_for_mode(&self.current_mode);
         let block = Block {
             id: self.current_block_id,
             status: BlockStatus::Committed,
-            kind: Self::kind_for_mode(&self.current_mode),
+            kind,
             raw,
+            span: self.span_for_lines(self.current_block_start_line, end_line_inclusive),
+            fragment: self.committed_block_fragment(),
+            custom: self.current_block_custom().cloned(),
+            parent: self.current_parent(),
             display: None,
         };
         self.push_committed_block(block, ctx);
@@ -569,7 +754,11 @@ impl MdStream {
 
     fn push_committed_block(&mut self, block: Block, ctx: &mut AppendCtx<'_>) {
         // Index usages for invalidation-based adapters.
-        if block.kind != BlockKind::CodeFence && block.raw.contains('[') {
+        let is_code = matches!(
+            block.kind,
+            BlockKind::CodeFence | BlockKind::IndentedCode | BlockKind::FrontMatter
+        );
+        if !is_code && block.raw.contains('[') {
             let used = extract_reference_usages(&block.raw);
             if !used.is_empty() {
                 for label in used {
@@ -581,32 +770,54 @@ impl MdStream {
             }
         }
 
-        // Emit invalidations when new reference definitions arrive.
-        if self.opts.reference_definitions == ReferenceDefinitionsMode::Invalidate
-            && block.kind != BlockKind::CodeFence
-            && block.raw.contains("]:")
-        {
+        let footnotes = self.opts.footnotes == FootnotesMode::Invalidate
+            && !is_code
+            && block.raw.contains("[^");
+        if footnotes {
+            for label in extract_footnote_usages(&block.raw) {
+                self.footnote_usage_index
+                    .entry(label)
+                    .or_default()
+                    .insert(block.id);
+            }
+        }
+
+        // Emit invalidations when new reference or footnote definitions arrive.
+        let mut invalidated_ids = Vec::new();
+        let references = self.opts.reference_definitions == ReferenceDefinitionsMode::Invalidate
+            && !is_code
+            && block.raw.contains("]:");
+        if references || footnotes {
             let mut invalidated = HashSet::new();
-            for line in block.raw.split('\n') {
-                let Some(label) = extract_reference_definition_label(line) else {
-                    continue;
-                };
-                if let Some(ids) = self.reference_usage_index.get(&label) {
-                    for id in ids {
-                        if *id != block.id {
-                            invalidated.insert(*id);
-                        }
+            if references {
+                for def in extract_reference_definitions(&block.raw) {
+                    if let Some(ids) = self.reference_usage_index.get(&def.label) {
+                        invalidated.extend(ids.iter().copied().filter(|id| *id != block.id));
+                    }
+                }
+            }
+            if footnotes {
+                for line in block.raw.split('\n') {
+                    let usages = extract_footnote_definition_label(line)
+                        .and_then(|label| self.footnote_usage_index.get(&label));
+                    if let Some(ids) = usages {
+                        invalidated.extend(ids.iter().copied().filter(|id| *id != block.id));
                     }
                 }
             }
             if !invalidated.is_empty() {
                 let mut ids: Vec<BlockId> = invalidated.into_iter().collect();
                 ids.sort_by_key(|id| id.0);
-                ctx.invalidated.extend(ids);
+                invalidated_ids = ids;
             }
         }
 
+        self.stats.record_commit(block.kind);
         self.committed.push(block);
+        // Observers see the definition before the blocks it invalidates.
+        self.notify_committed();
+        self.notify_invalidated(&invalidated_ids);
+        ctx.invalidated.extend(invalidated_ids);
         let block = self
             .committed
             .last()
@@ -616,7 +827,7 @@ impl MdStream {
 
     fn maybe_commit_single_line(&mut self, line_index: usize, ctx: &mut AppendCtx<'_>) {
         match self.current_mode {
-            BlockMode::Heading | BlockMode::ThematicBreak => {
+            BlockMode::Heading | BlockMode::ThematicBreak | BlockMode::TableRow => {
                 self.commit_block(line_index, ctx);
             }
             _ => {}
@@ -627,7 +838,32 @@ impl MdStream {
         self.lines[line_index].as_str(&self.buffer)
     }
 
+    fn span_for_lines(&self, start_line: usize, end_line_inclusive: usize) -> SourceSpan {
+        SourceSpan {
+            start: self.base_offset + self.lines[start_line].start,
+            end: self.base_offset + self.lines[end_line_inclusive].end_with_newline(),
+            start_line: self.base_line + start_line,
+            end_line: self.base_line + end_line_inclusive + 1,
+        }
+    }
+
+    /// Span of everything from `start_line` to the end of the buffer.
+    fn span_to_end(&self, start_line: usize) -> SourceSpan {
+        // A trailing empty line only exists because the buffer ends with '\n'; it holds no bytes.
+        let mut last = self.lines.len() - 1;
+        if last > start_line && self.lines[last].start == self.buffer.len() {
+            last -= 1;
+        }
+        SourceSpan {
+            start: self.base_offset + self.lines[start_line].start,
+            end: self.base_offset + self.buffer.len(),
+            start_line: self.base_line + start_line,
+            end_line: self.base_line + last + 1,
+        }
+    }
+
     fn process_line(&mut self, line_index: usize, ctx: &mut AppendCtx<'_>) {
+        self.stats.lines_processed += 1;
         // Skip if this line does not yet end with newline; we can't do stable boundary checks.
         if !self.lines[line_index].has_newline {
             return;
@@ -638,34 +874,39 @@ impl MdStream {
             return;
         }
 
+        if self.close_scope_at(line_index, ctx) {
+            return;
+        }
+
         if line_index == self.current_block_start_line {
             // Defensive: the first line of a block is the single source of truth for the block mode.
             // This avoids stale-mode edge cases where `current_mode` is not `Unknown` at a new start.
-            self.current_mode = self.start_mode_for_line(self.line_str(line_index));
+            self.current_mode = self.start_mode_for_block(line_index);
             self.maybe_commit_single_line(line_index, ctx);
             // Even on the first line, some modes need to update internal state (e.g. HTML tag stack).
             self.update_mode_with_line(line_index, ctx);
             return;
         }
 
-        let (boundary, next_mode) = {
+        // A table starts at the line before its delimiter row, so the block before it ends one
+        // line earlier than for other boundaries.
+        if self.is_table_start_in_paragraph(line_index) {
+            self.commit_block(line_index - 2, ctx);
+            self.current_mode = BlockMode::Paragraph;
+            self.update_mode_with_line(line_index, ctx);
+            return;
+        }
+
+        let boundary = {
             let prev = self.line_str(line_index - 1);
             let curr = self.line_str(line_index);
-            let boundary = self.is_new_block_boundary(prev, curr, line_index);
-            let next_mode = if boundary {
-                Some(self.start_mode_for_line(curr))
-            } else {
-                None
-            };
-            (boundary, next_mode)
+            self.is_new_block_boundary(prev, curr, line_index)
         };
 
         // Decide if current line starts a new block; if so, commit the previous block at prev line.
         if boundary {
             self.commit_block(line_index - 1, ctx);
-            if let Some(m) = next_mode {
-                self.current_mode = m;
-            }
+            self.current_mode = self.start_mode_for_block(line_index);
             self.maybe_commit_single_line(line_index, ctx);
             // If we started a new mode on this line, we must also update its per-line state.
             // This is required for modes like HTML/math where the opening line affects context.
@@ -701,7 +942,7 @@ impl MdStream {
 
         if boundary {
             self.commit_block(last - 1, ctx);
-            self.current_mode = self.start_mode_for_line(self.line_str(last));
+            self.current_mode = self.start_mode_for_block(last);
         }
     }
 
@@ -723,6 +964,22 @@ impl MdStream {
                 return false;
             }
         }
+        // Front matter only ends at its closing delimiter.
+        if let BlockMode::FrontMatter { .. } = self.current_mode {
+            return false;
+        }
+        // Indented code: blank lines stay inside; any other unindented line ends the block.
+        if let BlockMode::IndentedCode = self.current_mode {
+            return !is_empty_line(curr) && !is_indented_code_line(curr);
+        }
+
+        // Optional per-item commits: a marker left of the current item's content starts the next item.
+        if self.opts.commit_list_items
+            && matches!(self.current_mode, BlockMode::List)
+            && self.is_list_item_split(curr)
+        {
+            return true;
+        }
 
         // Footnote definition: continuation lines should remain in the same block.
         if let BlockMode::FootnoteDefinition = self.current_mode {
@@ -748,7 +1005,7 @@ impl MdStream {
                 return false;
             }
             // Blockquotes can continue after blank lines only if the marker is present.
-            if in_blockquote && is_blockquote_start(curr) {
+            if in_blockquote && is_blockquote_start(curr) && !is_indented_code_line(curr) {
                 return false;
             }
             return true;
@@ -763,6 +1020,12 @@ impl MdStream {
             return false;
         }
 
+        // A line indented by 4+ columns continues the current block: it cannot interrupt a
+        // paragraph, and inside lists/quotes it is item content.
+        if is_indented_code_line(curr) {
+            return false;
+        }
+
         // Certain block starters can interrupt paragraphs/lists/quotes.
         if is_heading(curr) || is_thematic_break(curr) {
             return true;
@@ -770,7 +1033,7 @@ impl MdStream {
         if fence_start(curr).is_some() {
             return true;
         }
-        if self.boundary_plugins.iter().any(|p| p.matches_start(curr)) {
+        if self.boundary_plugin_for_start(curr).is_some() {
             return true;
         }
         if is_footnote_definition_start(curr) {
@@ -789,21 +1052,20 @@ impl MdStream {
             return true;
         }
 
-        // Table detection: if current line is a delimiter and previous line contains pipes,
-        // consider starting a table block at the previous line.
-        if matches!(self.current_mode, BlockMode::Paragraph | BlockMode::Unknown)
-            && self.is_table_delimiter(curr)
-            && prev.contains('|')
-            // table starts at prev line, so boundary at prev-1 if block started earlier.
-            && curr_line_index >= 1
-            && self.current_block_start_line < curr_line_index - 1
-        {
-            return true;
-        }
-
         false
     }
 
+    /// `true` if `line_index` is a table delimiter row whose header line continues a paragraph
+    /// that started earlier; the table then takes over from the header line.
+    fn is_table_start_in_paragraph(&self, line_index: usize) -> bool {
+        matches!(self.current_mode, BlockMode::Paragraph | BlockMode::Unknown)
+            && line_index >= 2
+            && self.current_block_start_line < line_index - 1
+            && self.is_table_delimiter(self.line_str(line_index))
+            && self.line_str(line_index - 1).contains('|')
+            && !is_empty_line(self.line_str(line_index - 2))
+    }
+
     fn is_table_delimiter(&self, line: &str) -> bool {
         let s = line.trim();
         if s.is_empty() {
@@ -828,24 +1090,37 @@ impl MdStream {
         };
         let line = &self.buffer[start..end];
         match &mut self.current_mode {
+            // The line was already committed as a single-line block; the next block starts later.
+            BlockMode::Unknown if line_index < self.current_block_start_line => {}
             BlockMode::Unknown => {
-                self.current_mode = self.start_mode_for_line(line);
+                self.current_mode = self.start_mode_for_block(line_index);
                 self.maybe_commit_single_line(line_index, ctx);
             }
             BlockMode::CodeFence {
                 fence_char,
                 fence_len,
+                ..
             } => {
                 // Opening fence matches `fence_end()` pattern but must not close itself.
                 if line_index > self.current_block_start_line
                     && fence_end(line, *fence_char, *fence_len)
                 {
                     self.commit_block(line_index, ctx);
+                } else if line_index > self.current_block_start_line
+                    && self.opts.max_pending_bytes.is_some_and(|max| {
+                        self.lines[line_index].start
+                            - self.lines[self.current_block_start_line].start
+                            >= max
+                    })
+                {
+                    // Split before this line, so the fence's final segment is never empty.
+                    self.commit_code_fence_segment(line_index - 1, ctx);
                 }
             }
             BlockMode::CustomBoundary {
                 plugin_index,
                 started,
+                ..
             } => {
                 let idx = *plugin_index;
                 if idx >= self.boundary_plugins.len() {
@@ -859,6 +1134,9 @@ impl MdStream {
                 if self.boundary_plugins[idx].update(line) == BoundaryUpdate::Close {
                     self.active_boundary_plugin = None;
                     self.commit_block(line_index, ctx);
+                } else if self.opts.container_scopes {
+                    self.active_boundary_plugin = None;
+                    self.open_scope(idx, line_index, ctx);
                 }
             }
             BlockMode::MathBlock { open_count } => {
@@ -885,6 +1163,13 @@ impl MdStream {
                     let prev = self.lines[line_index - 1].as_str(&self.buffer);
                     if prev.contains('|') {
                         self.current_mode = BlockMode::Table;
+                        // Row mode: header + delimiter form the committed table head.
+                        if self.opts.commit_table_rows
+                            && self.current_block_start_line + 1 == line_index
+                            && self.table_head_columns(line_index).is_some()
+                        {
+                            self.commit_block(line_index, ctx);
+                        }
                     }
                 }
             }
@@ -892,6 +1177,9 @@ impl MdStream {
                 // End table when an empty line is followed by a non-table line.
                 // This is handled by boundary detection on next line arrival.
             }
+            BlockMode::TableRow => {
+                // Rows are single-line blocks, committed by `maybe_commit_single_line`.
+            }
             BlockMode::HtmlBlock { stack, in_comment } => {
                 update_html_block_state(line, stack, in_comment);
                 if !*in_comment && stack.is_empty() {
@@ -904,10 +1192,33 @@ impl MdStream {
             BlockMode::List | BlockMode::BlockQuote => {
                 // Conservative: rely on boundary logic on next line arrival.
             }
+            BlockMode::IndentedCode => {
+                // Ends on the first unindented line; handled by boundary logic.
+            }
+            BlockMode::FrontMatter { format } => {
+                if line_index > self.current_block_start_line
+                    && is_front_matter_close(line, *format)
+                {
+                    self.commit_block(line_index, ctx);
+                }
+            }
             BlockMode::Heading | BlockMode::ThematicBreak => {}
         }
     }
 
+    /// Kind of the block starting at `current_block_start_line`, even before its mode is known.
+    fn current_block_kind(&self) -> BlockKind {
+        if matches!(self.current_mode, BlockMode::Unknown) {
+            match Self::kind_for_mode(&self.start_mode_for_block(self.current_block_start_line)) {
+                // A plugin-declared kind is only reported once the start line is complete.
+                BlockKind::Custom => BlockKind::Unknown,
+                kind => kind,
+            }
+        } else {
+            Self::kind_for_mode(&self.current_mode)
+        }
+    }
+
     fn current_pending_info(&self) -> Option<PendingInfo> {
         if self.opts.footnotes == FootnotesMode::SingleBlock && self.footnotes_detected {
             if self.buffer.is_empty() {
@@ -916,7 +1227,10 @@ impl MdStream {
             return Some(PendingInfo {
                 id: BlockId(1),
                 kind: BlockKind::Unknown,
+                fragment: None,
+                parent: None,
                 raw_start: 0,
+                start_line: 0,
             });
         }
 
@@ -931,17 +1245,15 @@ impl MdStream {
             return None;
         }
 
-        let kind = if matches!(self.current_mode, BlockMode::Unknown) {
-            let mode = self.start_mode_for_line(self.line_str(self.current_block_start_line));
-            Self::kind_for_mode(&mode)
-        } else {
-            Self::kind_for_mode(&self.current_mode)
-        };
+        let kind = self.current_block_kind();
 
         Some(PendingInfo {
             id: self.current_block_id,
             kind,
+            fragment: self.current_block_fragment(),
+            parent: self.current_parent(),
             raw_start: start_off,
+            start_line: self.current_block_start_line,
         })
     }
 
@@ -961,6 +1273,10 @@ impl MdStream {
             id: info.id,
             kind: info.kind,
             raw,
+            span: self.span_to_end(info.start_line),
+            fragment: info.fragment,
+            custom: Self::custom_for_kind(&self.current_mode, info.kind),
+            parent: info.parent,
             display: self.pending_display_cache.as_deref(),
         })
     }
@@ -974,38 +1290,59 @@ impl MdStream {
         if self.pending_transformers.is_empty() {
             return display;
         }
+        let timer = Timer::start();
         let raw = &self.buffer[raw_start..];
+        let custom = Self::custom_for_kind(&self.current_mode, kind);
         for t in &mut self.pending_transformers {
+            if !t.applies_to(kind, custom) {
+                continue;
+            }
             if let Some(next) = t.transform(PendingTransformInput {
                 kind,
                 raw,
                 display: &display,
+                custom,
             }) {
                 display = next;
             }
         }
+        self.stats.record_transformers(timer);
         display
     }
 
     fn ensure_pending_display_for(&mut self, kind: BlockKind, raw_start: usize) {
-        if matches!(kind, BlockKind::CodeFence) {
+        if kind == BlockKind::CodeFence && self.opts.close_code_fences {
             if let BlockMode::CodeFence {
                 fence_char,
                 fence_len,
+                segment: None,
             } = self.current_mode
             {
+                let transformed = self
+                    .pending_transformers
+                    .iter()
+                    .any(|t| t.applies_to(kind, None));
                 if self.pending_display_cache.is_some()
-                    && self.pending_display_cache_suffix.is_some()
+                    && (self.pending_display_cache_suffix.is_some() || transformed)
                 {
                     return;
                 }
+                self.stats.pending_display_recomputes += 1;
                 let raw = &self.buffer[raw_start..];
                 let suffix = code_fence_suffix(raw.ends_with('\n'), fence_char, fence_len);
                 let mut display = String::with_capacity(raw.len() + suffix.len());
                 display.push_str(raw);
                 display.push_str(&suffix);
-                self.pending_display_cache = Some(display);
-                self.pending_display_cache_suffix = Some(suffix);
+                if !transformed {
+                    self.pending_display_cache = Some(display);
+                    self.pending_display_cache_suffix = Some(suffix);
+                } else {
+                    // Transformers may rewrite the body, so the display cannot be extended
+                    // incrementally.
+                    let display = self.transform_pending_display_at(kind, raw_start, display);
+                    self.pending_display_cache = Some(display);
+                    self.pending_display_cache_suffix = None;
+                }
                 return;
             }
         }
@@ -1013,10 +1350,8 @@ impl MdStream {
         if self.pending_display_cache.is_some() {
             return;
         }
-        let display = {
-            let raw = &self.buffer[raw_start..];
-            terminate_markdown(raw, &self.opts.terminator)
-        };
+        self.stats.pending_display_recomputes += 1;
+        let display = self.terminate_pending(kind, raw_start);
         let display = self.transform_pending_display_at(kind, raw_start, display);
         self.pending_display_cache = Some(display);
         self.pending_display_cache_suffix = None;
@@ -1033,6 +1368,7 @@ impl MdStream {
         let BlockMode::CodeFence {
             fence_char,
             fence_len,
+            ..
         } = self.current_mode
         else {
             self.pending_display_cache_suffix = None;
@@ -1057,6 +1393,22 @@ impl MdStream {
         true
     }
 
+    fn terminate_pending(&mut self, kind: BlockKind, raw_start: usize) -> String {
+        if matches!(kind, BlockKind::IndentedCode | BlockKind::FrontMatter)
+            || self.in_code_fence_continuation()
+        {
+            // Shown verbatim: not Markdown, and there is no closing fence to add.
+            return self.buffer[raw_start..].to_string();
+        }
+        if kind == BlockKind::HtmlBlock && self.opts.close_html_blocks {
+            return self.terminate_pending_html(raw_start);
+        }
+        let timer = Timer::start();
+        let display = terminate_markdown(&self.buffer[raw_start..], &self.opts.terminator);
+        self.stats.record_terminator(timer);
+        display
+    }
+
     fn pending_block_snapshot(&mut self) -> Option<Block> {
         if self.opts.footnotes == FootnotesMode::SingleBlock && self.footnotes_detected {
             let raw = self.buffer.clone();
@@ -1064,16 +1416,20 @@ impl MdStream {
                 return None;
             }
             let kind = BlockKind::Unknown;
-            let display = self.transform_pending_display(
-                kind,
-                &raw,
-                terminate_markdown(&raw, &self.opts.terminator),
-            );
+            self.stats.pending_display_recomputes += 1;
+            let timer = Timer::start();
+            let display = terminate_markdown(&raw, &self.opts.terminator);
+            self.stats.record_terminator(timer);
+            let display = self.transform_pending_display(kind, &raw, display);
             return Some(Block {
                 id: BlockId(1),
                 status: BlockStatus::Pending,
                 kind,
                 raw,
+                span: self.span_to_end(0),
+                fragment: None,
+                custom: None,
+                parent: None,
                 display: Some(display),
             });
         }
@@ -1089,19 +1445,19 @@ impl MdStream {
         if raw.is_empty() {
             return None;
         }
-        let kind = if matches!(self.current_mode, BlockMode::Unknown) {
-            let mode = self.start_mode_for_line(self.line_str(self.current_block_start_line));
-            Self::kind_for_mode(&mode)
-        } else {
-            Self::kind_for_mode(&self.current_mode)
-        };
-        let mut display = terminate_markdown(&raw, &self.opts.terminator);
+        let kind = self.current_block_kind();
+        self.stats.pending_display_recomputes += 1;
+        let mut display = self.terminate_pending(kind, start_off);
         display = self.transform_pending_display(kind, &raw, display);
         Some(Block {
             id: self.current_block_id,
             status: BlockStatus::Pending,
             kind,
             raw,
+            span: self.span_to_end(self.current_block_start_line),
+            fragment: self.current_block_fragment(),
+            custom: self.current_block_custom().cloned(),
+            parent: self.current_parent(),
             display: Some(display),
         })
     }
@@ -1119,6 +1475,10 @@ impl MdStream {
                     status: BlockStatus::Pending,
                     kind: BlockKind::Unknown,
                     raw,
+                    span: self.span_to_end(0),
+                    fragment: None,
+                    custom: None,
+                    parent: None,
                     display: Some(cached.clone()),
                 });
             }
@@ -1134,11 +1494,16 @@ impl MdStream {
             if raw.is_empty() {
                 return None;
             }
+            let kind = self.current_block_kind();
             return Some(Block {
                 id: self.current_block_id,
                 status: BlockStatus::Pending,
-                kind: Self::kind_for_mode(&self.current_mode),
+                kind,
                 raw,
+                span: self.span_to_end(self.current_block_start_line),
+                fragment: self.current_block_fragment(),
+                custom: self.current_block_custom().cloned(),
+                parent: self.current_parent(),
                 display: Some(cached.clone()),
             });
         }
@@ -1162,34 +1527,52 @@ impl MdStream {
         if self.pending_transformers.is_empty() {
             return display;
         }
+        let timer = Timer::start();
+        let custom = Self::custom_for_kind(&self.current_mode, kind);
         for t in &mut self.pending_transformers {
+            if !t.applies_to(kind, custom) {
+                continue;
+            }
             if let Some(next) = t.transform(PendingTransformInput {
                 kind,
                 raw,
                 display: &display,
+                custom,
             }) {
                 display = next;
             }
         }
+        self.stats.record_transformers(timer);
         display
     }
 
     pub fn append(&mut self, chunk: &str) -> Update {
+        self.stats.bytes_appended += chunk.len() as u64;
         let mut update = Update::empty();
         let mut ctx = AppendCtx::new(Some(&mut update.committed));
         self.append_core(chunk, &mut ctx);
         update.reset = ctx.reset;
         update.invalidated = ctx.invalidated;
-        update.pending = self.current_pending_block();
+        let pending_changed = ctx.pending_changed;
+        self.set_update_pending(&mut update);
+        if pending_changed {
+            self.notify_pending_changed();
+        }
         update
     }
 
     pub fn append_ref(&mut self, chunk: &str) -> UpdateRef<'_> {
+        // The borrowed view always carries the full pending block.
+        self.forget_sent_pending();
+        self.stats.bytes_appended += chunk.len() as u64;
         let committed_start = self.committed.len();
         let mut ctx = AppendCtx::new(None);
         self.append_core(chunk, &mut ctx);
         let committed_start = if ctx.reset { 0 } else { committed_start };
         self.ensure_current_pending_display();
+        if ctx.pending_changed {
+            self.notify_pending_changed();
+        }
         let pending = self.current_pending_ref_readonly();
         let committed = &self.committed[committed_start..];
         UpdateRef {
@@ -1201,6 +1584,11 @@ impl MdStream {
     }
 
     fn append_core(&mut self, chunk: &str, ctx: &mut AppendCtx<'_>) {
+        if !self.utf8_tail.is_empty() {
+            // A `&str` chunk cannot complete a character split by `append_bytes`.
+            let flushed = self.flush_utf8_tail_lossy();
+            self.append_core(&flushed, ctx);
+        }
         if chunk.is_empty() && !self.pending_cr {
             return;
         }
@@ -1210,7 +1598,9 @@ impl MdStream {
 
         // Best-effort incremental update for code-fence pending display.
         let pending_display_kept = self.try_incremental_pending_display_append(chunk.as_ref());
-        if !pending_display_kept {
+        if pending_display_kept {
+            self.stats.pending_display_incremental += 1;
+        } else {
             self.pending_display_cache = None;
             self.pending_display_cache_suffix = None;
         }
@@ -1220,8 +1610,8 @@ impl MdStream {
                 self.footnotes_detected = true;
             } else {
                 // Keep a small tail window to detect patterns across chunk boundaries.
-                const MAX_TAIL: usize = 256;
-                let chunk_prefix = take_prefix_at_char_boundary(chunk.as_ref(), MAX_TAIL);
+                let chunk_prefix =
+                    take_prefix_at_char_boundary(chunk.as_ref(), FOOTNOTE_SCAN_TAIL_BYTES);
                 if !self.footnote_scan_tail.is_empty() && !chunk_prefix.is_empty() {
                     let mut combined =
                         String::with_capacity(self.footnote_scan_tail.len() + chunk_prefix.len());
@@ -1232,7 +1622,11 @@ impl MdStream {
                     }
                 }
                 if !self.footnotes_detected {
-                    update_tail(&mut self.footnote_scan_tail, chunk.as_ref(), MAX_TAIL);
+                    update_tail(
+                        &mut self.footnote_scan_tail,
+                        chunk.as_ref(),
+                        FOOTNOTE_SCAN_TAIL_BYTES,
+                    );
                 }
             }
         }
@@ -1245,6 +1639,7 @@ impl MdStream {
 
         if enter_single_block_footnotes {
             self.reset_for_single_block_footnotes(ctx);
+            ctx.pending_changed = true;
             return;
         }
 
@@ -1262,16 +1657,20 @@ impl MdStream {
         self.process_incomplete_tail_boundary(ctx);
 
         self.maybe_compact_buffer();
+        ctx.pending_changed = true;
     }
 
     fn reset_for_single_block_footnotes(&mut self, ctx: &mut AppendCtx<'_>) {
         ctx.reset = true;
+        self.notify_reset();
 
         self.committed.clear();
         self.reference_usage_index.clear();
+        self.footnote_usage_index.clear();
         self.pending_display_cache = None;
         self.pending_display_cache_suffix = None;
         self.active_boundary_plugin = None;
+        self.scopes.clear();
 
         // Re-start IDs so consumers can treat it as a new document.
         self.current_block_start_line = 0;
@@ -1284,12 +1683,23 @@ impl MdStream {
     }
 
     pub fn finalize(&mut self) -> Update {
-        if !self.pending_cr && self.buffer.len() == self.last_finalized_buffer_len {
+        self.forget_sent_pending();
+        let flushed = self.flush_utf8_tail_lossy();
+        if !self.pending_cr
+            && flushed.is_empty()
+            && self.buffer.len() == self.last_finalized_buffer_len
+        {
             return Update::empty();
         }
+        let update = self.finalize_core(&flushed);
+        self.notify_finalize();
+        update
+    }
 
+    fn finalize_core(&mut self, flushed: &str) -> Update {
         let mut update = Update::empty();
         let mut ctx = AppendCtx::new(Some(&mut update.committed));
+        self.append_core(flushed, &mut ctx);
 
         if self.pending_cr {
             // Treat a trailing '\r' at EOF as a newline.
@@ -1308,6 +1718,10 @@ impl MdStream {
                     status: BlockStatus::Committed,
                     kind: BlockKind::Unknown,
                     raw: self.buffer.clone(),
+                    span: self.span_to_end(0),
+                    fragment: None,
+                    custom: None,
+                    parent: None,
                     display: None,
                 };
                 self.push_committed_block(block, &mut ctx);
@@ -1326,19 +1740,25 @@ impl MdStream {
             if end_off > start_off {
                 // Commit the remaining pending block.
                 if matches!(self.current_mode, BlockMode::Unknown) {
-                    self.current_mode =
-                        self.start_mode_for_line(self.line_str(self.current_block_start_line));
+                    self.current_mode = self.start_mode_for_block(self.current_block_start_line);
                 }
                 let raw = self.buffer[start_off..end_off].to_string();
-                if raw.trim().is_empty() {
+                // Blank code lines still form the final segment of a split code fence.
+                if raw.trim().is_empty() && !self.in_code_fence_continuation() {
+                    self.close_open_scopes(&mut ctx);
                     update.pending = None;
                     return update;
                 }
+                let kind = Self::kind_for_mode(&self.current_mode);
                 let block = Block {
                     id: self.current_block_id,
                     status: BlockStatus::Committed,
-                    kind: Self::kind_for_mode(&self.current_mode),
+                    kind,
                     raw,
+                    span: self.span_to_end(self.current_block_start_line),
+                    fragment: self.committed_block_fragment(),
+                    custom: self.current_block_custom().cloned(),
+                    parent: self.current_parent(),
                     display: None,
                 };
                 self.push_committed_block(block, &mut ctx);
@@ -1346,6 +1766,7 @@ impl MdStream {
                 self.current_block_start_line = end_line + 1;
             }
         }
+        self.close_open_scopes(&mut ctx);
         update.pending = None;
         self.maybe_compact_buffer();
         self.last_finalized_buffer_len = self.buffer.len();
@@ -1373,6 +1794,8 @@ impl MdStream {
             end: 0,
             has_newline: false,
         });
+        self.base_offset = 0;
+        self.base_line = 0;
         self.committed.clear();
         self.processed_line = 0;
         self.current_block_start_line = 0;
@@ -1388,11 +1811,17 @@ impl MdStream {
             p.reset();
         }
         self.active_boundary_plugin = None;
+        self.scopes.clear();
         self.footnotes_detected = false;
         self.footnote_scan_tail.clear();
         self.pending_cr = false;
         self.last_finalized_buffer_len = 0;
+        self.utf8_tail.clear();
+        self.sent_pending = None;
         self.reference_usage_index.clear();
+        self.footnote_usage_index.clear();
+        self.stats = StreamStats::default();
+        self.notify_reset();
     }
 }
 
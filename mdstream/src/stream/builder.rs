@@ -0,0 +1,365 @@
+use super::MdStream;
+use crate::boundary::{BoundaryPlugin, ContainerBoundaryPlugin};
+use crate::observer::StreamObserver;
+use crate::options::{FootnotesMode, InvalidUtf8Policy, Options, ReferenceDefinitionsMode};
+use crate::pending::TerminatorOptions;
+use crate::transform::{
+    IncompleteImageDropTransformer, IncompleteLinkPlaceholderTransformer, PendingTransformer,
+};
+
+/// Error returned by [`MdStreamBuilder::build`] for option combinations that cannot work together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// `terminator.links` / `terminator.images` is on while the Streamdown link transformers are
+    /// installed, so incomplete links and images would be repaired twice.
+    DuplicateLinkHandling,
+    /// A size option that must be positive is zero.
+    ZeroLimit { option: &'static str },
+    /// `max_buffer_bytes` is below `max_pending_bytes`; compaction never drops the pending block,
+    /// so the buffer cap could not be honored.
+    BufferSmallerThanPending {
+        max_buffer_bytes: usize,
+        max_pending_bytes: usize,
+    },
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateLinkHandling => f.write_str(
+                "terminator link/image repair conflicts with the Streamdown link transformers",
+            ),
+            Self::ZeroLimit { option } => write!(f, "{option} must be greater than zero"),
+            Self::BufferSmallerThanPending {
+                max_buffer_bytes,
+                max_pending_bytes,
+            } => write!(
+                f,
+                "max_buffer_bytes ({max_buffer_bytes}) is smaller than max_pending_bytes ({max_pending_bytes})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Fluent construction of an [`MdStream`] with its options, boundary plugins and pending
+/// transformers, starting from a named preset.
+///
+/// Presets only set a starting point; every setter can still override them.
+pub struct MdStreamBuilder {
+    opts: Options,
+    link_transformers: bool,
+    boundary_plugins: Vec<Box<dyn BoundaryPlugin>>,
+    pending_transformers: Vec<Box<dyn PendingTransformer>>,
+    observers: Vec<Box<dyn StreamObserver>>,
+}
+
+impl std::fmt::Debug for MdStreamBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MdStreamBuilder")
+            .field("opts", &self.opts)
+            .field("link_transformers", &self.link_transformers)
+            .field("boundary_plugins_len", &self.boundary_plugins.len())
+            .field("pending_transformers_len", &self.pending_transformers.len())
+            .field("observers_len", &self.observers.len())
+            .finish()
+    }
+}
+
+impl Default for MdStreamBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MdStreamBuilder {
+    /// Plain [`Options::default`]: built-in terminator, no plugins or transformers.
+    pub fn new() -> Self {
+        Self::from_options(Options::default())
+    }
+
+    pub fn from_options(opts: Options) -> Self {
+        Self {
+            opts,
+            link_transformers: false,
+            boundary_plugins: Vec::new(),
+            pending_transformers: Vec::new(),
+            observers: Vec::new(),
+        }
+    }
+
+    /// Streamdown-compatible: incomplete links become placeholder links and incomplete images are
+    /// dropped by pending transformers; the terminator handles everything else.
+    ///
+    /// Same as [`MdStream::streamdown_defaults`].
+    pub fn streamdown() -> Self {
+        Self::new()
+            .links(false)
+            .images(false)
+            .link_transformers(true)
+    }
+
+    /// Incremark-style: `:::` containers via [`ContainerBoundaryPlugin`], and footnotes handled
+    /// through invalidation instead of collapsing the document into one block.
+    pub fn incremark() -> Self {
+        Self::new()
+            .footnotes(FootnotesMode::Invalidate)
+            .boundary_plugin(ContainerBoundaryPlugin::default())
+    }
+
+    /// No display repair: the terminator and the closing fence of pending code fences are off,
+    /// so renderers see the CommonMark input received so far from both [`MdStream::append`] and
+    /// [`MdStream::append_ref`]. Pending transformers still run.
+    pub fn commonmark_strict() -> Self {
+        Self::new()
+            .terminator(TerminatorOptions {
+                setext_headings: false,
+                links: false,
+                images: false,
+                emphasis: false,
+                inline_code: false,
+                strikethrough: false,
+                katex_block: false,
+                ..Default::default()
+            })
+            .close_code_fences(false)
+    }
+
+    /// Chat bubbles: Streamdown-style link handling, pending HTML blocks closed as they stream,
+    /// footnotes handled through invalidation so a stray `[^1]` never resets the whole message,
+    /// and long code fences committed in 16 KiB segments so each update stays small.
+    pub fn chat_minimal() -> Self {
+        Self::streamdown()
+            .footnotes(FootnotesMode::Invalidate)
+            .close_html_blocks(true)
+            .max_pending_bytes(Some(16 * 1024))
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.opts
+    }
+
+    pub fn footnotes(mut self, mode: FootnotesMode) -> Self {
+        self.opts.footnotes = mode;
+        self
+    }
+
+    pub fn reference_definitions(mut self, mode: ReferenceDefinitionsMode) -> Self {
+        self.opts.reference_definitions = mode;
+        self
+    }
+
+    /// Replace all terminator settings at once.
+    pub fn terminator(mut self, terminator: TerminatorOptions) -> Self {
+        self.opts.terminator = terminator;
+        self
+    }
+
+    pub fn setext_headings(mut self, on: bool) -> Self {
+        self.opts.terminator.setext_headings = on;
+        self
+    }
+
+    pub fn links(mut self, on: bool) -> Self {
+        self.opts.terminator.links = on;
+        self
+    }
+
+    pub fn images(mut self, on: bool) -> Self {
+        self.opts.terminator.images = on;
+        self
+    }
+
+    pub fn emphasis(mut self, on: bool) -> Self {
+        self.opts.terminator.emphasis = on;
+        self
+    }
+
+    pub fn inline_code(mut self, on: bool) -> Self {
+        self.opts.terminator.inline_code = on;
+        self
+    }
+
+    pub fn strikethrough(mut self, on: bool) -> Self {
+        self.opts.terminator.strikethrough = on;
+        self
+    }
+
+    pub fn katex_block(mut self, on: bool) -> Self {
+        self.opts.terminator.katex_block = on;
+        self
+    }
+
+    pub fn inline_math(mut self, on: bool) -> Self {
+        self.opts.terminator.inline_math = on;
+        self
+    }
+
+    pub fn highlight(mut self, on: bool) -> Self {
+        self.opts.terminator.highlight = on;
+        self
+    }
+
+    pub fn subscript(mut self, on: bool) -> Self {
+        self.opts.terminator.subscript = on;
+        self
+    }
+
+    pub fn superscript(mut self, on: bool) -> Self {
+        self.opts.terminator.superscript = on;
+        self
+    }
+
+    pub fn wiki_links(mut self, on: bool) -> Self {
+        self.opts.terminator.wiki_links = on;
+        self
+    }
+
+    /// URL used for incomplete links, by the terminator and the Streamdown link transformer.
+    pub fn incomplete_link_url(mut self, url: impl Into<String>) -> Self {
+        self.opts.terminator.incomplete_link_url = url.into();
+        self
+    }
+
+    pub fn terminator_window_bytes(mut self, bytes: usize) -> Self {
+        self.opts.terminator_window_bytes = bytes;
+        self
+    }
+
+    /// Install [`IncompleteLinkPlaceholderTransformer`] and [`IncompleteImageDropTransformer`]
+    /// (ahead of any other transformer). Turn off `links` / `images` when enabling this.
+    pub fn link_transformers(mut self, on: bool) -> Self {
+        self.link_transformers = on;
+        self
+    }
+
+    pub fn max_buffer_bytes(mut self, bytes: Option<usize>) -> Self {
+        self.opts.max_buffer_bytes = bytes;
+        self
+    }
+
+    pub fn max_pending_bytes(mut self, bytes: Option<usize>) -> Self {
+        self.opts.max_pending_bytes = bytes;
+        self
+    }
+
+    pub fn commit_list_items(mut self, on: bool) -> Self {
+        self.opts.commit_list_items = on;
+        self
+    }
+
+    pub fn commit_table_rows(mut self, on: bool) -> Self {
+        self.opts.commit_table_rows = on;
+        self
+    }
+
+    pub fn front_matter(mut self, on: bool) -> Self {
+        self.opts.front_matter = on;
+        self
+    }
+
+    pub fn invalid_utf8(mut self, policy: InvalidUtf8Policy) -> Self {
+        self.opts.invalid_utf8 = policy;
+        self
+    }
+
+    pub fn pending_delta(mut self, on: bool) -> Self {
+        self.opts.pending_delta = on;
+        self
+    }
+
+    pub fn container_scopes(mut self, on: bool) -> Self {
+        self.opts.container_scopes = on;
+        self
+    }
+
+    pub fn close_html_blocks(mut self, on: bool) -> Self {
+        self.opts.close_html_blocks = on;
+        self
+    }
+
+    pub fn close_code_fences(mut self, on: bool) -> Self {
+        self.opts.close_code_fences = on;
+        self
+    }
+
+    /// Add a boundary plugin after those already installed (e.g. by the preset).
+    pub fn boundary_plugin<T>(mut self, plugin: T) -> Self
+    where
+        T: BoundaryPlugin + 'static,
+    {
+        self.boundary_plugins.push(Box::new(plugin));
+        self
+    }
+
+    /// Add a pending transformer after those already installed (e.g. by the preset).
+    pub fn pending_transformer<T>(mut self, transformer: T) -> Self
+    where
+        T: PendingTransformer + 'static,
+    {
+        self.pending_transformers.push(Box::new(transformer));
+        self
+    }
+
+    pub fn observer<T>(mut self, observer: T) -> Self
+    where
+        T: StreamObserver + 'static,
+    {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), BuildError> {
+        let opts = &self.opts;
+        if self.link_transformers && (opts.terminator.links || opts.terminator.images) {
+            return Err(BuildError::DuplicateLinkHandling);
+        }
+        if opts.terminator_window_bytes == 0 {
+            return Err(BuildError::ZeroLimit {
+                option: "terminator_window_bytes",
+            });
+        }
+        if opts.max_buffer_bytes == Some(0) {
+            return Err(BuildError::ZeroLimit {
+                option: "max_buffer_bytes",
+            });
+        }
+        if opts.max_pending_bytes == Some(0) {
+            return Err(BuildError::ZeroLimit {
+                option: "max_pending_bytes",
+            });
+        }
+        if let (Some(max_buffer_bytes), Some(max_pending_bytes)) =
+            (opts.max_buffer_bytes, opts.max_pending_bytes)
+        {
+            if max_buffer_bytes < max_pending_bytes {
+                return Err(BuildError::BufferSmallerThanPending {
+                    max_buffer_bytes,
+                    max_pending_bytes,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn build(self) -> Result<MdStream, BuildError> {
+        self.validate()?;
+        let mut stream = MdStream::new(self.opts);
+        if self.link_transformers {
+            stream.push_pending_transformer(IncompleteLinkPlaceholderTransformer {
+                incomplete_link_url: stream.opts.terminator.incomplete_link_url.clone(),
+                window_bytes: stream.opts.terminator_window_bytes,
+            });
+            stream.push_pending_transformer(IncompleteImageDropTransformer {
+                window_bytes: stream.opts.terminator_window_bytes,
+            });
+        }
+        stream.boundary_plugins.extend(self.boundary_plugins);
+        stream
+            .pending_transformers
+            .extend(self.pending_transformers);
+        stream.observers.extend(self.observers);
+        Ok(stream)
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::{HashMap, HashSet};
+
+use super::delta::SentPending;
+use super::lines::Line;
+use super::scopes::Scope;
+use super::{BlockMode, MdStream, StreamStats};
+use crate::types::{Block, BlockId};
+
+/// A self-contained copy of an [`MdStream`]'s internal state.
+///
+/// Take one with [`MdStream::checkpoint`] and hand it to [`MdStream::restore`] on a stream built
+/// with the same options, boundary plugins and pending transformers. The restored stream produces
+/// the same future `Update`s (including `BlockId`s) as the original would have, and its
+/// [`crate::StreamStats`] carry on from the snapshot's.
+///
+/// The contents are opaque. With the `serde` feature enabled the snapshot implements
+/// `Serialize`/`Deserialize`, so it can be persisted across process restarts.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MdStreamSnapshot {
+    buffer: String,
+    lines: Vec<Line>,
+    base_offset: usize,
+    base_line: usize,
+
+    committed: Vec<Block>,
+    processed_line: usize,
+    current_block_start_line: usize,
+    current_block_id: BlockId,
+    next_block_id: u64,
+    current_mode: BlockMode,
+
+    active_boundary_plugin: Option<usize>,
+    scopes: Vec<Scope>,
+    footnotes_detected: bool,
+    footnote_scan_tail: String,
+    pending_cr: bool,
+    last_finalized_buffer_len: usize,
+    utf8_tail: Vec<u8>,
+    sent_pending: Option<SentPending>,
+
+    reference_usage_index: HashMap<String, HashSet<BlockId>>,
+    footnote_usage_index: HashMap<String, HashSet<BlockId>>,
+
+    boundary_plugin_states: Vec<Option<String>>,
+    pending_transformer_states: Vec<Option<String>>,
+
+    stats: StreamStats,
+}
+
+impl MdStreamSnapshot {
+    /// Total number of bytes appended to the stream when the snapshot was taken.
+    pub fn appended_len(&self) -> usize {
+        self.base_offset + self.buffer.len()
+    }
+
+    fn validate(&self) -> bool {
+        let Some(last) = self.lines.last() else {
+            return false;
+        };
+        if last.end != self.buffer.len() || last.has_newline {
+            return false;
+        }
+        let mut prev_end = 0usize;
+        for line in &self.lines {
+            if line.start != prev_end
+                || line.end < line.start
+                || line.end_with_newline() > self.buffer.len()
+                || !self.buffer.is_char_boundary(line.start)
+                || !self.buffer.is_char_boundary(line.end)
+            {
+                return false;
+            }
+            prev_end = line.end_with_newline();
+        }
+        self.processed_line <= self.lines.len()
+            && self.current_block_start_line <= self.processed_line
+            && self.current_block_id.0 < self.next_block_id
+            && self.last_finalized_buffer_len <= self.buffer.len()
+            && self.utf8_tail.len() < 4
+    }
+}
+
+/// Error returned by [`MdStream::restore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestoreError {
+    /// The snapshot was taken from a stream with a different number of boundary plugins.
+    BoundaryPluginCount { expected: usize, found: usize },
+    /// The snapshot was taken from a stream with a different number of pending transformers.
+    PendingTransformerCount { expected: usize, found: usize },
+    /// The snapshot's internal offsets are inconsistent with its buffer, or a boundary plugin or
+    /// pending transformer rejected its checkpointed state.
+    Corrupt,
+}
+
+impl std::fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BoundaryPluginCount { expected, found } => write!(
+                f,
+                "snapshot has {found} boundary plugin state(s), stream has {expected} plugin(s)"
+            ),
+            Self::PendingTransformerCount { expected, found } => write!(
+                f,
+                "snapshot has {found} pending transformer state(s), stream has {expected} transformer(s)"
+            ),
+            Self::Corrupt => f.write_str("snapshot is internally inconsistent"),
+        }
+    }
+}
+
+impl std::error::Error for RestoreError {}
+
+impl MdStream {
+    /// Capture the full stream state so it can be resumed later with [`MdStream::restore`].
+    ///
+    /// Plugin and transformer state is captured through their optional `checkpoint` hooks.
+    pub fn checkpoint(&self) -> MdStreamSnapshot {
+        MdStreamSnapshot {
+            buffer: self.buffer.clone(),
+            lines: self.lines.clone(),
+            base_offset: self.base_offset,
+            base_line: self.base_line,
+            committed: self.committed.clone(),
+            processed_line: self.processed_line,
+            current_block_start_line: self.current_block_start_line,
+            current_block_id: self.current_block_id,
+            next_block_id: self.next_block_id,
+            current_mode: self.current_mode.clone(),
+            active_boundary_plugin: self.active_boundary_plugin,
+            scopes: self.scopes.clone(),
+            footnotes_detected: self.footnotes_detected,
+            footnote_scan_tail: self.footnote_scan_tail.clone(),
+            pending_cr: self.pending_cr,
+            last_finalized_buffer_len: self.last_finalized_buffer_len,
+            utf8_tail: self.utf8_tail.clone(),
+            sent_pending: self.sent_pending.clone(),
+            reference_usage_index: self.reference_usage_index.clone(),
+            footnote_usage_index: self.footnote_usage_index.clone(),
+            boundary_plugin_states: self
+                .boundary_plugins
+                .iter()
+                .map(|p| p.checkpoint())
+                .collect(),
+            pending_transformer_states: self
+                .pending_transformers
+                .iter()
+                .map(|t| t.checkpoint())
+                .collect(),
+            stats: self.stats.clone(),
+        }
+    }
+
+    /// Replace this stream's state with `snapshot`.
+    ///
+    /// Options, boundary plugins and pending transformers are kept from `self`; they must be
+    /// configured the same way as on the stream the snapshot was taken from. Plugins without
+    /// checkpointed state are `reset`.
+    ///
+    /// On error the stream is left unchanged.
+    pub fn restore(&mut self, snapshot: MdStreamSnapshot) -> Result<(), RestoreError> {
+        if snapshot.boundary_plugin_states.len() != self.boundary_plugins.len() {
+            return Err(RestoreError::BoundaryPluginCount {
+                expected: self.boundary_plugins.len(),
+                found: snapshot.boundary_plugin_states.len(),
+            });
+        }
+        if snapshot.pending_transformer_states.len() != self.pending_transformers.len() {
+            return Err(RestoreError::PendingTransformerCount {
+                expected: self.pending_transformers.len(),
+                found: snapshot.pending_transformer_states.len(),
+            });
+        }
+        if !snapshot.validate()
+            || snapshot
+                .active_boundary_plugin
+                .is_some_and(|i| i >= self.boundary_plugins.len())
+            || snapshot
+                .scopes
+                .iter()
+                .any(|s| s.plugin_index >= self.boundary_plugins.len())
+        {
+            return Err(RestoreError::Corrupt);
+        }
+
+        // Plugins go first so that a state they reject leaves the stream unchanged.
+        let saved_plugins: Vec<_> = self
+            .boundary_plugins
+            .iter()
+            .map(|p| p.checkpoint())
+            .collect();
+        let saved_transformers: Vec<_> = self
+            .pending_transformers
+            .iter()
+            .map(|t| t.checkpoint())
+            .collect();
+        if !self.restore_plugin_states(
+            &snapshot.boundary_plugin_states,
+            &snapshot.pending_transformer_states,
+        ) {
+            self.restore_plugin_states(&saved_plugins, &saved_transformers);
+            return Err(RestoreError::Corrupt);
+        }
+
+        let MdStreamSnapshot {
+            buffer,
+            lines,
+            base_offset,
+            base_line,
+            committed,
+            processed_line,
+            current_block_start_line,
+            current_block_id,
+            next_block_id,
+            current_mode,
+            active_boundary_plugin,
+            scopes,
+            footnotes_detected,
+            footnote_scan_tail,
+            pending_cr,
+            last_finalized_buffer_len,
+            utf8_tail,
+            sent_pending,
+            reference_usage_index,
+            footnote_usage_index,
+            boundary_plugin_states: _,
+            pending_transformer_states: _,
+            stats,
+        } = snapshot;
+
+        self.buffer = buffer;
+        self.lines = lines;
+        self.base_offset = base_offset;
+        self.base_line = base_line;
+        self.committed = committed;
+        self.processed_line = processed_line;
+        self.current_block_start_line = current_block_start_line;
+        self.current_block_id = current_block_id;
+        self.next_block_id = next_block_id;
+        self.current_mode = current_mode;
+        self.pending_display_cache = None;
+        self.pending_display_cache_suffix = None;
+        self.active_boundary_plugin = active_boundary_plugin;
+        self.scopes = scopes;
+        self.footnotes_detected = footnotes_detected;
+        self.footnote_scan_tail = footnote_scan_tail;
+        self.pending_cr = pending_cr;
+        self.last_finalized_buffer_len = last_finalized_buffer_len;
+        self.utf8_tail = utf8_tail;
+        self.sent_pending = sent_pending;
+        self.reference_usage_index = reference_usage_index;
+        self.footnote_usage_index = footnote_usage_index;
+        self.stats = stats;
+        Ok(())
+    }
+
+    /// Hand checkpointed states to the boundary plugins and pending transformers, resetting those
+    /// without one. Returns `false` as soon as one rejects its state.
+    fn restore_plugin_states(
+        &mut self,
+        boundary_plugin_states: &[Option<String>],
+        pending_transformer_states: &[Option<String>],
+    ) -> bool {
+        let plugins_ok = self
+            .boundary_plugins
+            .iter_mut()
+            .zip(boundary_plugin_states)
+            .all(|(p, state)| match state {
+                Some(state) => p.restore(state),
+                None => {
+                    p.reset();
+                    true
+                }
+            });
+        plugins_ok
+            && self
+                .pending_transformers
+                .iter_mut()
+                .zip(pending_transformer_states)
+                .all(|(t, state)| match state {
+                    Some(state) => t.restore(state),
+                    None => {
+                        t.reset();
+                        true
+                    }
+                })
+    }
+}
@@ -32,6 +32,7 @@ impl MdStream {
             return;
         }
 
+        self.stats.compactions += 1;
         let mut keep_from = keep_from;
         while keep_from < self.buffer.len() && !self.buffer.is_char_boundary(keep_from) {
             keep_from += 1;
@@ -44,6 +45,11 @@ impl MdStream {
 
         self.rebuild_lines_from_buffer();
 
+        // Keep spans absolute. When the block cursor is past the last line (after `finalize`),
+        // the last line is either empty or still open, so it is not counted as dropped.
+        self.base_offset += keep_from;
+        self.base_line += old_block_start_line.min(old_line_count.saturating_sub(1));
+
         self.current_block_start_line = 0;
         self.processed_line = old_processed_line.saturating_sub(old_block_start_line);
         if self.processed_line > self.lines.len() {
@@ -0,0 +1,87 @@
+use super::MdStream;
+use crate::types::{BlockId, PendingDelta, TextDelta, Update};
+
+/// The pending block as the consumer last received it (see `Options::pending_delta`).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct SentPending {
+    id: BlockId,
+    raw_len: usize,
+    display: String,
+    /// Whether the block had a plugin-declared kind, which deltas do not carry.
+    custom: bool,
+}
+
+impl MdStream {
+    /// Set `update.pending`, or `update.pending_delta` when the consumer already has an earlier
+    /// version of the same pending block.
+    pub(super) fn set_update_pending(&mut self, update: &mut Update) {
+        let pending = self.current_pending_block();
+        if !self.opts.pending_delta {
+            update.pending = pending;
+            return;
+        }
+        let Some(mut block) = pending else {
+            self.sent_pending = None;
+            return;
+        };
+        let Some(display) = block.display.take() else {
+            self.sent_pending = None;
+            update.pending = Some(block);
+            return;
+        };
+
+        let prev = self.sent_pending.take().filter(|sent| {
+            !update.reset
+                && sent.id == block.id
+                && sent.custom == block.custom.is_some()
+                && block.raw.is_char_boundary(sent.raw_len)
+        });
+        let (id, raw_len, custom) = (block.id, block.raw.len(), block.custom.is_some());
+        match prev {
+            Some(prev) => {
+                update.pending_delta = Some(PendingDelta {
+                    id: block.id,
+                    kind: block.kind,
+                    span: block.span,
+                    fragment: block.fragment,
+                    raw: block.raw[prev.raw_len..].to_string(),
+                    display: text_delta(&prev.display, &display),
+                });
+            }
+            None => {
+                block.display = Some(display.clone());
+                update.pending = Some(block);
+            }
+        }
+        self.sent_pending = Some(SentPending {
+            id,
+            raw_len,
+            display,
+            custom,
+        });
+    }
+
+    /// Forget the last sent pending block, so the next update carries it in full.
+    pub(super) fn forget_sent_pending(&mut self) {
+        self.sent_pending = None;
+    }
+}
+
+fn text_delta(old: &str, new: &str) -> TextDelta {
+    if let Some(appended) = new.strip_prefix(old) {
+        return TextDelta::Append(appended.to_string());
+    }
+    let mut offset = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !new.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    TextDelta::ReplaceFrom {
+        offset,
+        text: new[offset..].to_string(),
+    }
+}
@@ -0,0 +1,75 @@
+use super::{AppendCtx, BlockMode, MdStream, fence_start};
+use crate::types::{Block, BlockFragment, BlockId, BlockKind, BlockStatus, CodeFenceFragment};
+
+impl MdStream {
+    /// Whether the pending block is a later segment of a fence split by `max_pending_bytes`.
+    pub(super) fn in_code_fence_continuation(&self) -> bool {
+        matches!(
+            self.current_mode,
+            BlockMode::CodeFence {
+                segment: Some(_),
+                ..
+            }
+        )
+    }
+
+    /// Commit the fence lines up to `end_line_inclusive` as one segment; the fence continues in
+    /// the next block.
+    pub(super) fn commit_code_fence_segment(
+        &mut self,
+        end_line_inclusive: usize,
+        ctx: &mut AppendCtx<'_>,
+    ) {
+        let BlockMode::CodeFence {
+            fence_char,
+            fence_len,
+            segment,
+        } = self.current_mode
+        else {
+            return;
+        };
+        let (fence_id, index) = segment.unwrap_or((self.current_block_id, 0));
+        let start_off = self.lines[self.current_block_start_line].start;
+        let end_off = self.lines[end_line_inclusive].end_with_newline();
+        let block = Block {
+            id: self.current_block_id,
+            status: BlockStatus::Committed,
+            kind: BlockKind::CodeFence,
+            raw: self.buffer[start_off..end_off].to_string(),
+            span: self.span_for_lines(self.current_block_start_line, end_line_inclusive),
+            fragment: Some(BlockFragment::CodeFenceSegment(CodeFenceFragment {
+                fence_id,
+                index,
+                last: false,
+            })),
+            custom: None,
+            parent: self.current_parent(),
+            display: None,
+        };
+        self.push_committed_block(block, ctx);
+
+        self.current_block_start_line = end_line_inclusive + 1;
+        self.current_block_id = BlockId(self.next_block_id);
+        self.next_block_id += 1;
+        self.current_mode = BlockMode::CodeFence {
+            fence_char,
+            fence_len,
+            segment: Some((fence_id, index + 1)),
+        };
+        self.pending_display_cache = None;
+        self.pending_display_cache_suffix = None;
+    }
+
+    /// Mode to resume in when re-splitting from the start of `block`: a later fence segment
+    /// cannot be recognized from its own first line.
+    pub(super) fn resume_mode_for(&self, block: &Block) -> Option<BlockMode> {
+        let segment = block.code_fence_segment().filter(|s| !s.is_first())?;
+        let head = self.committed.iter().find(|b| b.id == segment.fence_id)?;
+        let (fence_char, fence_len) = head.raw.lines().next().and_then(fence_start)?;
+        Some(BlockMode::CodeFence {
+            fence_char,
+            fence_len,
+            segment: Some((segment.fence_id, segment.index)),
+        })
+    }
+}
@@ -1,3 +1,7 @@
+use std::collections::HashSet;
+
+use crate::reference::normalize_reference_label;
+
 pub(super) fn is_footnote_definition_start(line: &str) -> bool {
     let s = line.trim_start();
     s.starts_with("[^") && s.contains("]:")
@@ -49,3 +53,34 @@ pub(super) fn detect_footnotes(text: &str) -> bool {
     }
     false
 }
+
+/// Labels of footnote references (`[^id]`, not definitions) in `text`, normalized.
+///
+/// Over-approximates like the reference-link extractor: extra labels only cause extra invalidations.
+pub(super) fn extract_footnote_usages(text: &str) -> HashSet<String> {
+    const MAX_ID_LEN: usize = 200;
+    let bytes = text.as_bytes();
+    let mut out = HashSet::new();
+    let mut i = 0usize;
+    while i + 2 < bytes.len() {
+        if bytes[i] != b'[' || bytes[i + 1] != b'^' {
+            i += 1;
+            continue;
+        }
+        let start = i + 2;
+        let mut j = start;
+        while j < bytes.len() && j - start <= MAX_ID_LEN && !bytes[j].is_ascii_whitespace() {
+            if bytes[j] == b']' {
+                break;
+            }
+            j += 1;
+        }
+        if j < bytes.len() && bytes[j] == b']' && j > start && bytes.get(j + 1) != Some(&b':') {
+            if let Some(label) = normalize_reference_label(&text[start..j]) {
+                out.insert(label);
+            }
+        }
+        i = j.max(i + 1);
+    }
+    out
+}
@@ -1,3 +1,7 @@
+use super::stats::Timer;
+use super::{BlockMode, MdStream};
+use crate::pending::terminate_markdown;
+
 pub(super) fn html_block_start_state(line: &str) -> Option<(Vec<String>, bool)> {
     // Best-effort HTML block start (block-level):
     // - up to 3 leading spaces
@@ -113,13 +117,12 @@ fn parse_tag_at(s: &str, lt_index: usize) -> Option<(HtmlTag, &str)> {
     ))
 }
 
-fn apply_tag_to_stack(tag: &HtmlTag, rest: &str, stack: &mut Vec<String>, in_comment: &mut bool) {
+fn apply_tag_to_stack(tag: &HtmlTag, stack: &mut Vec<String>, in_comment: &mut bool) {
     match tag {
         HtmlTag::CommentOpen => {
-            // If the comment closes on the same line, do not enter comment mode.
-            if !rest.contains("-->") {
-                *in_comment = true;
-            }
+            // The scan resumes inside the comment, so tags in it are skipped even if it closes
+            // on the same line.
+            *in_comment = true;
         }
         HtmlTag::Opening { name, self_closing } => {
             if !*self_closing {
@@ -157,9 +160,68 @@ pub(super) fn update_html_block_state(line: &str, stack: &mut Vec<String>, in_co
             s = &s[lt + 1..];
             continue;
         };
-        apply_tag_to_stack(&tag, rest, stack, in_comment);
+        apply_tag_to_stack(&tag, stack, in_comment);
 
         // Continue scanning after the parsed tag opener/closer.
         s = rest;
     }
 }
+
+/// Start of a tag at the end of `line` that is still missing its `>` (`<`, `</di`, `<a href=`,
+/// `<!-`).
+fn partial_tag_start(line: &str) -> Option<usize> {
+    let lt = line.rfind('<')?;
+    let after = &line[lt + 1..];
+    if after.contains('>') {
+        return None;
+    }
+    if after.starts_with('!') {
+        return "!--".starts_with(after).then_some(lt);
+    }
+    let name = after.strip_prefix('/').unwrap_or(after).as_bytes();
+    let is_tag = match name.first() {
+        None => true,
+        Some(b) if b.is_ascii_alphabetic() => {
+            match name.iter().position(|&b| !is_ascii_tag_name_char(b)) {
+                Some(end) => matches!(name[end], b' ' | b'\t' | b'/'),
+                None => true,
+            }
+        }
+        Some(_) => false,
+    };
+    is_tag.then_some(lt)
+}
+
+impl MdStream {
+    /// Pending display of an HTML block: the raw text without a trailing partial tag, followed
+    /// by closers for the comment and elements still open, innermost first.
+    pub(super) fn terminate_pending_html(&mut self, raw_start: usize) -> String {
+        let raw = &self.buffer[raw_start..];
+        let (mut stack, mut in_comment) = match &self.current_mode {
+            BlockMode::HtmlBlock { stack, in_comment } => (stack.clone(), *in_comment),
+            // The start line is still partial, so none of its tags are tracked yet.
+            _ => (Vec::new(), false),
+        };
+        // Complete lines are already in the state; add the partial last line.
+        let tail_start = raw.rfind('\n').map_or(0, |i| i + 1);
+        let tail = &raw[tail_start..];
+        update_html_block_state(tail, &mut stack, &mut in_comment);
+        let visible = match partial_tag_start(tail) {
+            Some(lt) if !in_comment => tail_start + lt,
+            _ => raw.len(),
+        };
+
+        let timer = Timer::start();
+        let mut display = terminate_markdown(&raw[..visible], &self.opts.terminator);
+        self.stats.record_terminator(timer);
+        if in_comment {
+            display.push_str("-->");
+        }
+        for name in stack.iter().rev() {
+            display.push_str("</");
+            display.push_str(name);
+            display.push('>');
+        }
+        display
+    }
+}
@@ -3,6 +3,7 @@ use std::borrow::Cow;
 use super::MdStream;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(super) struct Line {
     pub(super) start: usize,
     pub(super) end: usize,        // end excluding '\n'
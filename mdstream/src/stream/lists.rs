@@ -0,0 +1,125 @@
+use super::MdStream;
+use crate::types::{BlockFragment, ListItemFragment};
+
+/// A parsed list item marker line (`- a`, `* a`, `+ a`, `1. a`, `1) a`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct ListMarker {
+    /// Columns before the marker.
+    pub(super) indent: usize,
+    /// Bullet character, or the delimiter (`.` / `)`) of an ordered marker.
+    pub(super) marker: u8,
+    pub(super) number: Option<u64>,
+    /// Column where the item content starts; sibling markers are indented less than this.
+    pub(super) content_offset: usize,
+}
+
+impl ListMarker {
+    /// Whether `next` continues the same list (CommonMark: same bullet char or delimiter).
+    pub(super) fn same_list(&self, next: &ListMarker) -> bool {
+        self.marker == next.marker && self.number.is_some() == next.number.is_some()
+    }
+}
+
+pub(super) fn list_marker(line: &str) -> Option<ListMarker> {
+    let bytes = line.as_bytes();
+    let mut i = 0usize;
+    let mut indent = 0usize;
+    while i < bytes.len() {
+        match bytes[i] {
+            b' ' => indent += 1,
+            b'\t' => indent += 4 - indent % 4,
+            _ => break,
+        }
+        i += 1;
+    }
+
+    let marker_start = i;
+    let (marker, number) = match bytes.get(i)? {
+        b'-' | b'+' | b'*' => {
+            i += 1;
+            (bytes[marker_start], None)
+        }
+        b'0'..=b'9' => {
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            if i - marker_start > 9 {
+                return None;
+            }
+            let number = line[marker_start..i].parse().ok()?;
+            let delim = *bytes.get(i)?;
+            if delim != b'.' && delim != b')' {
+                return None;
+            }
+            i += 1;
+            (delim, Some(number))
+        }
+        _ => return None,
+    };
+
+    let marker_end = indent + (i - marker_start);
+    let mut spaces = 0usize;
+    while i < bytes.len() && (bytes[i] == b' ' || bytes[i] == b'\t') {
+        spaces += if bytes[i] == b'\t' { 4 } else { 1 };
+        i += 1;
+    }
+    if spaces == 0 {
+        return None;
+    }
+    // Content indented by 5+ columns is an indented code block; the item content starts after
+    // a single space (CommonMark 5.2). Blank items behave the same.
+    if spaces > 4 || i == bytes.len() {
+        spaces = 1;
+    }
+    Some(ListMarker {
+        indent,
+        marker,
+        number,
+        content_offset: marker_end + spaces,
+    })
+}
+
+impl MdStream {
+    /// `true` if `curr` starts a sibling (or a new list) for the current top-level list item.
+    pub(super) fn is_list_item_split(&self, curr: &str) -> bool {
+        let Some(next) = list_marker(curr) else {
+            return false;
+        };
+        let Some(item) = list_marker(self.line_str(self.current_block_start_line)) else {
+            return false;
+        };
+        next.indent < item.content_offset
+    }
+
+    /// List-group metadata for the list block starting at `current_block_start_line`.
+    pub(super) fn list_item_fragment(&self) -> Option<BlockFragment> {
+        let marker = list_marker(self.line_str(self.current_block_start_line))?;
+
+        // Items are committed back to back, so the previous item is the last committed block.
+        if let Some(prev) = self.committed.last() {
+            if let (Some(prev_item), Some(prev_marker)) = (
+                prev.list_item(),
+                prev.raw.lines().next().and_then(list_marker),
+            ) {
+                let ends_at_current = prev.span.end
+                    == self.base_offset + self.lines[self.current_block_start_line].start;
+                if ends_at_current
+                    && prev_marker.same_list(&marker)
+                    && marker.indent < prev_marker.content_offset
+                {
+                    return Some(BlockFragment::ListItem(ListItemFragment {
+                        index: prev_item.index + 1,
+                        ..*prev_item
+                    }));
+                }
+            }
+        }
+
+        Some(BlockFragment::ListItem(ListItemFragment {
+            list_id: self.current_block_id,
+            ordered: marker.number.is_some(),
+            start: marker.number,
+            index: 0,
+        }))
+    }
+}
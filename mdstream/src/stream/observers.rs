@@ -0,0 +1,77 @@
+use super::MdStream;
+use crate::types::{BlockId, Update};
+
+impl MdStream {
+    /// Report the block just pushed to `committed`.
+    pub(super) fn notify_committed(&mut self) {
+        let Some(block) = self.committed.last() else {
+            return;
+        };
+        for o in &mut self.observers {
+            o.on_commit(block);
+        }
+    }
+
+    pub(super) fn notify_invalidated(&mut self, ids: &[BlockId]) {
+        if ids.is_empty() {
+            return;
+        }
+        for o in &mut self.observers {
+            o.on_invalidate(ids);
+        }
+    }
+
+    pub(super) fn notify_removed(&mut self, ids: &[BlockId]) {
+        if ids.is_empty() {
+            return;
+        }
+        for o in &mut self.observers {
+            o.on_remove(ids);
+        }
+    }
+
+    pub(super) fn notify_reset(&mut self) {
+        for o in &mut self.observers {
+            o.on_reset();
+        }
+    }
+
+    pub(super) fn notify_finalize(&mut self) {
+        for o in &mut self.observers {
+            o.on_finalize();
+        }
+    }
+
+    /// Report the pending block after new input, with the display the caller already computed
+    /// for its `Update`. Observers never cause a display to be computed, so they cannot change it.
+    pub(super) fn notify_pending_changed(&mut self) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let mut observers = std::mem::take(&mut self.observers);
+        if let Some(pending) = self.current_pending_ref_readonly() {
+            for o in &mut observers {
+                o.on_pending_changed(pending);
+            }
+        }
+        self.observers = observers;
+    }
+
+    /// Replay a finished `truncate_to` update to the observers.
+    pub(super) fn notify_update(&mut self, update: &Update) {
+        if self.observers.is_empty() {
+            return;
+        }
+        if update.reset {
+            self.notify_reset();
+        }
+        self.notify_removed(&update.removed);
+        for block in &update.committed {
+            for o in &mut self.observers {
+                o.on_commit(block);
+            }
+        }
+        self.notify_invalidated(&update.invalidated);
+        self.notify_pending_changed();
+    }
+}
@@ -0,0 +1,180 @@
+use super::{AppendCtx, BlockMode, MdStream};
+use crate::boundary::BoundaryUpdate;
+use crate::types::{
+    Block, BlockFragment, BlockId, BlockKind, BlockStatus, ContainerFragment, CustomBlock,
+    SourceSpan,
+};
+
+/// A container opened by a boundary plugin (see `Options::container_scopes`).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(super) struct Scope {
+    /// ID of the container's open block.
+    id: BlockId,
+    pub(super) plugin_index: usize,
+    /// Kind of the open block, repeated on the close block.
+    kind: BlockKind,
+    custom: Option<CustomBlock>,
+}
+
+impl MdStream {
+    /// Open block of the innermost open container.
+    pub(super) fn current_parent(&self) -> Option<BlockId> {
+        self.scopes.last().map(|scope| scope.id)
+    }
+
+    pub(super) fn in_scope_of(&self, plugin_index: usize) -> bool {
+        self.scopes.iter().any(|s| s.plugin_index == plugin_index)
+    }
+
+    /// Index of the open block of the outermost container holding `committed[index]` (or the
+    /// pending block, past the end).
+    pub(super) fn outermost_container_of(&self, index: usize) -> Option<usize> {
+        let mut container = match self.committed.get(index) {
+            Some(block) => match block.fragment {
+                // The container is still open on its close line.
+                Some(BlockFragment::ContainerClose(c)) => Some(c.container_id),
+                _ => block.parent,
+            },
+            None => self.current_parent(),
+        };
+        let mut outermost = None;
+        while let Some(id) = container {
+            let Some(open) = self.committed[..index].iter().rposition(|b| b.id == id) else {
+                break;
+            };
+            outermost = Some(open);
+            container = self.committed[open].parent;
+        }
+        outermost
+    }
+
+    /// Commit the start line of a container on its own and open a scope for it: the lines that
+    /// follow are split into child blocks until the plugin closes the container.
+    pub(super) fn open_scope(
+        &mut self,
+        plugin_index: usize,
+        line_index: usize,
+        ctx: &mut AppendCtx<'_>,
+    ) {
+        let kind = Self::kind_for_mode(&self.current_mode);
+        let custom = self.current_block_custom().cloned();
+        let scope = Scope {
+            id: self.current_block_id,
+            plugin_index,
+            kind,
+            custom: custom.clone(),
+        };
+        let fragment = ContainerFragment {
+            container_id: scope.id,
+            depth: self.scopes.len(),
+        };
+        self.commit_scope_line(
+            line_index,
+            kind,
+            BlockFragment::ContainerOpen(fragment),
+            custom,
+            ctx,
+        );
+        self.scopes.push(scope);
+    }
+
+    /// Feed a complete line to the innermost container's plugin. If the plugin closes the
+    /// container on it, commit the pending child block and then the line as the close block.
+    pub(super) fn close_scope_at(&mut self, line_index: usize, ctx: &mut AppendCtx<'_>) -> bool {
+        let Some(plugin_index) = self.scopes.last().map(|s| s.plugin_index) else {
+            return false;
+        };
+        let (start, end) = {
+            let l = &self.lines[line_index];
+            (l.start, l.end)
+        };
+        let line = &self.buffer[start..end];
+        if self.boundary_plugins[plugin_index].update(line) != BoundaryUpdate::Close {
+            return false;
+        }
+
+        if line_index > self.current_block_start_line {
+            self.commit_block(line_index - 1, ctx);
+        }
+        let scope = self.scopes.pop().expect("checked above");
+        let fragment = ContainerFragment {
+            container_id: scope.id,
+            depth: self.scopes.len(),
+        };
+        self.commit_scope_line(
+            line_index,
+            scope.kind,
+            BlockFragment::ContainerClose(fragment),
+            scope.custom,
+            ctx,
+        );
+        true
+    }
+
+    /// Close the containers still open at finalize, innermost first. There is no end line, so
+    /// each close block is empty and sits at the end of the input.
+    pub(super) fn close_open_scopes(&mut self, ctx: &mut AppendCtx<'_>) {
+        let offset = self.base_offset + self.buffer.len();
+        let trailing_empty_line = self
+            .lines
+            .last()
+            .is_some_and(|l| l.start == self.buffer.len());
+        let line = self.base_line + self.lines.len() - usize::from(trailing_empty_line);
+        while let Some(scope) = self.scopes.pop() {
+            let fragment = ContainerFragment {
+                container_id: scope.id,
+                depth: self.scopes.len(),
+            };
+            let block = Block {
+                id: BlockId(self.next_block_id),
+                status: BlockStatus::Committed,
+                kind: scope.kind,
+                raw: String::new(),
+                span: SourceSpan {
+                    start: offset,
+                    end: offset,
+                    start_line: line,
+                    end_line: line,
+                },
+                fragment: Some(BlockFragment::ContainerClose(fragment)),
+                custom: scope.custom,
+                parent: self.current_parent(),
+                display: None,
+            };
+            self.next_block_id += 1;
+            self.push_committed_block(block, ctx);
+        }
+    }
+
+    fn commit_scope_line(
+        &mut self,
+        line_index: usize,
+        kind: BlockKind,
+        fragment: BlockFragment,
+        custom: Option<CustomBlock>,
+        ctx: &mut AppendCtx<'_>,
+    ) {
+        let start_off = self.lines[line_index].start;
+        let end_off = self.lines[line_index].end_with_newline();
+        let block = Block {
+            id: self.current_block_id,
+            status: BlockStatus::Committed,
+            kind,
+            raw: self.buffer[start_off..end_off].to_string(),
+            span: self.span_for_lines(line_index, line_index),
+            fragment: Some(fragment),
+            custom,
+            parent: self.current_parent(),
+            display: None,
+        };
+        self.push_committed_block(block, ctx);
+
+        self.current_block_start_line = line_index + 1;
+        self.current_block_id = BlockId(self.next_block_id);
+        self.next_block_id += 1;
+        self.current_mode = BlockMode::Unknown;
+        self.pending_display_cache = None;
+        self.pending_display_cache_suffix = None;
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+#[cfg(feature = "timing")]
+use std::time::{Duration, Instant};
+
+use super::MdStream;
+use crate::types::BlockKind;
+
+/// Counters describing how much work a stream has done, from [`MdStream::stats`].
+///
+/// The counters measure work done, not the current document: lines and blocks replayed by
+/// `truncate_to` are counted again, and `blocks_committed` includes blocks it later removed.
+///
+/// Counting is always on and costs a few integer increments per call. The `timing` feature adds
+/// cumulative wall-clock time spent in the terminator and in pending transformers.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamStats {
+    /// Bytes passed to `append` / `append_ref` (and `append_bytes`, after decoding).
+    pub bytes_appended: u64,
+    /// Completed lines run through block detection, including lines replayed by `truncate_to`.
+    pub lines_processed: u64,
+    /// Commits per kind, including code fence segments, list item / table row fragments and
+    /// blocks recommitted by `truncate_to`.
+    pub committed_by_kind: HashMap<BlockKind, u64>,
+    /// Pending displays rebuilt from the whole pending block.
+    pub pending_display_recomputes: u64,
+    /// Appends served by the incremental code-fence display fast path instead of a rebuild.
+    pub pending_display_incremental: u64,
+    /// Terminator runs; each rescans up to `terminator_window_bytes` of the pending tail.
+    pub terminator_rescans: u64,
+    /// Buffer compactions triggered by `Options::max_buffer_bytes`.
+    pub compactions: u64,
+    /// `truncate_to` calls that changed the stream.
+    pub truncations: u64,
+    /// Time spent in the terminator.
+    #[cfg(feature = "timing")]
+    pub terminator_time: Duration,
+    /// Time spent in pending transformers.
+    #[cfg(feature = "timing")]
+    pub transformer_time: Duration,
+}
+
+impl StreamStats {
+    /// Total committed blocks of all kinds.
+    pub fn blocks_committed(&self) -> u64 {
+        self.committed_by_kind.values().sum()
+    }
+
+    pub fn committed(&self, kind: BlockKind) -> u64 {
+        self.committed_by_kind.get(&kind).copied().unwrap_or(0)
+    }
+
+    pub(super) fn record_commit(&mut self, kind: BlockKind) {
+        *self.committed_by_kind.entry(kind).or_default() += 1;
+    }
+
+    pub(super) fn record_terminator(&mut self, timer: Timer) {
+        self.terminator_rescans += 1;
+        #[cfg(feature = "timing")]
+        {
+            self.terminator_time += timer.0.elapsed();
+        }
+        #[cfg(not(feature = "timing"))]
+        let _ = timer;
+    }
+
+    pub(super) fn record_transformers(&mut self, timer: Timer) {
+        #[cfg(feature = "timing")]
+        {
+            self.transformer_time += timer.0.elapsed();
+        }
+        #[cfg(not(feature = "timing"))]
+        let _ = timer;
+    }
+}
+
+/// Start time of a measured section; zero-sized without the `timing` feature.
+pub(super) struct Timer(#[cfg(feature = "timing")] Instant);
+
+impl Timer {
+    pub(super) fn start() -> Self {
+        Self(
+            #[cfg(feature = "timing")]
+            Instant::now(),
+        )
+    }
+}
+
+impl MdStream {
+    /// Work done by this stream so far; cleared by [`MdStream::reset`], not part of checkpoints.
+    pub fn stats(&self) -> &StreamStats {
+        &self.stats
+    }
+}
@@ -0,0 +1,57 @@
+use super::{BlockMode, MdStream, is_empty_line};
+use crate::syntax::{parse_table_delimiter_row, table_cell_count};
+use crate::types::{BlockFragment, TableFragment};
+
+impl MdStream {
+    /// `true` if the block starting at `line_index` is the next body row of a row-committed table.
+    pub(super) fn continues_table(&self, line_index: usize) -> bool {
+        let Some(prev) = self.committed.last() else {
+            return false;
+        };
+        if prev.table().is_none() {
+            return false;
+        }
+        if prev.span.end != self.base_offset + self.lines[line_index].start {
+            return false;
+        }
+        // GFM: a table ends at a blank line or at the start of another block.
+        let line = self.line_str(line_index);
+        !is_empty_line(line)
+            && matches!(
+                self.start_mode_for_line(line),
+                BlockMode::Paragraph | BlockMode::IndentedCode
+            )
+    }
+
+    /// Column count for a head block whose delimiter row is `line_index`.
+    ///
+    /// `None` unless the header row above it has the same number of cells (GFM).
+    pub(super) fn table_head_columns(&self, line_index: usize) -> Option<usize> {
+        let columns = parse_table_delimiter_row(self.line_str(line_index))?.len();
+        let header = self.line_str(line_index.checked_sub(1)?);
+        (table_cell_count(header) == columns).then_some(columns)
+    }
+
+    pub(super) fn table_head_fragment(&self) -> Option<BlockFragment> {
+        let columns = self.table_head_columns(self.current_block_start_line + 1)?;
+        Some(BlockFragment::TableHead(TableFragment {
+            table_id: self.current_block_id,
+            columns,
+            row: 0,
+        }))
+    }
+
+    pub(super) fn table_row_fragment(&self) -> Option<BlockFragment> {
+        let prev = self.committed.last()?;
+        let row = match prev.fragment? {
+            BlockFragment::TableHead(_) => 0,
+            BlockFragment::TableRow(t) => t.row + 1,
+            BlockFragment::ListItem(_)
+            | BlockFragment::CodeFenceSegment(_)
+            | BlockFragment::ContainerOpen(_)
+            | BlockFragment::ContainerClose(_) => return None,
+        };
+        let table = prev.table()?;
+        Some(BlockFragment::TableRow(TableFragment { row, ..*table }))
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::HashSet;
+
+use super::lines::{Line, update_tail};
+use super::{AppendCtx, BlockMode, FOOTNOTE_SCAN_TAIL_BYTES, MdStream};
+use crate::options::{FootnotesMode, ReferenceDefinitionsMode};
+use crate::reference::{extract_footnote_definition_label, extract_reference_definitions};
+use crate::types::{Block, BlockId, BlockKind, Update};
+
+/// Error returned by [`MdStream::truncate_to`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TruncateError {
+    /// `offset` is past the end of the appended text (`len`).
+    OutOfRange { offset: usize, len: usize },
+    /// `offset` does not fall on a UTF-8 character boundary.
+    NotCharBoundary { offset: usize },
+    /// Rewinding to `offset` needs text that buffer compaction already dropped
+    /// (see `Options::max_buffer_bytes`).
+    Compacted { offset: usize },
+}
+
+impl std::fmt::Display for TruncateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfRange { offset, len } => {
+                write!(f, "truncate offset {offset} is past the end ({len})")
+            }
+            Self::NotCharBoundary { offset } => {
+                write!(f, "truncate offset {offset} is not a char boundary")
+            }
+            Self::Compacted { offset } => {
+                write!(f, "truncate offset {offset} reaches into compacted text")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TruncateError {}
+
+impl MdStream {
+    /// Drop everything appended after the logical byte `offset`.
+    ///
+    /// `offset` uses the same coordinates as [`crate::SourceSpan`]. Committed blocks whose
+    /// commit depended on text past the cut are dropped and listed in [`Update::removed`]; the
+    /// surviving text is re-split and comes back as committed or pending blocks under fresh IDs.
+    /// Afterwards the stream behaves like one that was only ever fed the first `offset` bytes,
+    /// and can be appended to or finalized again.
+    ///
+    /// A trailing `\r` that has not been resolved into a newline yet is discarded, as is an
+    /// incomplete character left by [`MdStream::append_bytes`].
+    pub fn truncate_to(&mut self, offset: usize) -> Result<Update, TruncateError> {
+        let len = self.base_offset + self.buffer.len();
+        // Cutting at the end still discards a pending `\r` or a partial UTF-8 character.
+        let changes_state = offset < len || self.pending_cr || !self.utf8_tail.is_empty();
+        // Replayed blocks may be dropped or renamed again; observers only see the result.
+        let observers = std::mem::take(&mut self.observers);
+        let result = self.truncate_core(offset);
+        self.observers = observers;
+        if let Ok(update) = &result {
+            if changes_state {
+                self.stats.truncations += 1;
+                self.notify_update(update);
+            }
+        }
+        result
+    }
+
+    fn truncate_core(&mut self, offset: usize) -> Result<Update, TruncateError> {
+        let len = self.base_offset + self.buffer.len();
+        if offset > len {
+            return Err(TruncateError::OutOfRange { offset, len });
+        }
+        if offset < self.base_offset {
+            return Err(TruncateError::Compacted { offset });
+        }
+        let cut = offset - self.base_offset;
+        if !self.buffer.is_char_boundary(cut) {
+            return Err(TruncateError::NotCharBoundary { offset });
+        }
+        // `Update::removed` may drop the consumer's pending block; always resend it in full.
+        self.forget_sent_pending();
+        if offset == len && !self.pending_cr {
+            self.utf8_tail.clear();
+            let mut update = Update::empty();
+            self.set_update_pending(&mut update);
+            return Ok(update);
+        }
+
+        if self.opts.footnotes == FootnotesMode::SingleBlock && self.footnotes_detected {
+            return Ok(self.truncate_single_block_footnotes(cut));
+        }
+
+        // Blocks that end before the cut stay committed, except possibly the last one: it may
+        // have been committed only because of the lines that follow it (a table header can
+        // split a paragraph two lines later). That block is replayed under its old ID and kept
+        // if it comes out unchanged; once its text is compacted, the commit is taken as final.
+        let keep = self.committed.partition_point(|b| b.span.end <= offset);
+        let mut reconsider = keep > 0 && {
+            let last = &self.committed[keep - 1];
+            last.span.start_line >= self.base_line || !self.commit_survives_cut(last, offset)
+        };
+        let mut first_replayed = if reconsider { keep - 1 } else { keep };
+        // Boundary plugin state cannot be rewound into a container; re-split it from its start.
+        if let Some(open) = self.outermost_container_of(first_replayed) {
+            if open < first_replayed {
+                first_replayed = open;
+                reconsider = false;
+            }
+        }
+        let rewind_line = match self.committed.get(first_replayed) {
+            Some(block) => block
+                .span
+                .start_line
+                .checked_sub(self.base_line)
+                .ok_or(TruncateError::Compacted { offset })?,
+            None => self.current_block_start_line.min(self.lines.len() - 1),
+        };
+
+        let resume_mode = match self.committed.get(first_replayed) {
+            Some(block) => self.resume_mode_for(block),
+            None if self.in_code_fence_continuation() => Some(self.current_mode.clone()),
+            None => None,
+        };
+
+        let replayed: Vec<Block> = self.committed.drain(first_replayed..).collect();
+        let replayed_ids: HashSet<BlockId> = replayed.iter().map(|b| b.id).collect();
+        for index in [
+            &mut self.reference_usage_index,
+            &mut self.footnote_usage_index,
+        ] {
+            for ids in index.values_mut() {
+                ids.retain(|id| !replayed_ids.contains(id));
+            }
+            index.retain(|_, ids| !ids.is_empty());
+        }
+
+        // Blocks that relied on a removed reference or footnote definition must be re-parsed.
+        let references = self.opts.reference_definitions == ReferenceDefinitionsMode::Invalidate;
+        let footnotes = self.opts.footnotes == FootnotesMode::Invalidate;
+        let mut invalidated = Vec::new();
+        if references || footnotes {
+            for block in &replayed {
+                if matches!(
+                    block.kind,
+                    BlockKind::CodeFence | BlockKind::IndentedCode | BlockKind::FrontMatter
+                ) || !block.raw.contains("]:")
+                {
+                    continue;
+                }
+                if references {
+                    for def in extract_reference_definitions(&block.raw) {
+                        if let Some(ids) = self.reference_usage_index.get(&def.label) {
+                            invalidated.extend(ids.iter().copied());
+                        }
+                    }
+                }
+                if footnotes {
+                    for line in block.raw.split('\n') {
+                        let usages = extract_footnote_definition_label(line)
+                            .and_then(|label| self.footnote_usage_index.get(&label));
+                        if let Some(ids) = usages {
+                            invalidated.extend(ids.iter().copied());
+                        }
+                    }
+                }
+            }
+        }
+
+        let tail = self.buffer[self.lines[rewind_line].start..cut].to_string();
+        self.rewind_to_line(rewind_line);
+        if let Some(mode) = resume_mode {
+            self.current_mode = mode;
+        }
+        match replayed.first() {
+            Some(first) if reconsider => self.current_block_id = first.id,
+            Some(_) => {
+                self.current_block_id = BlockId(self.next_block_id);
+                self.next_block_id += 1;
+            }
+            None => {}
+        }
+
+        let mut update = Update::empty();
+        let mut ctx = AppendCtx::new(Some(&mut update.committed));
+        ctx.invalidated = invalidated;
+        self.append_core(&tail, &mut ctx);
+        let mut invalidated = ctx.invalidated;
+        update.reset = ctx.reset;
+
+        let mut removed: Vec<BlockId> = replayed.iter().map(|b| b.id).collect();
+        if reconsider && !update.reset {
+            let old = &replayed[0];
+            let unchanged = update
+                .committed
+                .first()
+                .is_some_and(|b| b.id == old.id && b.kind == old.kind && b.raw == old.raw);
+            if unchanged {
+                update.committed.remove(0);
+                removed.remove(0);
+            } else {
+                // Replayed content never reuses a removed ID.
+                let fresh = BlockId(self.next_block_id);
+                self.next_block_id += 1;
+                self.rename_block(old.id, fresh, &mut update.committed, &mut invalidated);
+            }
+        }
+
+        if !update.reset {
+            update.removed = removed;
+        }
+        invalidated.retain(|id| !update.removed.contains(id));
+        invalidated.sort_by_key(|id| id.0);
+        invalidated.dedup();
+        update.invalidated = invalidated;
+        self.set_update_pending(&mut update);
+        Ok(update)
+    }
+
+    /// Whether the line right after `block` (which triggered its commit) is fully before `offset`.
+    fn commit_survives_cut(&self, block: &Block, offset: usize) -> bool {
+        let Some(trigger) = block.span.end_line.checked_sub(self.base_line) else {
+            // The deciding line was compacted away, so it lies before `offset`.
+            return true;
+        };
+        let Some(line) = self.lines.get(trigger) else {
+            return false;
+        };
+        line.has_newline && self.base_offset + line.end_with_newline() <= offset
+    }
+
+    fn rename_block(
+        &mut self,
+        from: BlockId,
+        to: BlockId,
+        emitted: &mut [Block],
+        invalidated: &mut [BlockId],
+    ) {
+        for block in self.committed.iter_mut().rev().chain(emitted.iter_mut()) {
+            if block.id == from {
+                block.id = to;
+            }
+        }
+        for ids in self
+            .reference_usage_index
+            .values_mut()
+            .chain(self.footnote_usage_index.values_mut())
+        {
+            if ids.remove(&from) {
+                ids.insert(to);
+            }
+        }
+        for id in invalidated.iter_mut().filter(|id| **id == from) {
+            *id = to;
+        }
+        if self.current_block_id == from {
+            self.current_block_id = to;
+        }
+    }
+
+    fn truncate_single_block_footnotes(&mut self, cut: usize) -> Update {
+        // The whole document is one pending (or finalized) block; rebuild it from scratch.
+        let tail = self.buffer[..cut].to_string();
+        self.rewind_to_line(0);
+        self.committed.clear();
+        self.reference_usage_index.clear();
+        self.footnote_usage_index.clear();
+        self.current_block_id = BlockId(1);
+        self.next_block_id = 2;
+        self.footnotes_detected = false;
+        self.footnote_scan_tail.clear();
+
+        let mut update = Update::empty();
+        let mut ctx = AppendCtx::new(Some(&mut update.committed));
+        ctx.reset = true;
+        self.append_core(&tail, &mut ctx);
+        update.reset = ctx.reset;
+        update.invalidated = ctx.invalidated;
+        self.set_update_pending(&mut update);
+        update
+    }
+
+    /// Drop `lines[line..]` from the buffer and restart block detection at `line`.
+    fn rewind_to_line(&mut self, line: usize) {
+        let start = self.lines[line].start;
+        self.buffer.truncate(start);
+        self.lines.truncate(line);
+        self.lines.push(Line {
+            start,
+            end: start,
+            has_newline: false,
+        });
+
+        self.processed_line = line;
+        self.current_block_start_line = line;
+        self.current_mode = BlockMode::Unknown;
+        self.active_boundary_plugin = None;
+        self.scopes.clear();
+        self.pending_display_cache = None;
+        self.pending_display_cache_suffix = None;
+        for t in &mut self.pending_transformers {
+            t.reset();
+        }
+        for p in &mut self.boundary_plugins {
+            p.reset();
+        }
+        self.pending_cr = false;
+        self.utf8_tail.clear();
+        self.last_finalized_buffer_len = 0;
+
+        self.footnote_scan_tail.clear();
+        update_tail(
+            &mut self.footnote_scan_tail,
+            &self.buffer,
+            FOOTNOTE_SCAN_TAIL_BYTES,
+        );
+    }
+}
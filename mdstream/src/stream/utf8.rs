@@ -0,0 +1,107 @@
+use std::borrow::Cow;
+
+use super::MdStream;
+use crate::options::InvalidUtf8Policy;
+use crate::types::Update;
+
+/// Error returned by [`MdStream::append_bytes`] under [`InvalidUtf8Policy::Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidUtf8Error {
+    /// Index in the rejected chunk where the invalid sequence starts (0 if it started in bytes
+    /// carried over from the previous chunk).
+    pub offset: usize,
+}
+
+impl std::fmt::Display for InvalidUtf8Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid UTF-8 at chunk offset {}", self.offset)
+    }
+}
+
+impl std::error::Error for InvalidUtf8Error {}
+
+impl MdStream {
+    /// Append raw bytes, e.g. straight from a network body.
+    ///
+    /// A multi-byte character split across chunks is held back until the rest arrives.
+    /// Invalid sequences are handled per [`crate::Options::invalid_utf8`]; on error nothing is
+    /// appended.
+    pub fn append_bytes(&mut self, chunk: &[u8]) -> Result<Update, InvalidUtf8Error> {
+        let carried = self.utf8_tail.len();
+        let joined: Vec<u8>;
+        let input = if carried == 0 {
+            chunk
+        } else {
+            joined = [self.utf8_tail.as_slice(), chunk].concat();
+            joined.as_slice()
+        };
+        let (text, tail) =
+            decode_utf8(input, self.opts.invalid_utf8).map_err(|at| InvalidUtf8Error {
+                offset: at.saturating_sub(carried),
+            })?;
+        // The carried bytes are part of `input` now; `append` must not flush them again.
+        self.utf8_tail.clear();
+        let update = self.append(&text);
+        self.utf8_tail = tail;
+        Ok(update)
+    }
+
+    /// Finalize a stream fed with [`MdStream::append_bytes`].
+    ///
+    /// Same as [`MdStream::finalize`], except that a truncated final character is reported as
+    /// an error under [`InvalidUtf8Policy::Error`] (the stream is left unfinalized).
+    /// `finalize` itself replaces it with U+FFFD under `Replace` and drops it otherwise.
+    pub fn finalize_bytes(&mut self) -> Result<Update, InvalidUtf8Error> {
+        if self.opts.invalid_utf8 == InvalidUtf8Policy::Error && !self.utf8_tail.is_empty() {
+            return Err(InvalidUtf8Error { offset: 0 });
+        }
+        Ok(self.finalize())
+    }
+
+    /// Take the pending incomplete character as text for a stream that will not receive the rest.
+    pub(super) fn flush_utf8_tail_lossy(&mut self) -> String {
+        if self.utf8_tail.is_empty() {
+            return String::new();
+        }
+        self.utf8_tail.clear();
+        match self.opts.invalid_utf8 {
+            InvalidUtf8Policy::Replace => char::REPLACEMENT_CHARACTER.to_string(),
+            InvalidUtf8Policy::Error | InvalidUtf8Policy::Drop => String::new(),
+        }
+    }
+}
+
+/// Decode `input`, returning the text and an incomplete trailing sequence to carry over.
+///
+/// Fails with the index of the first invalid sequence under [`InvalidUtf8Policy::Error`].
+fn decode_utf8(input: &[u8], policy: InvalidUtf8Policy) -> Result<(Cow<'_, str>, Vec<u8>), usize> {
+    let mut out = String::new();
+    let mut rest = input;
+    loop {
+        let err = match std::str::from_utf8(rest) {
+            Ok(s) if out.is_empty() => return Ok((Cow::Borrowed(s), Vec::new())),
+            Ok(s) => {
+                out.push_str(s);
+                return Ok((Cow::Owned(out), Vec::new()));
+            }
+            Err(err) => err,
+        };
+        let (valid, after) = rest.split_at(err.valid_up_to());
+        let valid = std::str::from_utf8(valid).expect("prefix is valid UTF-8");
+        let Some(bad_len) = err.error_len() else {
+            // Incomplete sequence at the end: the next chunk may complete it.
+            if out.is_empty() {
+                return Ok((Cow::Borrowed(valid), after.to_vec()));
+            }
+            out.push_str(valid);
+            return Ok((Cow::Owned(out), after.to_vec()));
+        };
+        out.push_str(valid);
+        match policy {
+            InvalidUtf8Policy::Replace => out.push(char::REPLACEMENT_CHARACTER),
+            InvalidUtf8Policy::Drop => {}
+            InvalidUtf8Policy::Error => return Err(input.len() - after.len()),
+        }
+        rest = &after[bad_len..];
+    }
+}
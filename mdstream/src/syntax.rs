@@ -108,3 +108,166 @@ pub fn is_list_marker_line_prefix(line: &str) -> bool {
     }
     has_ws
 }
+
+/// Column alignment declared by a table delimiter row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TableAlignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// Parse a GFM table delimiter row (eg `| :-- | :-: | --: |`) into per-column alignments.
+///
+/// Accepts the same lines the stream treats as table delimiters; returns `None` otherwise.
+pub fn parse_table_delimiter_row(line: &str) -> Option<Vec<TableAlignment>> {
+    let mut s = line.trim();
+    if s.is_empty() {
+        return None;
+    }
+    s = s.strip_prefix('|').unwrap_or(s);
+    s = s.strip_suffix('|').unwrap_or(s);
+
+    let mut out = Vec::new();
+    for cell in s.split('|') {
+        let cell = cell.trim_matches([' ', '\t']);
+        let left = cell.starts_with(':');
+        let right = cell.len() > 1 && cell.ends_with(':');
+        let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+        if dashes.is_empty() || !dashes.bytes().all(|b| b == b'-') {
+            return None;
+        }
+        out.push(match (left, right) {
+            (true, true) => TableAlignment::Center,
+            (true, false) => TableAlignment::Left,
+            (false, true) => TableAlignment::Right,
+            (false, false) => TableAlignment::None,
+        });
+    }
+    Some(out)
+}
+
+/// Number of cells in a table row, ignoring outer pipes and escaped `\|`.
+pub(crate) fn table_cell_count(line: &str) -> usize {
+    let mut s = line.trim();
+    s = s.strip_prefix('|').unwrap_or(s);
+    if s.ends_with('|') && !s.ends_with("\\|") {
+        s = &s[..s.len() - 1];
+    }
+    let bytes = s.as_bytes();
+    let mut cells = 1;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'|' => cells += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    cells
+}
+
+/// Front matter flavor, chosen by the delimiter on the first line of the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrontMatterFormat {
+    /// `---` ... `---` (or `...`)
+    Yaml,
+    /// `+++` ... `+++`
+    Toml,
+}
+
+/// Parse an opening front matter delimiter line (`---` or `+++`).
+pub fn parse_front_matter_open(line: &str) -> Option<FrontMatterFormat> {
+    match line.trim_end() {
+        "---" => Some(FrontMatterFormat::Yaml),
+        "+++" => Some(FrontMatterFormat::Toml),
+        _ => None,
+    }
+}
+
+/// Whether `line` closes front matter opened as `format`.
+pub fn is_front_matter_close(line: &str, format: FrontMatterFormat) -> bool {
+    matches!(
+        (format, line.trim_end()),
+        (FrontMatterFormat::Yaml, "---" | "...") | (FrontMatterFormat::Toml, "+++")
+    )
+}
+
+/// GitHub alert type (`> [!NOTE]`, `> [!WARNING]`, ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlertKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl AlertKind {
+    pub const ALL: [AlertKind; 5] = [
+        AlertKind::Note,
+        AlertKind::Tip,
+        AlertKind::Important,
+        AlertKind::Warning,
+        AlertKind::Caution,
+    ];
+
+    /// Marker name as written between `[!` and `]`.
+    pub fn name(self) -> &'static str {
+        match self {
+            AlertKind::Note => "NOTE",
+            AlertKind::Tip => "TIP",
+            AlertKind::Important => "IMPORTANT",
+            AlertKind::Warning => "WARNING",
+            AlertKind::Caution => "CAUTION",
+        }
+    }
+}
+
+/// Text after the `>` marker of a blockquote line (up to 3 leading spaces, one optional space).
+fn blockquote_content(line: &str) -> Option<&str> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = line[indent..].strip_prefix('>')?;
+    Some(rest.strip_prefix([' ', '\t']).unwrap_or(rest))
+}
+
+/// Parse the first line of an alert blockquote (eg `> [!WARNING] Custom title`).
+///
+/// Returns the alert type (case-insensitive) and the optional title after the marker.
+pub fn parse_alert_marker(line: &str) -> Option<(AlertKind, Option<&str>)> {
+    let rest = blockquote_content(line)?.strip_prefix("[!")?;
+    let (name, title) = rest.split_once(']')?;
+    let kind = AlertKind::ALL
+        .into_iter()
+        .find(|k| k.name().eq_ignore_ascii_case(name))?;
+    let title = title.trim();
+    Some((kind, (!title.is_empty()).then_some(title)))
+}
+
+/// Whether an incomplete first blockquote line may still turn into an alert marker line.
+pub fn is_alert_marker_prefix(line: &str) -> bool {
+    let Some(rest) = blockquote_content(line) else {
+        return false;
+    };
+    if "[!".starts_with(rest) {
+        return true;
+    }
+    let Some(rest) = rest.strip_prefix("[!") else {
+        return false;
+    };
+    match rest.split_once(']') {
+        // Marker done; the title may still be streaming.
+        Some(_) => parse_alert_marker(line).is_some(),
+        None => AlertKind::ALL.into_iter().any(|k| {
+            k.name().len() >= rest.len() && k.name()[..rest.len()].eq_ignore_ascii_case(rest)
+        }),
+    }
+}
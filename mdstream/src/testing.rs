@@ -0,0 +1,355 @@
+//! Chunking-invariance checks for custom plugins and transformers.
+//!
+//! A stream must produce the same blocks no matter how its input is split into chunks.
+//! [`ChunkingVerifier`] feeds one input through fresh [`MdStream`]s, once as a single chunk and
+//! once per [`ChunkStrategy`] chunking, and compares the results.
+
+use std::fmt;
+
+use crate::options::Options;
+use crate::state::DocumentState;
+use crate::stream::MdStream;
+use crate::types::Block;
+
+/// How to split an input into chunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkStrategy {
+    /// One chunk per byte. Multi-byte characters are split, so such inputs are fed through
+    /// [`MdStream::append_bytes`].
+    Bytes,
+    /// One chunk per `char`.
+    Chars,
+    /// One chunk per line, newline included.
+    Lines,
+    /// Chunks of 1 to `max_bytes` bytes (extended to a char boundary), from a seeded generator.
+    Random { seed: u64, max_bytes: usize },
+    /// Every two-chunk split `[..i]`, `[i..]`, for each byte offset `i`. Runs the input once per
+    /// byte, so keep inputs short.
+    EverySplit,
+}
+
+impl ChunkStrategy {
+    /// `trials` random strategies with seeds `0..trials`.
+    pub fn random(trials: u64, max_bytes: usize) -> impl Iterator<Item = ChunkStrategy> {
+        (0..trials).map(move |seed| ChunkStrategy::Random { seed, max_bytes })
+    }
+
+    /// The chunkings of `text` this strategy produces (one, except for `EverySplit`).
+    pub fn chunkings<'a>(&self, text: &'a str) -> Vec<Vec<&'a [u8]>> {
+        let bytes = text.as_bytes();
+        match self {
+            Self::Bytes => vec![bytes.chunks(1).collect()],
+            Self::Chars => vec![
+                text.char_indices()
+                    .map(|(i, c)| &bytes[i..i + c.len_utf8()])
+                    .collect(),
+            ],
+            Self::Lines => vec![text.split_inclusive('\n').map(str::as_bytes).collect()],
+            Self::Random { seed, max_bytes } => {
+                let max_bytes = (*max_bytes).max(1);
+                // xorshift64 needs a non-zero state.
+                let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+                let mut chunks = Vec::new();
+                let mut start = 0;
+                while start < text.len() {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    let mut end = (start + (state as usize % max_bytes) + 1).min(text.len());
+                    while !text.is_char_boundary(end) {
+                        end += 1;
+                    }
+                    chunks.push(&bytes[start..end]);
+                    start = end;
+                }
+                vec![chunks]
+            }
+            Self::EverySplit => (1..bytes.len())
+                .map(|i| vec![&bytes[..i], &bytes[i..]])
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for ChunkStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bytes => f.write_str("per byte"),
+            Self::Chars => f.write_str("per char"),
+            Self::Lines => f.write_str("per line"),
+            Self::Random { seed, max_bytes } => {
+                write!(f, "random (seed {seed}, max {max_bytes} bytes)")
+            }
+            Self::EverySplit => f.write_str("every split point"),
+        }
+    }
+}
+
+/// Which part of the result differed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MismatchStage {
+    /// Committed blocks after all chunks were appended.
+    Committed,
+    /// The pending block after all chunks were appended.
+    Pending,
+    /// Committed blocks after `finalize`.
+    Finalized,
+}
+
+impl fmt::Display for MismatchStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Committed => "committed blocks before finalize",
+            Self::Pending => "pending block before finalize",
+            Self::Finalized => "blocks after finalize",
+        })
+    }
+}
+
+/// A chunking whose result differs from feeding the input as a single chunk.
+///
+/// `Display` renders a readable report: the strategy, the first differing block and a line diff
+/// of its text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkingMismatch {
+    pub strategy: ChunkStrategy,
+    /// The chunks that were fed (lossy UTF-8).
+    pub chunks: Vec<String>,
+    pub stage: MismatchStage,
+    /// Index of the first differing block (always 0 for [`MismatchStage::Pending`]).
+    pub index: usize,
+    /// Block from the single-chunk run; `None` if that run has fewer blocks.
+    pub expected: Option<Block>,
+    /// Block from the chunked run; `None` if that run has fewer blocks.
+    pub actual: Option<Block>,
+}
+
+impl fmt::Display for ChunkingMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "chunking `{}` ({} chunks) diverged from a single append: {}, block #{}",
+            self.strategy,
+            self.chunks.len(),
+            self.stage,
+            self.index
+        )?;
+        writeln!(f, "  expected: {}", BlockSummary(self.expected.as_ref()))?;
+        writeln!(f, "  actual:   {}", BlockSummary(self.actual.as_ref()))?;
+        let raw = |b: &Option<Block>| b.as_ref().map(|b| b.raw.clone()).unwrap_or_default();
+        write_line_diff(f, "raw", &raw(&self.expected), &raw(&self.actual))?;
+        let display = |b: &Option<Block>| b.as_ref().and_then(|b| b.display.clone());
+        let (expected_display, actual_display) = (display(&self.expected), display(&self.actual));
+        if expected_display != actual_display {
+            write_line_diff(
+                f,
+                "display",
+                expected_display.as_deref().unwrap_or_default(),
+                actual_display.as_deref().unwrap_or_default(),
+            )?;
+        }
+        const SHOWN_CHUNKS: usize = 16;
+        write!(
+            f,
+            "  chunks: {:?}",
+            &self.chunks[..self.chunks.len().min(SHOWN_CHUNKS)]
+        )?;
+        if self.chunks.len() > SHOWN_CHUNKS {
+            write!(f, " and {} more", self.chunks.len() - SHOWN_CHUNKS)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ChunkingMismatch {}
+
+struct BlockSummary<'a>(Option<&'a Block>);
+
+impl fmt::Display for BlockSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(b) = self.0 else {
+            return f.write_str("(none)");
+        };
+        write!(
+            f,
+            "{:?} {:?} bytes {}..{} lines {}..{}",
+            b.kind, b.id, b.span.start, b.span.end, b.span.start_line, b.span.end_line
+        )?;
+        if let Some(fragment) = &b.fragment {
+            write!(f, " {fragment:?}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Lines shared at the start and end are shown once; the differing middle as `-` / `+`.
+fn write_line_diff(
+    f: &mut fmt::Formatter<'_>,
+    label: &str,
+    expected: &str,
+    actual: &str,
+) -> fmt::Result {
+    writeln!(f, "  {label}:")?;
+    let expected: Vec<&str> = expected.split_inclusive('\n').collect();
+    let actual: Vec<&str> = actual.split_inclusive('\n').collect();
+    let prefix = expected
+        .iter()
+        .zip(&actual)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = expected[prefix..]
+        .iter()
+        .rev()
+        .zip(actual[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    for line in &expected[..prefix] {
+        writeln!(f, "     {line:?}")?;
+    }
+    for line in &expected[prefix..expected.len() - suffix] {
+        writeln!(f, "    -{line:?}")?;
+    }
+    for line in &actual[prefix..actual.len() - suffix] {
+        writeln!(f, "    +{line:?}")?;
+    }
+    for line in &expected[expected.len() - suffix..] {
+        writeln!(f, "     {line:?}")?;
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Outcome {
+    committed: Vec<Block>,
+    pending: Option<Block>,
+    finalized: Vec<Block>,
+}
+
+fn run(mut stream: MdStream, chunks: &[&[u8]]) -> Outcome {
+    let mut state = DocumentState::new();
+    // `append` would flush a character split by `append_bytes`, so use one or the other.
+    let as_str: Option<Vec<&str>> = chunks.iter().map(|c| std::str::from_utf8(c).ok()).collect();
+    match &as_str {
+        Some(chunks) => {
+            for chunk in chunks {
+                state.apply(stream.append(chunk));
+            }
+        }
+        None => {
+            for chunk in chunks {
+                let update = stream
+                    .append_bytes(chunk)
+                    .expect("chunks of valid UTF-8 text");
+                state.apply(update);
+            }
+        }
+    }
+    let committed = state.committed().to_vec();
+    let pending = state.pending().cloned();
+    state.apply(stream.finalize());
+    Outcome {
+        committed,
+        pending,
+        finalized: state.committed().to_vec(),
+    }
+}
+
+fn first_difference(
+    expected: &[Block],
+    actual: &[Block],
+) -> Option<(usize, Option<Block>, Option<Block>)> {
+    let len = expected.len().max(actual.len());
+    (0..len)
+        .find(|&i| expected.get(i) != actual.get(i))
+        .map(|i| (i, expected.get(i).cloned(), actual.get(i).cloned()))
+}
+
+/// Checks that chunked input produces the same blocks as a single append.
+///
+/// Each run uses a fresh stream from the factory, so install the plugins and transformers under
+/// test there. Compared are the committed blocks and the pending block (raw and display) after
+/// the last chunk, and the blocks after `finalize`, including IDs, spans and fragments.
+pub struct ChunkingVerifier<F> {
+    make_stream: F,
+    strategies: Vec<ChunkStrategy>,
+}
+
+impl ChunkingVerifier<Box<dyn FnMut() -> MdStream>> {
+    /// Verify plain streams built from `opts`.
+    pub fn with_options(opts: Options) -> Self {
+        ChunkingVerifier::new(Box::new(move || MdStream::new(opts.clone())))
+    }
+}
+
+impl<F> ChunkingVerifier<F>
+where
+    F: FnMut() -> MdStream,
+{
+    /// Default strategies: per line, per char, per byte and 8 random chunkings of up to 16 bytes.
+    pub fn new(make_stream: F) -> Self {
+        let mut strategies = vec![
+            ChunkStrategy::Lines,
+            ChunkStrategy::Chars,
+            ChunkStrategy::Bytes,
+        ];
+        strategies.extend(ChunkStrategy::random(8, 16));
+        Self {
+            make_stream,
+            strategies,
+        }
+    }
+
+    /// Replace the strategies to run.
+    pub fn strategies(mut self, strategies: impl IntoIterator<Item = ChunkStrategy>) -> Self {
+        self.strategies = strategies.into_iter().collect();
+        self
+    }
+
+    /// Add a strategy to those already configured.
+    pub fn strategy(mut self, strategy: ChunkStrategy) -> Self {
+        self.strategies.push(strategy);
+        self
+    }
+
+    /// Run every strategy on `text`; returns the first mismatch.
+    pub fn verify(&mut self, text: &str) -> Result<(), Box<ChunkingMismatch>> {
+        let expected = run((self.make_stream)(), &[text.as_bytes()]);
+        for strategy in &self.strategies {
+            for chunks in strategy.chunkings(text) {
+                let actual = run((self.make_stream)(), &chunks);
+                let mismatch = |stage, (index, expected, actual)| {
+                    Box::new(ChunkingMismatch {
+                        strategy: strategy.clone(),
+                        chunks: chunks
+                            .iter()
+                            .map(|c| String::from_utf8_lossy(c).into_owned())
+                            .collect(),
+                        stage,
+                        index,
+                        expected,
+                        actual,
+                    })
+                };
+                if let Some(diff) = first_difference(&expected.committed, &actual.committed) {
+                    return Err(mismatch(MismatchStage::Committed, diff));
+                }
+                if expected.pending != actual.pending {
+                    let diff = (0, expected.pending.clone(), actual.pending.clone());
+                    return Err(mismatch(MismatchStage::Pending, diff));
+                }
+                if let Some(diff) = first_difference(&expected.finalized, &actual.finalized) {
+                    return Err(mismatch(MismatchStage::Finalized, diff));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Like [`ChunkingVerifier::verify`], but panics with the mismatch report.
+    #[track_caller]
+    pub fn assert_invariant(&mut self, text: &str) {
+        if let Err(mismatch) = self.verify(text) {
+            panic!("{mismatch}");
+        }
+    }
+}
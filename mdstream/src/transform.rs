@@ -1,10 +1,16 @@
-use crate::types::BlockKind;
+use crate::json::{json_block_body, repair_partial_json};
+use crate::syntax::{
+    TableAlignment, is_alert_marker_prefix, parse_table_delimiter_row, table_cell_count,
+};
+use crate::types::{BlockKind, CustomBlock};
 
 #[derive(Debug, Clone, Copy)]
 pub struct PendingTransformInput<'a> {
     pub kind: BlockKind,
     pub raw: &'a str,
     pub display: &'a str,
+    /// Plugin-declared kind, for [`BlockKind::Custom`] blocks.
+    pub custom: Option<&'a CustomBlock>,
 }
 
 #[cfg(feature = "sync")]
@@ -18,7 +24,31 @@ pub trait PendingTransformer: Send + Sync {
     /// Return `Some(new_display)` to replace `display`, or `None` to leave it unchanged.
     fn transform(&mut self, input: PendingTransformInput<'_>) -> Option<String>;
 
+    /// Whether `transform` may change the display of a `kind` block (`custom` as in
+    /// [`PendingTransformInput::custom`]).
+    ///
+    /// Transformers that return `false` are skipped for that block; when none applies to a pending
+    /// code fence, its display is extended incrementally instead of rebuilt on every append.
+    fn applies_to(&self, _kind: BlockKind, _custom: Option<&CustomBlock>) -> bool {
+        true
+    }
+
     fn reset(&mut self) {}
+
+    /// Capture internal state for [`crate::MdStream::checkpoint`].
+    ///
+    /// Return `None` (the default) for stateless transformers; they are `reset` on restore.
+    fn checkpoint(&self) -> Option<String> {
+        None
+    }
+
+    /// Restore state previously returned by [`PendingTransformer::checkpoint`].
+    ///
+    /// Return `false` if `state` cannot be parsed; [`crate::MdStream::restore`] then fails with
+    /// [`crate::RestoreError::Corrupt`].
+    fn restore(&mut self, _state: &str) -> bool {
+        true
+    }
 }
 
 #[cfg(not(feature = "sync"))]
@@ -32,7 +62,31 @@ pub trait PendingTransformer: Send {
     /// Return `Some(new_display)` to replace `display`, or `None` to leave it unchanged.
     fn transform(&mut self, input: PendingTransformInput<'_>) -> Option<String>;
 
+    /// Whether `transform` may change the display of a `kind` block (`custom` as in
+    /// [`PendingTransformInput::custom`]).
+    ///
+    /// Transformers that return `false` are skipped for that block; when none applies to a pending
+    /// code fence, its display is extended incrementally instead of rebuilt on every append.
+    fn applies_to(&self, _kind: BlockKind, _custom: Option<&CustomBlock>) -> bool {
+        true
+    }
+
     fn reset(&mut self) {}
+
+    /// Capture internal state for [`crate::MdStream::checkpoint`].
+    ///
+    /// Return `None` (the default) for stateless transformers; they are `reset` on restore.
+    fn checkpoint(&self) -> Option<String> {
+        None
+    }
+
+    /// Restore state previously returned by [`PendingTransformer::checkpoint`].
+    ///
+    /// Return `false` if `state` cannot be parsed; [`crate::MdStream::restore`] then fails with
+    /// [`crate::RestoreError::Corrupt`].
+    fn restore(&mut self, _state: &str) -> bool {
+        true
+    }
 }
 
 pub struct FnPendingTransformer<F>(pub F);
@@ -84,10 +138,17 @@ impl Default for IncompleteLinkPlaceholderTransformer {
     }
 }
 
+/// Code and front matter are not Markdown, so link and image repair leaves them alone.
+fn is_markdown_kind(kind: BlockKind) -> bool {
+    !matches!(
+        kind,
+        BlockKind::CodeFence | BlockKind::IndentedCode | BlockKind::FrontMatter
+    )
+}
+
 impl PendingTransformer for IncompleteLinkPlaceholderTransformer {
     fn transform(&mut self, input: PendingTransformInput<'_>) -> Option<String> {
-        // Avoid touching code fences entirely.
-        if matches!(input.kind, BlockKind::CodeFence) {
+        if !is_markdown_kind(input.kind) {
             return None;
         }
         let (window, offset) = tail_window(input.display, self.window_bytes);
@@ -105,6 +166,10 @@ impl PendingTransformer for IncompleteLinkPlaceholderTransformer {
         out.push_str(&fixed);
         Some(out)
     }
+
+    fn applies_to(&self, kind: BlockKind, _custom: Option<&CustomBlock>) -> bool {
+        is_markdown_kind(kind)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -122,7 +187,7 @@ impl Default for IncompleteImageDropTransformer {
 
 impl PendingTransformer for IncompleteImageDropTransformer {
     fn transform(&mut self, input: PendingTransformInput<'_>) -> Option<String> {
-        if matches!(input.kind, BlockKind::CodeFence) {
+        if !is_markdown_kind(input.kind) {
             return None;
         }
         let (window, offset) = tail_window(input.display, self.window_bytes);
@@ -135,4 +200,201 @@ impl PendingTransformer for IncompleteImageDropTransformer {
         out.push_str(&fixed);
         Some(out)
     }
+
+    fn applies_to(&self, kind: BlockKind, _custom: Option<&CustomBlock>) -> bool {
+        is_markdown_kind(kind)
+    }
+}
+
+/// Hides the first line of a pending blockquote while it may still become an alert marker
+/// (`> [!NOTE]`), so UIs do not flash a plain quote before switching to a callout.
+///
+/// Once the line is complete, [`crate::AlertAnalyzer`] reports the alert type.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AlertMarkerTransformer;
+
+impl PendingTransformer for AlertMarkerTransformer {
+    fn transform(&mut self, input: PendingTransformInput<'_>) -> Option<String> {
+        if input.kind != BlockKind::BlockQuote || input.raw.contains('\n') {
+            return None;
+        }
+        if !is_alert_marker_prefix(input.raw) || input.display.is_empty() {
+            return None;
+        }
+        Some(String::new())
+    }
+
+    fn applies_to(&self, kind: BlockKind, _custom: Option<&CustomBlock>) -> bool {
+        kind == BlockKind::BlockQuote
+    }
+}
+
+/// Keeps a half-streamed pipe table rendering as a table from its first line, instead of as a
+/// paragraph that turns into a table once the delimiter row arrives.
+///
+/// A pending paragraph that starts with a pipe-led header row gets a delimiter row matching the
+/// header's column count (keeping alignments already typed), and the last row of a pending table
+/// is padded with empty cells up to the column count. Rows pending on their own under
+/// `Options::commit_table_rows` carry no header and are left as is.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TableRepairTransformer;
+
+impl PendingTransformer for TableRepairTransformer {
+    fn transform(&mut self, input: PendingTransformInput<'_>) -> Option<String> {
+        let out = match input.kind {
+            BlockKind::Paragraph => complete_table_head(input.display)?,
+            BlockKind::Table => pad_last_table_row(input.display)?,
+            _ => return None,
+        };
+        (out != input.display).then_some(out)
+    }
+
+    fn applies_to(&self, kind: BlockKind, _custom: Option<&CustomBlock>) -> bool {
+        matches!(kind, BlockKind::Paragraph | BlockKind::Table)
+    }
+}
+
+/// Header row plus a (possibly partial) delimiter row → header plus a complete delimiter row.
+fn complete_table_head(text: &str) -> Option<String> {
+    let (header, partial) = match text.split_once('\n') {
+        Some((header, rest)) => (header, rest),
+        None => (text, ""),
+    };
+    let header_content = header.trim_start().strip_prefix('|')?;
+    if partial.contains('\n') || header_content.trim_matches([' ', '\t', '|']).is_empty() {
+        return None;
+    }
+    let mut alignments = partial_delimiter_alignments(partial)?;
+    alignments.resize(table_cell_count(header), TableAlignment::None);
+
+    let mut out = String::with_capacity(header.len() + 1 + 6 * alignments.len() + 1);
+    out.push_str(header);
+    out.push('\n');
+    out.push('|');
+    for alignment in alignments {
+        out.push_str(match alignment {
+            TableAlignment::None => " --- |",
+            TableAlignment::Left => " :-- |",
+            TableAlignment::Center => " :-: |",
+            TableAlignment::Right => " --: |",
+        });
+    }
+    Some(out)
+}
+
+/// Alignments of the cells typed so far on a pipe-led delimiter row, or `None` if `line` cannot
+/// become one.
+fn partial_delimiter_alignments(line: &str) -> Option<Vec<TableAlignment>> {
+    let s = line.trim_start();
+    if s.is_empty() {
+        return Some(Vec::new());
+    }
+    let s = s.strip_prefix('|')?;
+    if !s
+        .bytes()
+        .all(|b| matches!(b, b'|' | b':' | b'-' | b' ' | b'\t'))
+    {
+        return None;
+    }
+    let alignments = s
+        .split('|')
+        .map(|cell| cell.trim_matches([' ', '\t']))
+        .filter(|cell| !cell.is_empty())
+        .map(
+            |cell| match (cell.starts_with(':'), cell.len() > 1 && cell.ends_with(':')) {
+                (true, true) => TableAlignment::Center,
+                (true, false) => TableAlignment::Left,
+                (false, true) => TableAlignment::Right,
+                (false, false) => TableAlignment::None,
+            },
+        )
+        .collect();
+    Some(alignments)
+}
+
+/// Pad the last body row of a table with empty cells up to the delimiter row's column count.
+fn pad_last_table_row(text: &str) -> Option<String> {
+    let mut lines = text.split('\n');
+    let header = lines.next()?;
+    let delimiter = lines.next()?;
+    let columns = parse_table_delimiter_row(delimiter)?.len();
+    let body_start = header.len() + delimiter.len() + 2;
+    let row_start = text.rfind('\n')? + 1;
+    if row_start < body_start {
+        return None;
+    }
+    let row = text[row_start..].trim_end();
+    if row.trim_start().is_empty() {
+        return None;
+    }
+
+    let mut out = String::with_capacity(row_start + row.len() + 2 * columns + 2);
+    out.push_str(&text[..row_start]);
+    out.push_str(row);
+    let missing = if row.trim_start() == "|" {
+        columns
+    } else {
+        let missing = columns.saturating_sub(table_cell_count(row));
+        if missing == 0 {
+            return None;
+        }
+        if !row.ends_with('|') || row.ends_with("\\|") {
+            out.push_str(" |");
+        }
+        missing
+    };
+    for _ in 0..missing {
+        out.push_str(" |");
+    }
+    Some(out)
+}
+
+/// Closes open strings, arrays and objects in the pending display of JSON code fences
+/// (```` ```json ````, `jsonc`, ...) and of custom blocks named in `block_names`, so the display
+/// always holds parseable JSON (see [`crate::repair_partial_json`]).
+///
+/// For code fences the repaired body is spliced into the display, so the closing fence added for
+/// the pending block is kept; custom blocks display the repaired raw text only.
+#[derive(Debug, Default, Clone)]
+pub struct PartialJsonTransformer {
+    /// Names of [`BlockKind::Custom`] blocks whose content is JSON.
+    pub block_names: Vec<String>,
+}
+
+impl PartialJsonTransformer {
+    pub fn with_block_name(mut self, name: impl Into<String>) -> Self {
+        self.block_names.push(name.into());
+        self
+    }
+}
+
+impl PendingTransformer for PartialJsonTransformer {
+    fn transform(&mut self, input: PendingTransformInput<'_>) -> Option<String> {
+        json_block_body(input.kind, input.raw, input.custom, &self.block_names)?;
+        // For code fences, repair the body as it appears in the display and keep whatever
+        // earlier stages added after it (the synthetic closing fence). Anything the terminator
+        // appended to a custom block is markdown repair, which is not JSON, so it is dropped.
+        let (body, rest) = if input.kind != BlockKind::CodeFence {
+            (input.raw, "")
+        } else {
+            match input.display.strip_prefix(input.raw) {
+                Some(rest) => (input.raw, rest),
+                None => (input.display, ""),
+            }
+        };
+        let body_start = body.find('\n')? + 1;
+        let repaired = repair_partial_json(&body[body_start..]);
+        if repaired == body[body_start..] {
+            return None;
+        }
+        let mut out = String::with_capacity(body_start + repaired.len() + rest.len());
+        out.push_str(&body[..body_start]);
+        out.push_str(&repaired);
+        out.push_str(rest);
+        Some(out)
+    }
+
+    fn applies_to(&self, kind: BlockKind, custom: Option<&CustomBlock>) -> bool {
+        kind == BlockKind::CodeFence || custom.is_some_and(|c| self.block_names.contains(&c.name))
+    }
 }
@@ -1,6 +1,8 @@
 use std::fmt;
+use std::ops::Range;
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockId(pub u64);
 
 impl fmt::Debug for BlockId {
@@ -10,32 +12,211 @@ impl fmt::Debug for BlockId {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlockStatus {
     Committed,
     Pending,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlockKind {
     Paragraph,
     Heading,
     ThematicBreak,
     CodeFence,
+    /// CommonMark indented (4+ columns) code block.
+    IndentedCode,
     List,
     BlockQuote,
     Table,
     HtmlBlock,
     MathBlock,
     FootnoteDefinition,
+    /// YAML (`---`) or TOML (`+++`) front matter at the start of the stream
+    /// (see `Options::front_matter`).
+    FrontMatter,
+    /// Block claimed by a [`crate::BoundaryPlugin`] that declared a kind for it; the name and
+    /// attributes are in [`Block::custom`].
+    Custom,
     Unknown,
 }
 
+/// Location of a block in the logical document.
+///
+/// Offsets are absolute: they count every byte and line ever appended to the stream (after
+/// newline normalization), so they stay valid after buffer compaction and footnote resets.
+/// Both ranges are half-open.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourceSpan {
+    pub start: usize,
+    pub end: usize,
+    /// Zero-based index of the first line of the block.
+    pub start_line: usize,
+    /// Zero-based index one past the last line of the block.
+    pub end_line: usize,
+}
+
+impl SourceSpan {
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn line_range(&self) -> Range<usize> {
+        self.start_line..self.end_line
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+}
+
+/// Where a block sits inside a construct that the stream emits as several blocks.
+///
+/// Only produced when the matching option is enabled (e.g. [`crate::Options::commit_list_items`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlockFragment {
+    /// One top-level item of a list.
+    ListItem(ListItemFragment),
+    /// Header and delimiter rows of a table.
+    TableHead(TableFragment),
+    /// One body row of a table.
+    TableRow(TableFragment),
+    /// One segment of a code fence split by [`crate::Options::max_pending_bytes`].
+    CodeFenceSegment(CodeFenceFragment),
+    /// Start line of a container split with [`crate::Options::container_scopes`].
+    ContainerOpen(ContainerFragment),
+    /// End line of a container split with [`crate::Options::container_scopes`].
+    ContainerClose(ContainerFragment),
+}
+
+/// List-group metadata for a [`BlockFragment::ListItem`] block.
+///
+/// Consecutive items with the same `list_id` belong to one list and can be rendered together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListItemFragment {
+    /// ID of the block holding the list's first item.
+    pub list_id: BlockId,
+    pub ordered: bool,
+    /// Start number of an ordered list, taken from its first item.
+    pub start: Option<u64>,
+    /// Zero-based position of the item within its list.
+    pub index: usize,
+}
+
+/// Table-group metadata for [`BlockFragment::TableHead`] / [`BlockFragment::TableRow`] blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableFragment {
+    /// ID of the table's head block.
+    pub table_id: BlockId,
+    /// Column count, from the delimiter row.
+    pub columns: usize,
+    /// Zero-based body row index (always `0` for the head).
+    pub row: usize,
+}
+
+/// Fence-group metadata for a [`BlockFragment::CodeFenceSegment`] block.
+///
+/// Segments with the same `fence_id` are consecutive and their `raw` texts concatenate to the
+/// whole fence. Only the first segment holds the opening fence line; only the last one holds the
+/// closing fence line, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodeFenceFragment {
+    /// ID of the fence's first segment.
+    pub fence_id: BlockId,
+    /// Zero-based segment index.
+    pub index: usize,
+    /// Whether this segment ends the fence (closing fence line, or end of stream).
+    pub last: bool,
+}
+
+/// Container metadata for [`BlockFragment::ContainerOpen`] / [`BlockFragment::ContainerClose`]
+/// blocks.
+///
+/// The blocks between a container's open and close blocks have [`Block::parent`] set to
+/// `container_id` (or to a nested container's id).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContainerFragment {
+    /// ID of the container's open block.
+    pub container_id: BlockId,
+    /// Nesting depth (`0` for a top-level container).
+    pub depth: usize,
+}
+
+impl CodeFenceFragment {
+    pub fn is_first(&self) -> bool {
+        self.index == 0
+    }
+
+    /// Neither the first nor the last segment: code lines only.
+    pub fn is_middle(&self) -> bool {
+        self.index > 0 && !self.last
+    }
+}
+
+/// Kind name and start-line attributes declared by a [`crate::BoundaryPlugin`] for a
+/// [`BlockKind::Custom`] block.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CustomBlock {
+    /// Plugin-supplied identifier, e.g. `thinking` for `<thinking>` or `warning` for `::: warning`.
+    pub name: String,
+    /// Attributes of the start line, in source order. Valueless attributes map to `""`.
+    pub attributes: Vec<(String, String)>,
+}
+
+impl CustomBlock {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            attributes: Vec::new(),
+        }
+    }
+
+    pub fn with_attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.push((name.into(), value.into()));
+        self
+    }
+
+    /// Value of the first attribute called `name`.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
     pub id: BlockId,
     pub status: BlockStatus,
     pub kind: BlockKind,
     pub raw: String,
+    /// Where `raw` sits in the whole streamed document.
+    pub span: SourceSpan,
+    /// Set when this block is one piece of a larger construct (e.g. a single list item).
+    pub fragment: Option<BlockFragment>,
+    /// Set for [`BlockKind::Custom`] blocks.
+    pub custom: Option<CustomBlock>,
+    /// Open block of the innermost container holding this block (see
+    /// [`crate::Options::container_scopes`]).
+    pub parent: Option<BlockId>,
     /// Optional display string for pending blocks (remend-like termination, JSON repair, etc.).
     pub display: Option<String>,
 }
@@ -55,6 +236,46 @@ impl Block {
     pub fn code_fence_language(&self) -> Option<&str> {
         self.code_fence_header().and_then(|h| h.language)
     }
+
+    pub fn list_item(&self) -> Option<&ListItemFragment> {
+        match &self.fragment {
+            Some(BlockFragment::ListItem(item)) => Some(item),
+            _ => None,
+        }
+    }
+
+    /// Table metadata for a table head or row block.
+    pub fn table(&self) -> Option<&TableFragment> {
+        match &self.fragment {
+            Some(BlockFragment::TableHead(t) | BlockFragment::TableRow(t)) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Fence-group metadata for a segment of a split code fence.
+    pub fn code_fence_segment(&self) -> Option<&CodeFenceFragment> {
+        match &self.fragment {
+            Some(BlockFragment::CodeFenceSegment(segment)) => Some(segment),
+            _ => None,
+        }
+    }
+
+    /// Container metadata for the open or close block of a scoped container.
+    pub fn container(&self) -> Option<&ContainerFragment> {
+        match &self.fragment {
+            Some(BlockFragment::ContainerOpen(c) | BlockFragment::ContainerClose(c)) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Column alignments of a table head block, parsed from its delimiter row.
+    pub fn table_alignments(&self) -> Option<Vec<crate::syntax::TableAlignment>> {
+        if !matches!(self.fragment, Some(BlockFragment::TableHead(_))) {
+            return None;
+        }
+        let delimiter = self.raw.split('\n').nth(1)?;
+        crate::syntax::parse_table_delimiter_row(delimiter)
+    }
 }
 
 /// A borrowed view of the current pending block.
@@ -68,6 +289,10 @@ pub struct PendingBlockRef<'a> {
     pub id: BlockId,
     pub kind: BlockKind,
     pub raw: &'a str,
+    pub span: SourceSpan,
+    pub fragment: Option<BlockFragment>,
+    pub custom: Option<&'a CustomBlock>,
+    pub parent: Option<BlockId>,
     /// Optional terminated/transformed display string for pending.
     ///
     /// When present, this is usually safer to feed into downstream Markdown parsers/renderers.
@@ -80,10 +305,75 @@ impl<'a> PendingBlockRef<'a> {
     }
 }
 
+/// Change to one text field of the pending block since the previous update.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextDelta {
+    /// The new text is the old text followed by these bytes.
+    Append(String),
+    /// Keep the first `offset` bytes of the old text and replace the rest with `text`.
+    ReplaceFrom { offset: usize, text: String },
+}
+
+impl TextDelta {
+    pub fn apply(&self, target: &mut String) {
+        match self {
+            Self::Append(text) => target.push_str(text),
+            Self::ReplaceFrom { offset, text } => {
+                target.truncate(*offset);
+                target.push_str(text);
+            }
+        }
+    }
+}
+
+/// The pending block expressed relative to the one sent in the previous update.
+///
+/// Only produced with [`crate::Options::pending_delta`], and only while the same block stays
+/// pending. Kind, span and fragment are small and always sent in full.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PendingDelta {
+    /// ID of the pending block this delta applies to.
+    pub id: BlockId,
+    pub kind: BlockKind,
+    pub span: SourceSpan,
+    pub fragment: Option<BlockFragment>,
+    /// Bytes appended to `raw` (the raw text of a pending block only ever grows).
+    pub raw: String,
+    pub display: TextDelta,
+}
+
+impl PendingDelta {
+    /// Apply this delta to `block`. Returns `false` (leaving `block` untouched) if it is a
+    /// different block.
+    pub fn apply(&self, block: &mut Block) -> bool {
+        if block.id != self.id {
+            return false;
+        }
+        block.kind = self.kind;
+        block.span = self.span;
+        block.fragment = self.fragment;
+        block.raw.push_str(&self.raw);
+        self.display
+            .apply(block.display.get_or_insert_with(String::new));
+        true
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Update {
     pub committed: Vec<Block>,
+    /// The current pending block in full, or `None` if there is none.
+    ///
+    /// With [`crate::Options::pending_delta`] this is also `None` whenever `pending_delta` is set.
     pub pending: Option<Block>,
+    /// The current pending block as a change to the previous update's pending block.
+    ///
+    /// Only set with [`crate::Options::pending_delta`]. When present, `pending` is `None` and
+    /// [`Update::apply_to`] patches the existing pending block instead of replacing it.
+    pub pending_delta: Option<PendingDelta>,
     /// If true, consumers must drop all previously rendered state and rebuild from this update.
     ///
     /// This is used for scope-driven transitions that inherently require a full re-parse (e.g.
@@ -93,12 +383,17 @@ pub struct Update {
     ///
     /// Note: populated in post-MVP invalidation mode.
     pub invalidated: Vec<BlockId>,
+    /// Previously emitted block IDs that no longer exist and must be dropped.
+    ///
+    /// Populated by [`crate::MdStream::truncate_to`]. Removals apply before `committed`.
+    pub removed: Vec<BlockId>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AppliedUpdate {
     pub reset: bool,
     pub invalidated: Vec<BlockId>,
+    pub removed: Vec<BlockId>,
 }
 
 /// A borrowed update view that avoids allocating the pending block.
@@ -133,10 +428,16 @@ impl<'a> UpdateRef<'a> {
                 status: BlockStatus::Pending,
                 kind: p.kind,
                 raw: p.raw.to_string(),
+                span: p.span,
+                fragment: p.fragment,
+                custom: p.custom.cloned(),
+                parent: p.parent,
                 display: p.display.map(|d| d.to_string()),
             }),
+            pending_delta: None,
             reset: self.reset,
             invalidated: self.invalidated.clone(),
+            removed: Vec::new(),
         }
     }
 }
@@ -146,22 +447,33 @@ impl Update {
         Self {
             committed: Vec::new(),
             pending: None,
+            pending_delta: None,
             reset: false,
             invalidated: Vec::new(),
+            removed: Vec::new(),
         }
     }
 
     pub fn is_empty(&self) -> bool {
         self.committed.is_empty()
             && self.pending.is_none()
+            && self.pending_delta.is_none()
             && !self.reset
             && self.invalidated.is_empty()
+            && self.removed.is_empty()
     }
 
+    /// Newly committed blocks followed by the pending block.
+    ///
+    /// A pending block sent as [`Update::pending_delta`] is not included.
     pub fn blocks(&self) -> impl Iterator<Item = &Block> {
         self.committed.iter().chain(self.pending.iter())
     }
 
+    /// Apply this update to a consumer's committed list and pending block.
+    ///
+    /// A [`Update::pending_delta`] is applied to `pending`; if `pending` is not the block it was
+    /// computed against (updates skipped or applied out of order), `pending` is cleared.
     pub fn apply_to(
         self,
         committed: &mut Vec<Block>,
@@ -171,11 +483,22 @@ impl Update {
             committed.clear();
             *pending = None;
         }
+        if !self.removed.is_empty() {
+            committed.retain(|b| !self.removed.contains(&b.id));
+        }
         committed.extend(self.committed);
-        *pending = self.pending;
+        match self.pending_delta {
+            Some(delta) => {
+                if !pending.as_mut().is_some_and(|p| delta.apply(p)) {
+                    *pending = None;
+                }
+            }
+            None => *pending = self.pending,
+        }
         AppliedUpdate {
             reset: self.reset,
             invalidated: self.invalidated,
+            removed: self.removed,
         }
     }
 }
@@ -0,0 +1,73 @@
+use mdstream::{
+    AlertAnalyzer, AlertKind, AlertMarkerTransformer, AnalyzedStream, BlockKind, MdStream, Options,
+    parse_alert_marker,
+};
+
+#[test]
+fn alert_type_is_reported_once_the_first_line_completes() {
+    let mut s = AnalyzedStream::new(Options::default(), AlertAnalyzer);
+    let u = s.append("> [!WARN");
+    assert_eq!(
+        u.update.pending.expect("pending").kind,
+        BlockKind::BlockQuote
+    );
+    assert!(u.pending_meta.is_none());
+
+    let u = s.append("ING] Mind the gap\n> Body");
+    let meta = u.pending_meta.expect("pending meta").meta;
+    assert_eq!(meta.kind, AlertKind::Warning);
+    assert_eq!(meta.title.as_deref(), Some("Mind the gap"));
+    assert!(!meta.closed);
+
+    let u = s.append("\n\nAfter\n");
+    let committed = &u.committed_meta[0];
+    assert!(committed.meta.closed);
+    assert_eq!(
+        s.meta_for(committed.id).map(|m| m.kind),
+        Some(AlertKind::Warning)
+    );
+}
+
+#[test]
+fn plain_quotes_and_unknown_types_have_no_meta() {
+    let mut s = AnalyzedStream::new(Options::default(), AlertAnalyzer);
+    let u = s.append("> [!FOO]\n> x\n\nText\n\n> plain\n\n");
+    assert_eq!(u.update.committed.len(), 2);
+    assert!(u.committed_meta.is_empty());
+}
+
+#[test]
+fn marker_parsing() {
+    assert_eq!(
+        parse_alert_marker("> [!note]"),
+        Some((AlertKind::Note, None))
+    );
+    assert_eq!(
+        parse_alert_marker(">[!TIP]  Pro tip "),
+        Some((AlertKind::Tip, Some("Pro tip")))
+    );
+    assert_eq!(parse_alert_marker("    > [!NOTE]"), None);
+    assert_eq!(parse_alert_marker("> [NOTE]"), None);
+}
+
+#[test]
+fn transformer_hides_an_incomplete_marker_line() {
+    let mut s = MdStream::new(Options::default()).with_pending_transformer(AlertMarkerTransformer);
+    let mut displays = Vec::new();
+    for chunk in ["> ", "[!", "CAU", "TION", "]", " Hot", "\n", "> Careful"] {
+        let u = s.append(chunk);
+        displays.push(u.pending.and_then(|p| p.display).unwrap_or_default());
+    }
+    assert!(displays[..6].iter().all(|d| d.is_empty()), "{displays:?}");
+    assert_eq!(displays[6], "> [!CAUTION] Hot\n");
+    assert_eq!(displays[7], "> [!CAUTION] Hot\n> Careful");
+
+    // Ordinary quotes show up as soon as they cannot be an alert.
+    let mut s = MdStream::new(Options::default()).with_pending_transformer(AlertMarkerTransformer);
+    let u = s.append("> [!X");
+    assert!(
+        u.pending
+            .and_then(|p| p.display)
+            .is_some_and(|d| d.starts_with("> [!X"))
+    );
+}
@@ -1,5 +1,7 @@
-use mdstream::TagBoundaryPlugin;
-use mdstream::{AnalyzedStream, Options, TaggedBlockAnalyzer};
+use mdstream::{
+    AnalyzedStream, BoundaryUpdate, CustomBlock, FnBoundaryPlugin, Options, TagBoundaryPlugin,
+    TaggedBlockAnalyzer,
+};
 
 #[test]
 fn tagged_block_analyzer_extracts_tag_and_content() {
@@ -13,7 +15,7 @@ fn tagged_block_analyzer_extracts_tag_and_content() {
     let u1 = s.append("<thinking>\nstep 1\n");
     let m1 = u1.pending_meta.expect("pending meta").meta;
     assert_eq!(m1.tag, "thinking");
-    assert_eq!(m1.attributes, None);
+    assert!(m1.attributes.is_empty());
     assert!(!m1.closed);
     assert_eq!(m1.content, "step 1\n");
 
@@ -79,3 +81,44 @@ fn tagged_block_analyzer_handles_tool_call_tag_with_boundary_plugin() {
     assert!(m2.meta.closed);
     assert!(m2.meta.content.contains("{\"name\""));
 }
+
+#[test]
+fn tagged_block_analyzer_parses_attributes_without_a_plugin() {
+    let mut s = AnalyzedStream::new(Options::default(), TaggedBlockAnalyzer::default());
+    let u = s.append("<thinking mode=\"deep\" verbose>\nx\n</thinking>\n\nAfter");
+    let m = &u.committed_meta[0].meta;
+    assert_eq!(
+        m.attributes,
+        vec![
+            ("mode".to_string(), "deep".to_string()),
+            ("verbose".to_string(), String::new()),
+        ]
+    );
+}
+
+#[test]
+fn tagged_block_analyzer_prefers_plugin_declared_name_and_attributes() {
+    let plugin = FnBoundaryPlugin::new(
+        |line| line.starts_with("<think"),
+        |line| {
+            if line.trim() == "</think>" {
+                BoundaryUpdate::Close
+            } else {
+                BoundaryUpdate::Continue
+            }
+        },
+    )
+    .with_custom_block(|_| Some(CustomBlock::new("Reasoning").with_attribute("source", "plugin")));
+    let mut s = AnalyzedStream::new(Options::default(), TaggedBlockAnalyzer::default());
+    s.inner_mut().push_boundary_plugin(plugin);
+
+    let u = s.append("<think depth=2>\nhmm\n</think>\n");
+    let m = &u.committed_meta[0].meta;
+    assert_eq!(m.tag, "reasoning");
+    assert_eq!(
+        m.attributes,
+        vec![("source".to_string(), "plugin".to_string())]
+    );
+    assert!(m.closed);
+    assert_eq!(m.content, "hmm\n");
+}
@@ -0,0 +1,103 @@
+mod support;
+
+use mdstream::{InvalidUtf8Error, InvalidUtf8Policy, MdStream, Options};
+
+const DOC: &str = "# Überschrift 🚀\n\nPárrafo con **énfasis** y 中文字符.\n\n```rust\nlet s = \"日本語\";\n```\n\n- ✅ done\n- ❌ todo\n\n| α | β |\n|---|---|\n| γ | δ |\n\nTail 🎉";
+
+fn opts(policy: InvalidUtf8Policy) -> Options {
+    Options {
+        invalid_utf8: policy,
+        ..Default::default()
+    }
+}
+
+fn assert_bytes_invariant(case_name: &str, bytes: &[u8], expected_text: &str, opts: Options) {
+    let expected = support::collect_final_blocks(support::chunk_whole(expected_text), opts.clone());
+
+    let single: Vec<Vec<u8>> = bytes.iter().map(|b| vec![*b]).collect();
+    let blocks = support::collect_final_blocks_from_bytes(single, opts.clone());
+    assert_eq!(blocks, expected, "case={case_name} chunker=bytes");
+
+    for t in 0..32 {
+        let chunks = support::chunk_bytes_pseudo_random(bytes, case_name, t, 7);
+        let blocks = support::collect_final_blocks_from_bytes(chunks, opts.clone());
+        assert_eq!(blocks, expected, "case={case_name} chunker=rand t={t}");
+    }
+}
+
+#[test]
+fn split_codepoints_match_str_input() {
+    for policy in [
+        InvalidUtf8Policy::Replace,
+        InvalidUtf8Policy::Error,
+        InvalidUtf8Policy::Drop,
+    ] {
+        assert_bytes_invariant("valid", DOC.as_bytes(), DOC, opts(policy));
+    }
+}
+
+#[test]
+fn invalid_sequences_follow_the_policy() {
+    // Stray continuation byte, overlong encoding, and a truncated 4-byte sequence mid-text.
+    let mut bytes = b"Bad \x80 byte\n\nOverlong \xC0\xAF end\n\nCut \xF0\x9F\x9A x".to_vec();
+    bytes.extend_from_slice("\n\nok ✓\n".as_bytes());
+
+    let replaced = String::from_utf8_lossy(&bytes).into_owned();
+    assert_bytes_invariant(
+        "replace",
+        &bytes,
+        &replaced,
+        opts(InvalidUtf8Policy::Replace),
+    );
+
+    let dropped = replaced.replace('\u{FFFD}', "");
+    assert_bytes_invariant("drop", &bytes, &dropped, opts(InvalidUtf8Policy::Drop));
+}
+
+#[test]
+fn error_policy_rejects_the_chunk_and_keeps_state() {
+    let mut s = MdStream::new(opts(InvalidUtf8Policy::Error));
+    s.append_bytes("A é".as_bytes()).unwrap();
+    assert_eq!(
+        s.append_bytes(b"ok \xFF more"),
+        Err(InvalidUtf8Error { offset: 3 })
+    );
+    assert_eq!(s.buffer(), "A é");
+
+    // A held-back lead byte followed by a non-continuation byte fails at offset 0.
+    s.append_bytes(b" \xE2\x82").unwrap();
+    assert_eq!(s.buffer(), "A é ");
+    assert_eq!(s.append_bytes(b"A"), Err(InvalidUtf8Error { offset: 0 }));
+    let u = s.append_bytes(b"\xAC!").unwrap();
+    assert_eq!(u.pending.unwrap().raw, "A é €!");
+
+    s.append_bytes(b"\xC3").unwrap();
+    assert_eq!(s.finalize_bytes(), Err(InvalidUtf8Error { offset: 0 }));
+    // Plain `finalize` drops the truncated character.
+    assert_eq!(s.finalize().committed[0].raw, "A é €!");
+}
+
+#[test]
+fn str_append_and_finalize_flush_a_truncated_character() {
+    let mut s = MdStream::new(Options::default());
+    s.append_bytes(b"caf\xC3").unwrap();
+    assert_eq!(s.buffer(), "caf");
+    s.append(" au lait");
+    assert_eq!(s.buffer(), "caf\u{FFFD} au lait");
+
+    s.append_bytes(b" \xE2\x82").unwrap();
+    let u = s.finalize();
+    assert_eq!(u.committed[0].raw, "caf\u{FFFD} au lait \u{FFFD}");
+}
+
+#[test]
+fn checkpoint_keeps_the_partial_character() {
+    let mut s = MdStream::new(Options::default());
+    s.append_bytes(b"x \xF0\x9F").unwrap();
+    let snapshot = s.checkpoint();
+
+    let mut resumed = MdStream::new(Options::default());
+    resumed.restore(snapshot).unwrap();
+    let u = resumed.append_bytes(b"\x9A\x80").unwrap();
+    assert_eq!(u.pending.unwrap().raw, "x 🚀");
+}
@@ -0,0 +1,192 @@
+use mdstream::{
+    AlertMarkerTransformer, BlockKind, BuildError, DocumentState, FootnotesMode, MdStream,
+    MdStreamBuilder, Options, TagBoundaryPlugin,
+};
+
+fn final_state(mut s: MdStream, text: &str, chunk: usize) -> DocumentState {
+    let mut state = DocumentState::new();
+    let mut i = 0;
+    while i < text.len() {
+        let end = (i + chunk).min(text.len());
+        state.apply(s.append(&text[i..end]));
+        i = end;
+    }
+    state
+}
+
+fn pending_display(s: &mut MdStream, text: &str) -> Option<String> {
+    s.append(text).pending.and_then(|p| p.display)
+}
+
+#[test]
+fn streamdown_preset_matches_streamdown_defaults() {
+    let text = "Hello **world** see [docs](http://exa and ![img](http://x";
+    let mut a = MdStreamBuilder::streamdown().build().unwrap();
+    let mut b = MdStream::streamdown_defaults();
+    for chunk in text.split_inclusive(' ') {
+        assert_eq!(a.append(chunk), b.append(chunk));
+    }
+    assert_eq!(a.finalize(), b.finalize());
+}
+
+#[test]
+fn incremark_preset_keeps_containers_and_avoids_footnote_resets() {
+    let text = "Intro [^1]\n\n::: warning\nInside\n\nStill inside\n:::\n\n[^1]: Note\n\nEnd\n";
+    let mut s = MdStreamBuilder::incremark().build().unwrap();
+    let mut blocks = Vec::new();
+    for chunk in text.split_inclusive('\n') {
+        let u = s.append(chunk);
+        assert!(!u.reset);
+        blocks.extend(u.committed);
+    }
+    blocks.extend(s.finalize().committed);
+    let raws: Vec<&str> = blocks.iter().map(|b| b.raw.as_str()).collect();
+    assert!(
+        raws.contains(&"::: warning\nInside\n\nStill inside\n:::\n"),
+        "{raws:?}"
+    );
+    assert!(
+        blocks
+            .iter()
+            .any(|b| b.kind == BlockKind::FootnoteDefinition)
+    );
+}
+
+#[test]
+fn commonmark_strict_preset_shows_raw_text() {
+    let mut s = MdStreamBuilder::commonmark_strict().build().unwrap();
+    let raw = "Some **bold and `code and [link](http";
+    assert_eq!(pending_display(&mut s, raw).as_deref(), Some(raw));
+
+    // Both entry points show an open code fence as received.
+    let fence = "```rust\nfn f() {";
+    let mut s = MdStreamBuilder::commonmark_strict().build().unwrap();
+    assert_eq!(s.append_ref(fence).pending.unwrap().display, Some(fence));
+    let mut s = MdStreamBuilder::commonmark_strict().build().unwrap();
+    assert_eq!(pending_display(&mut s, fence).as_deref(), Some(fence));
+}
+
+#[test]
+fn chat_minimal_preset_repairs_chat_output_without_resets() {
+    let mut s = MdStreamBuilder::chat_minimal().build().unwrap();
+    assert_eq!(
+        pending_display(&mut s, "Some **bold").as_deref(),
+        Some("Some **bold**")
+    );
+    assert_eq!(
+        pending_display(&mut s, "**\n\nSee [docs](http://exa").as_deref(),
+        Some("See [docs](streamdown:incomplete-link)")
+    );
+    let u = s.append(
+        ")
+
+<details><summary>Foo",
+    );
+    assert_eq!(
+        u.pending.unwrap().display.as_deref(),
+        Some("<details><summary>Foo</summary></details>")
+    );
+    let u = s.append(
+        "</summary></details>
+
+ [^1]
+
+[^1]: x
+",
+    );
+    assert!(!u.reset);
+
+    let opts = MdStreamBuilder::chat_minimal().options().clone();
+    assert_eq!(opts.footnotes, FootnotesMode::Invalidate);
+    assert!(opts.close_html_blocks);
+    assert_eq!(opts.max_pending_bytes, Some(16 * 1024));
+}
+
+#[test]
+fn setters_override_the_preset() {
+    let b = MdStreamBuilder::commonmark_strict()
+        .emphasis(true)
+        .commit_list_items(true)
+        .max_pending_bytes(Some(1024));
+    let opts = b.options();
+    assert!(opts.terminator.emphasis && !opts.terminator.links);
+    assert!(opts.commit_list_items);
+    assert_eq!(opts.max_pending_bytes, Some(1024));
+
+    let mut s = b.build().unwrap();
+    assert_eq!(pending_display(&mut s, "a *b").as_deref(), Some("a *b*"));
+}
+
+#[test]
+fn plugins_and_transformers_are_installed_in_order() {
+    let s = MdStreamBuilder::streamdown()
+        .boundary_plugin(TagBoundaryPlugin::thinking())
+        .pending_transformer(AlertMarkerTransformer)
+        .build()
+        .unwrap();
+    let state = final_state(s, "<thinking>\n\nhidden\n\n</thinking>\n\n> [!NO", 4);
+    assert_eq!(state.committed().len(), 1);
+    assert_eq!(
+        state.committed()[0].raw,
+        "<thinking>\n\nhidden\n\n</thinking>\n"
+    );
+    let pending = state.pending().expect("pending");
+    assert_eq!(pending.display.as_deref(), Some(""));
+}
+
+#[test]
+fn incompatible_combinations_are_rejected() {
+    assert_eq!(
+        MdStreamBuilder::streamdown().links(true).build().err(),
+        Some(BuildError::DuplicateLinkHandling)
+    );
+    assert_eq!(
+        MdStreamBuilder::new()
+            .max_pending_bytes(Some(0))
+            .build()
+            .err(),
+        Some(BuildError::ZeroLimit {
+            option: "max_pending_bytes"
+        })
+    );
+    assert_eq!(
+        MdStreamBuilder::new().terminator_window_bytes(0).validate(),
+        Err(BuildError::ZeroLimit {
+            option: "terminator_window_bytes"
+        })
+    );
+    let err = MdStreamBuilder::new()
+        .max_buffer_bytes(Some(512))
+        .max_pending_bytes(Some(4096))
+        .validate()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        BuildError::BufferSmallerThanPending {
+            max_buffer_bytes: 512,
+            max_pending_bytes: 4096
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "max_buffer_bytes (512) is smaller than max_pending_bytes (4096)"
+    );
+}
+
+#[test]
+fn default_builder_matches_default_options() {
+    let text = "# T\n\n- a\n- b\n\nSome *text";
+    let a = final_state(MdStream::builder().build().unwrap(), text, 3);
+    let b = final_state(MdStream::new(Options::default()), text, 3);
+    assert_eq!(a.committed(), b.committed());
+    assert_eq!(a.pending(), b.pending());
+}
+
+#[test]
+fn builder_debug_lists_options_and_counts() {
+    let b = MdStreamBuilder::streamdown().boundary_plugin(TagBoundaryPlugin::thinking());
+    let debug = format!("{b:?}");
+    assert!(debug.starts_with("MdStreamBuilder {"), "{debug}");
+    assert!(debug.contains("link_transformers: true"), "{debug}");
+    assert!(debug.contains("boundary_plugins_len: 1"), "{debug}");
+}
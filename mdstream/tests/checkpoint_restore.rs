@@ -0,0 +1,210 @@
+use mdstream::{
+    BoundaryPlugin, ContainerBoundaryPlugin, FenceBoundaryPlugin,
+    IncompleteLinkPlaceholderTransformer, MdStream, Options, PendingTransformInput,
+    PendingTransformer, RestoreError, TagBoundaryPlugin, Update,
+};
+
+const DOC: &str = "# Title\r\n\r\nSee [docs][ref] and [^1].\n\n<thinking>\nstep one\n\nstep two\n</thinking>\n\n:::note\nInside **bold\n:::\n\n```rust\nfn main() {}\n```\n\n[ref]: https://example.com\n\n[^1]: A footnote.\n\nTail with [link](http://exa";
+
+fn new_stream(opts: Options) -> MdStream {
+    MdStream::new(opts)
+        .with_boundary_plugin(TagBoundaryPlugin::thinking())
+        .with_boundary_plugin(FenceBoundaryPlugin::triple_colon())
+        .with_pending_transformer(IncompleteLinkPlaceholderTransformer::default())
+}
+
+fn chunks(doc: &str, size: usize) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut rest = doc;
+    while !rest.is_empty() {
+        let mut end = size.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end += 1;
+        }
+        let (head, tail) = rest.split_at(end);
+        out.push(head);
+        rest = tail;
+    }
+    out
+}
+
+fn run(s: &mut MdStream, chunks: &[&str]) -> Vec<Update> {
+    let mut out: Vec<Update> = chunks.iter().map(|c| s.append(c)).collect();
+    out.push(s.finalize());
+    out
+}
+
+fn assert_resume_matches(opts: Options, chunk_size: usize) {
+    let parts = chunks(DOC, chunk_size);
+    for split in 0..=parts.len() {
+        let mut original = new_stream(opts.clone());
+        for c in &parts[..split] {
+            original.append(c);
+        }
+        let snapshot = original.checkpoint();
+        let expected = run(&mut original, &parts[split..]);
+
+        let mut resumed = new_stream(opts.clone());
+        resumed.restore(snapshot).expect("restore");
+        let actual = run(&mut resumed, &parts[split..]);
+
+        assert_eq!(actual, expected, "split={split} chunk_size={chunk_size}");
+        assert_eq!(resumed.snapshot_blocks(), original.snapshot_blocks());
+        let (a, b) = (resumed.stats(), original.stats());
+        assert_eq!(a.bytes_appended, b.bytes_appended);
+        assert_eq!(a.lines_processed, b.lines_processed);
+        assert_eq!(a.committed_by_kind, b.committed_by_kind);
+    }
+}
+
+#[test]
+fn restored_stream_produces_identical_updates() {
+    for size in [1, 3, 7, 16] {
+        assert_resume_matches(Options::default(), size);
+    }
+}
+
+#[test]
+fn restored_stream_matches_in_invalidate_modes() {
+    let opts = Options {
+        footnotes: mdstream::FootnotesMode::Invalidate,
+        reference_definitions: mdstream::ReferenceDefinitionsMode::Invalidate,
+        ..Default::default()
+    };
+    for size in [2, 9] {
+        assert_resume_matches(opts.clone(), size);
+    }
+}
+
+#[test]
+fn restored_stream_matches_after_compaction() {
+    let opts = Options {
+        max_buffer_bytes: Some(48),
+        ..Default::default()
+    };
+    for size in [4, 11] {
+        assert_resume_matches(opts.clone(), size);
+    }
+}
+
+#[test]
+fn restore_continues_block_ids() {
+    let mut s = new_stream(Options::default());
+    s.append("A\n\nB\n\n");
+    let snapshot = s.checkpoint();
+    assert_eq!(snapshot.appended_len(), 6);
+
+    let mut resumed = new_stream(Options::default());
+    resumed.restore(snapshot).unwrap();
+    let u = resumed.append("C\n\nD");
+    let ids: Vec<u64> = u.committed.iter().map(|b| b.id.0).collect();
+    assert_eq!(ids, vec![2, 3]);
+    assert_eq!(u.committed[1].span.start, 6);
+    assert_eq!(u.pending.expect("pending").id.0, 4);
+}
+
+#[test]
+fn restore_rejects_mismatched_plugins() {
+    let mut s = new_stream(Options::default());
+    s.append("<thinking>\nhmm");
+    let snapshot = s.checkpoint();
+
+    let mut plain = MdStream::new(Options::default());
+    assert_eq!(
+        plain.restore(snapshot.clone()),
+        Err(RestoreError::BoundaryPluginCount {
+            expected: 0,
+            found: 2
+        })
+    );
+    assert_eq!(plain.buffer(), "");
+
+    let mut no_transformer = MdStream::new(Options::default())
+        .with_boundary_plugin(TagBoundaryPlugin::thinking())
+        .with_boundary_plugin(FenceBoundaryPlugin::triple_colon());
+    assert!(matches!(
+        no_transformer.restore(snapshot),
+        Err(RestoreError::PendingTransformerCount { .. })
+    ));
+}
+
+/// Checkpoints a fixed state and only accepts it back when `accept` is set.
+struct PickyTransformer {
+    accept: bool,
+}
+
+impl PendingTransformer for PickyTransformer {
+    fn transform(&mut self, _input: PendingTransformInput<'_>) -> Option<String> {
+        None
+    }
+
+    fn checkpoint(&self) -> Option<String> {
+        Some("state".to_string())
+    }
+
+    fn restore(&mut self, state: &str) -> bool {
+        self.accept && state == "state"
+    }
+}
+
+#[test]
+fn restore_rejects_plugin_state_and_leaves_stream_unchanged() {
+    let mut original = MdStream::new(Options::default())
+        .with_boundary_plugin(TagBoundaryPlugin::thinking())
+        .with_pending_transformer(PickyTransformer { accept: true });
+    original.append("Intro\n\n");
+    let snapshot = original.checkpoint();
+
+    let mut target = MdStream::new(Options::default())
+        .with_boundary_plugin(TagBoundaryPlugin::thinking())
+        .with_pending_transformer(PickyTransformer { accept: false });
+    target.append("<thinking>\nhmm\n");
+    assert_eq!(target.restore(snapshot), Err(RestoreError::Corrupt));
+    assert_eq!(target.buffer(), "<thinking>\nhmm\n");
+
+    // The tag plugin got its own state back, so the open block still closes.
+    let u = target.append("</thinking>\n\nAfter");
+    assert_eq!(u.committed[0].raw, "<thinking>\nhmm\n</thinking>\n");
+}
+
+#[test]
+fn builtin_plugins_reject_malformed_state() {
+    let mut fence = FenceBoundaryPlugin::triple_colon();
+    assert!(fence.restore(&fence.checkpoint().unwrap()));
+    for state in ["", "v1 x 0", "v1 - x", "v1 -", "v1 - 0 0", "- 0"] {
+        assert!(!fence.restore(state), "{state:?}");
+    }
+
+    let mut container = ContainerBoundaryPlugin::default();
+    assert!(container.restore("v1 3 1 0"));
+    assert_eq!(container.checkpoint().as_deref(), Some("v1 3 1 0"));
+    for state in ["v1 3 x 0", "v1 - 1", "v1 3 1 2", "3 1 0"] {
+        assert!(!container.restore(state), "{state:?}");
+    }
+
+    let mut tag = TagBoundaryPlugin::thinking();
+    assert!(tag.restore("v1 1"));
+    assert!(!tag.restore("v1 yes"));
+    // A state from another format version is rejected rather than misread.
+    assert!(!tag.restore("1"));
+    assert!(!tag.restore("v2 1"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn snapshot_round_trips_through_serde() {
+    let parts = chunks(DOC, 5);
+    let split = parts.len() / 2;
+    let mut original = new_stream(Options::default());
+    for c in &parts[..split] {
+        original.append(c);
+    }
+    let json = serde_json::to_string(&original.checkpoint()).unwrap();
+    let expected = run(&mut original, &parts[split..]);
+
+    let mut resumed = new_stream(Options::default());
+    resumed
+        .restore(serde_json::from_str(&json).unwrap())
+        .unwrap();
+    assert_eq!(run(&mut resumed, &parts[split..]), expected);
+}
@@ -1,4 +1,4 @@
-use mdstream::{Block, BlockId, BlockKind, BlockStatus};
+use mdstream::{Block, BlockId, BlockKind, BlockStatus, SourceSpan};
 
 #[test]
 fn parses_code_fence_language_backticks() {
@@ -7,6 +7,10 @@ fn parses_code_fence_language_backticks() {
         status: BlockStatus::Committed,
         kind: BlockKind::CodeFence,
         raw: "```mermaid\ngraph TD;\nA-->B;\n```\n".to_string(),
+        span: SourceSpan::default(),
+        fragment: None,
+        custom: None,
+        parent: None,
         display: None,
     };
     assert_eq!(b.code_fence_language(), Some("mermaid"));
@@ -23,6 +27,10 @@ fn parses_code_fence_language_tildes_and_whitespace() {
         status: BlockStatus::Committed,
         kind: BlockKind::CodeFence,
         raw: "~~~   jsonc   \n{a:1,}\n~~~\n".to_string(),
+        span: SourceSpan::default(),
+        fragment: None,
+        custom: None,
+        parent: None,
         display: None,
     };
     assert_eq!(b.code_fence_language(), Some("jsonc"));
@@ -35,6 +43,10 @@ fn returns_none_for_non_code_fence_blocks() {
         status: BlockStatus::Committed,
         kind: BlockKind::Paragraph,
         raw: "```mermaid\n".to_string(),
+        span: SourceSpan::default(),
+        fragment: None,
+        custom: None,
+        parent: None,
         display: None,
     };
     assert_eq!(b.code_fence_language(), None);
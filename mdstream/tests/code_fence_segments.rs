@@ -0,0 +1,184 @@
+use mdstream::{Block, BlockKind, DocumentState, MdStream, Options};
+
+fn opts(max: usize) -> Options {
+    Options {
+        max_pending_bytes: Some(max),
+        ..Default::default()
+    }
+}
+
+fn run(opts: Options, text: &str, chunk: usize) -> Vec<Block> {
+    let mut s = MdStream::new(opts);
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < text.len() {
+        let end = (i + chunk).min(text.len());
+        out.extend(s.append(&text[i..end]).committed);
+        i = end;
+    }
+    out.extend(s.finalize().committed);
+    out
+}
+
+fn code_lines(n: usize) -> String {
+    (0..n).map(|i| format!("let x{i} = {i} ** 2;\n")).collect()
+}
+
+/// `(fence_id, index, last)`
+type SegmentInfo = (u64, usize, bool);
+
+fn segments(blocks: &[Block]) -> Vec<(String, Option<SegmentInfo>)> {
+    blocks
+        .iter()
+        .map(|b| {
+            (
+                b.raw.clone(),
+                b.code_fence_segment()
+                    .map(|s| (s.fence_id.0, s.index, s.last)),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn long_fence_is_committed_in_segments() {
+    let code = code_lines(40);
+    let text = format!("Intro\n\n```rust\n{code}```\n\nAfter\n");
+    let blocks = run(opts(128), &text, 7);
+
+    let fence: Vec<&Block> = blocks
+        .iter()
+        .filter(|b| b.kind == BlockKind::CodeFence)
+        .collect();
+    assert!(fence.len() > 3, "{} segments", fence.len());
+    let joined: String = fence.iter().map(|b| b.raw.as_str()).collect();
+    assert_eq!(joined, format!("```rust\n{code}```\n"));
+
+    let fence_id = fence[0].id;
+    for (i, b) in fence.iter().enumerate() {
+        let seg = b.code_fence_segment().expect("segment");
+        assert_eq!(seg.fence_id, fence_id);
+        assert_eq!(seg.index, i);
+        assert_eq!(seg.last, i + 1 == fence.len());
+        assert_eq!(seg.is_middle(), i > 0 && i + 1 < fence.len());
+    }
+    assert!(fence[0].raw.starts_with("```rust\n"));
+    assert_eq!(fence[0].code_fence_language(), Some("rust"));
+    assert!(fence.last().unwrap().raw.ends_with("```\n"));
+    assert_eq!(blocks.last().map(|b| b.raw.as_str()), Some("After\n"));
+}
+
+#[test]
+fn segments_are_chunking_invariant() {
+    let text = format!("```\n{}```\nTail\n", code_lines(30));
+    let expected = segments(&run(opts(100), &text, text.len()));
+    for chunk in 1..10 {
+        assert_eq!(
+            segments(&run(opts(100), &text, chunk)),
+            expected,
+            "chunk={chunk}"
+        );
+    }
+}
+
+#[test]
+fn pending_stays_bounded_and_verbatim() {
+    let mut s = MdStream::new(opts(64));
+    s.append("```python\n");
+    let mut max_pending = 0;
+    for i in 0..200 {
+        let u = s.append(&format!("print('**{i}')\n"));
+        // Right after a segment commit there is nothing pending.
+        let Some(p) = u.pending else {
+            continue;
+        };
+        max_pending = max_pending.max(p.raw.len());
+        if let Some(seg) = p.code_fence_segment() {
+            assert!(!seg.is_first());
+            // No opening fence in this segment, so nothing is closed or terminated.
+            assert_eq!(p.display.as_deref(), Some(p.raw.as_str()));
+        }
+    }
+    // At most the segment budget plus the line that started the next segment.
+    assert!(max_pending < 64 + 16, "max pending {max_pending}");
+
+    let f = s.finalize();
+    let last = f.committed.last().expect("last segment");
+    assert!(last.code_fence_segment().is_some_and(|s| s.last));
+}
+
+#[test]
+fn closing_fence_ends_the_last_segment() {
+    // The opening line alone fills the first segment.
+    let blocks = run(opts(8), "```text\nab\n```\nNext\n", 1);
+    assert_eq!(
+        segments(&blocks),
+        vec![
+            ("```text\n".to_string(), Some((1, 0, false))),
+            ("ab\n```\n".to_string(), Some((1, 1, true))),
+            ("Next\n".to_string(), None),
+        ]
+    );
+}
+
+#[test]
+fn short_fences_are_not_split() {
+    let blocks = run(opts(1024), "```\nshort\n```\n", 3);
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].fragment, None);
+}
+
+#[test]
+fn buffer_stays_bounded_with_compaction() {
+    let mut s = MdStream::new(Options {
+        max_buffer_bytes: Some(4096),
+        ..opts(1024)
+    });
+    s.append("~~~log\n");
+    for i in 0..20_000 {
+        s.append(&format!("line {i}: some log output\n"));
+        assert!(s.buffer().len() < 8192);
+    }
+}
+
+#[test]
+fn truncate_inside_a_split_fence_matches_a_fresh_stream() {
+    let text = format!("# T\n\n```js\n{}```\n\nEnd\n", code_lines(12));
+    for cut in (0..=text.len()).step_by(5) {
+        let mut s = MdStream::new(opts(64));
+        let mut state = DocumentState::new();
+        let mut i = 0;
+        while i < text.len() {
+            let end = (i + 6).min(text.len());
+            state.apply(s.append(&text[i..end]));
+            i = end;
+        }
+        state.apply(s.truncate_to(cut).expect("truncate"));
+        state.apply(s.append("\n~~~\n"));
+        state.apply(s.finalize());
+
+        let resumed = format!("{}\n~~~\n", &text[..cut]);
+        let want: Vec<(BlockKind, String)> = run(opts(64), &resumed, resumed.len())
+            .into_iter()
+            .map(|b| (b.kind, b.raw))
+            .collect();
+        let got: Vec<(BlockKind, String)> = state
+            .blocks()
+            .filter(|b| !b.raw.trim().is_empty())
+            .map(|b| (b.kind, b.raw.clone()))
+            .collect();
+        let want: Vec<(BlockKind, String)> = want
+            .into_iter()
+            .filter(|(_, raw)| !raw.trim().is_empty())
+            .collect();
+        assert_eq!(got, want, "cut={cut}");
+    }
+}
+
+#[test]
+fn disabled_by_default() {
+    let text = format!("```\n{}```\n", code_lines(500));
+    let blocks = run(Options::default(), &text, 64);
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].fragment, None);
+}
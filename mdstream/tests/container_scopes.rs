@@ -0,0 +1,202 @@
+use mdstream::testing::ChunkingVerifier;
+use mdstream::{
+    Block, BlockFragment, BlockId, BlockKind, ContainerBoundaryPlugin, ContainerFragment, MdStream,
+    MdStreamBuilder, Options, TagBoundaryPlugin,
+};
+
+fn scoped() -> Options {
+    Options {
+        container_scopes: true,
+        ..Default::default()
+    }
+}
+
+fn new_stream() -> MdStream {
+    MdStream::new(scoped())
+        .with_boundary_plugin(TagBoundaryPlugin::thinking())
+        .with_boundary_plugin(ContainerBoundaryPlugin::default())
+}
+
+fn summary(blocks: &[Block]) -> Vec<(&str, Option<u64>)> {
+    blocks
+        .iter()
+        .map(|b| (b.raw.as_str(), b.parent.map(|id| id.0)))
+        .collect()
+}
+
+#[test]
+fn container_content_is_split_into_child_blocks() {
+    let mut s = new_stream();
+    let u = s
+        .append("Intro\n\n<thinking>\n# Plan\n\nStep one\n\n- a\n- b\n</thinking>\n\nAfter\n\nEnd");
+    assert_eq!(
+        summary(&u.committed),
+        vec![
+            ("Intro\n\n", None),
+            ("<thinking>\n", None),
+            ("# Plan\n", Some(2)),
+            ("Step one\n\n", Some(2)),
+            ("- a\n- b\n", Some(2)),
+            ("</thinking>\n", None),
+            ("After\n\n", None),
+        ]
+    );
+
+    let open = &u.committed[1];
+    assert_eq!(open.kind, BlockKind::Custom);
+    assert_eq!(open.custom.as_ref().unwrap().name, "thinking");
+    assert_eq!(
+        open.fragment,
+        Some(BlockFragment::ContainerOpen(ContainerFragment {
+            container_id: open.id,
+            depth: 0,
+        }))
+    );
+    assert_eq!(u.committed[2].kind, BlockKind::Heading);
+    assert_eq!(u.committed[4].kind, BlockKind::List);
+
+    let close = u
+        .committed
+        .iter()
+        .find(|b| b.raw == "</thinking>\n")
+        .unwrap();
+    assert_eq!(close.kind, BlockKind::Custom);
+    assert_eq!(
+        close.container(),
+        Some(&ContainerFragment {
+            container_id: open.id,
+            depth: 0,
+        })
+    );
+    assert!(matches!(
+        close.fragment,
+        Some(BlockFragment::ContainerClose(_))
+    ));
+    assert_eq!(u.pending.unwrap().parent, None);
+}
+
+#[test]
+fn children_commit_while_the_container_is_open() {
+    let mut s = new_stream();
+    let u = s.append("<thinking>\nfirst\n\nsecond");
+    assert_eq!(
+        summary(&u.committed),
+        vec![("<thinking>\n", None), ("first\n\n", Some(1))]
+    );
+    let pending = u.pending.unwrap();
+    assert_eq!(pending.raw, "second");
+    assert_eq!(pending.kind, BlockKind::Paragraph);
+    assert_eq!(pending.parent, Some(BlockId(1)));
+
+    let u = s.append_ref(" part\n</thinking>\n");
+    assert_eq!(
+        summary(u.committed),
+        vec![("second part\n", Some(1)), ("</thinking>\n", None)]
+    );
+    assert!(u.pending.is_none());
+}
+
+#[test]
+fn containers_of_different_plugins_nest() {
+    let mut s = new_stream();
+    let u = s.append("::: details\nOuter\n\n<thinking>\nInner\n</thinking>\nMore\n:::\n");
+    assert_eq!(
+        summary(&u.committed),
+        vec![
+            ("::: details\n", None),
+            ("Outer\n\n", Some(1)),
+            ("<thinking>\n", Some(1)),
+            ("Inner\n", Some(3)),
+            ("</thinking>\n", Some(1)),
+            ("More\n", Some(1)),
+            (":::\n", None),
+        ]
+    );
+    assert_eq!(u.committed[2].container().unwrap().depth, 1);
+    assert_eq!(u.committed[4].container().unwrap().container_id.0, 3);
+    assert_eq!(u.committed[6].custom.as_ref().unwrap().name, "details");
+}
+
+#[test]
+fn a_plugin_tracks_its_own_nesting() {
+    let mut s = new_stream();
+    let u = s.append("::: outer\n::: inner\nx\n:::\n:::\n");
+    let raws: Vec<&str> = u.committed.iter().map(|b| b.raw.as_str()).collect();
+    assert_eq!(raws.first(), Some(&"::: outer\n"));
+    assert_eq!(raws.last(), Some(&":::\n"));
+    assert!(
+        u.committed[1..u.committed.len() - 1]
+            .iter()
+            .all(|b| b.parent == Some(u.committed[0].id) && b.fragment.is_none())
+    );
+}
+
+#[test]
+fn containers_stay_single_blocks_by_default() {
+    let mut s =
+        MdStream::new(Options::default()).with_boundary_plugin(TagBoundaryPlugin::thinking());
+    let u = s.append("<thinking>\nA\n\nB\n</thinking>\n");
+    assert_eq!(u.committed.len(), 1);
+    assert_eq!(u.committed[0].fragment, None);
+    assert_eq!(u.committed[0].parent, None);
+}
+
+#[test]
+fn finalize_closes_containers_left_open() {
+    let mut s = MdStreamBuilder::incremark()
+        .container_scopes(true)
+        .build()
+        .unwrap();
+    let text = "::: warning\nhello\n\nworld\n";
+    let mut blocks = s.append(text).committed;
+    blocks.extend(s.finalize().committed);
+    assert_eq!(
+        summary(&blocks),
+        vec![
+            ("::: warning\n", None),
+            ("hello\n\n", Some(1)),
+            ("world\n", Some(1)),
+            ("", None),
+        ]
+    );
+    let close = &blocks[3];
+    assert_eq!(
+        close.fragment,
+        Some(BlockFragment::ContainerClose(ContainerFragment {
+            container_id: BlockId(1),
+            depth: 0,
+        }))
+    );
+    assert_eq!(close.custom.as_ref().unwrap().name, "warning");
+    assert_eq!((close.span.start, close.span.end), (text.len(), text.len()));
+    assert_eq!(close.span.start_line, 4);
+    assert!(close.id.0 > blocks[2].id.0);
+
+    let mut s = new_stream();
+    s.append("::: details\n<thinking>\nstill thinking");
+    let u = s.finalize();
+    assert_eq!(
+        summary(&u.committed),
+        vec![("still thinking", Some(2)), ("", Some(1)), ("", None)]
+    );
+    assert_eq!(u.committed[1].container().unwrap().container_id, BlockId(2));
+    assert_eq!(u.committed[2].container().unwrap().container_id, BlockId(1));
+}
+
+#[test]
+fn checkpoint_restores_open_containers() {
+    let mut a = new_stream();
+    a.append("<thinking>\nA\n\n");
+    let mut b = new_stream();
+    b.restore(a.checkpoint()).unwrap();
+
+    let rest = "B\n</thinking>\nC\n";
+    assert_eq!(a.append(rest), b.append(rest));
+    assert_eq!(summary(&b.snapshot_blocks()).last(), Some(&("C\n", None)));
+}
+
+#[test]
+fn container_scopes_are_chunking_invariant() {
+    let text = "Intro\n\n::: details\n<thinking>\n# Plan\n\n```\ncode\n```\n</thinking>\n| a |\n|---|\n| 1 |\n:::\n\nTail";
+    ChunkingVerifier::new(new_stream).assert_invariant(text);
+}
@@ -0,0 +1,152 @@
+use mdstream::testing::ChunkingVerifier;
+use mdstream::{
+    BlockKind, BoundaryUpdate, ContainerBoundaryPlugin, CustomBlock, FenceBoundaryPlugin,
+    FnBoundaryPlugin, MdStream, Options, TagBoundaryPlugin,
+};
+
+#[test]
+fn tag_plugin_declares_tag_name_and_attributes() {
+    let mut s =
+        MdStream::new(Options::default()).with_boundary_plugin(TagBoundaryPlugin::thinking());
+    let u = s.append("Intro\n\n<Thinking mode=\"deep\" budget=3 verbose>\nA\n\nB\n</thinking>\n");
+    assert_eq!(u.committed.len(), 2);
+    assert_eq!(u.committed[0].kind, BlockKind::Paragraph);
+    assert_eq!(u.committed[0].custom, None);
+
+    let block = &u.committed[1];
+    assert_eq!(block.kind, BlockKind::Custom);
+    let custom = block.custom.as_ref().unwrap();
+    assert_eq!(custom.name, "thinking");
+    assert_eq!(
+        custom.attributes,
+        vec![
+            ("mode".to_string(), "deep".to_string()),
+            ("budget".to_string(), "3".to_string()),
+            ("verbose".to_string(), String::new()),
+        ]
+    );
+    assert_eq!(custom.attribute("budget"), Some("3"));
+    assert_eq!(custom.attribute("missing"), None);
+}
+
+#[test]
+fn container_plugin_declares_container_name() {
+    let mut s =
+        MdStream::new(Options::default()).with_boundary_plugin(ContainerBoundaryPlugin::default());
+    let u = s.append("::: warning {title=\"Be careful\" open}\nBody\n:::\n\n::: \nx\n");
+    assert_eq!(u.committed.len(), 1);
+    assert_eq!(u.committed[0].kind, BlockKind::Custom);
+    assert_eq!(
+        u.committed[0].custom,
+        Some(
+            CustomBlock::new("warning")
+                .with_attribute("title", "Be careful")
+                .with_attribute("open", "")
+        )
+    );
+}
+
+#[test]
+fn fence_plugin_declares_info_word() {
+    let mut s =
+        MdStream::new(Options::default()).with_boundary_plugin(FenceBoundaryPlugin::triple_colon());
+    let u = s.append(":::note\nA\n:::\n\n:::\nB\n:::\n");
+    assert_eq!(u.committed.len(), 2);
+    assert_eq!(u.committed[0].custom, Some(CustomBlock::new("note")));
+    assert_eq!(u.committed[0].kind, BlockKind::Custom);
+    // A bare fence has no name to report.
+    assert_eq!(u.committed[1].kind, BlockKind::Unknown);
+    assert_eq!(u.committed[1].custom, None);
+}
+
+fn at_at_plugin() -> FnBoundaryPlugin {
+    FnBoundaryPlugin::new(
+        |line| line.starts_with("@@"),
+        |line| {
+            if line.trim() == "@@end" {
+                BoundaryUpdate::Close
+            } else {
+                BoundaryUpdate::Continue
+            }
+        },
+    )
+}
+
+#[test]
+fn plugins_without_a_declared_kind_stay_unknown() {
+    let mut s = MdStream::new(Options::default()).with_boundary_plugin(at_at_plugin());
+    let u = s.append("@@ panel\nx\n@@end\n");
+    assert_eq!(u.committed[0].kind, BlockKind::Unknown);
+    assert_eq!(u.committed[0].custom, None);
+
+    let plugin = at_at_plugin().with_custom_block(|line| {
+        let name = line.trim_start_matches('@').trim();
+        (!name.is_empty()).then(|| CustomBlock::new(name))
+    });
+    let mut s = MdStream::new(Options::default()).with_boundary_plugin(plugin);
+    let u = s.append("@@ panel\nx\n@@end\n");
+    assert_eq!(u.committed[0].kind, BlockKind::Custom);
+    assert_eq!(u.committed[0].custom, Some(CustomBlock::new("panel")));
+}
+
+#[test]
+fn pending_block_reports_kind_once_start_line_is_complete() {
+    let mut s =
+        MdStream::new(Options::default()).with_boundary_plugin(TagBoundaryPlugin::thinking());
+    let u = s.append("<thinking id=a>");
+    let p = u.pending.unwrap();
+    assert_eq!(p.kind, BlockKind::Unknown);
+    assert_eq!(p.custom, None);
+
+    let u = s.append_ref("\nstep one");
+    let p = u.pending.unwrap();
+    assert_eq!(p.kind, BlockKind::Custom);
+    assert_eq!(p.custom.unwrap().attribute("id"), Some("a"));
+
+    let u = s.finalize();
+    assert_eq!(u.committed[0].kind, BlockKind::Custom);
+    assert_eq!(u.committed[0].custom.as_ref().unwrap().name, "thinking");
+}
+
+#[test]
+fn finalize_declares_kind_for_unterminated_start_line() {
+    let mut s =
+        MdStream::new(Options::default()).with_boundary_plugin(ContainerBoundaryPlugin::default());
+    s.append("::: tip");
+    let u = s.finalize();
+    assert_eq!(u.committed[0].kind, BlockKind::Custom);
+    assert_eq!(u.committed[0].custom, Some(CustomBlock::new("tip")));
+}
+
+#[test]
+fn pending_delta_resends_block_when_kind_is_declared() {
+    let opts = Options {
+        pending_delta: true,
+        ..Default::default()
+    };
+    let mut s = MdStream::new(opts).with_boundary_plugin(TagBoundaryPlugin::thinking());
+    let u = s.append("<thinking>");
+    assert_eq!(u.pending.unwrap().custom, None);
+
+    let u = s.append("\nA");
+    assert!(u.pending_delta.is_none());
+    assert_eq!(
+        u.pending.unwrap().custom,
+        Some(CustomBlock::new("thinking"))
+    );
+
+    let u = s.append("B");
+    assert!(u.pending.is_none());
+    assert_eq!(u.pending_delta.unwrap().kind, BlockKind::Custom);
+}
+
+#[test]
+fn custom_blocks_are_chunking_invariant() {
+    let text = "Intro\n\n<thinking a=\"1 2\">\nA\n\nB\n</thinking>\n\n::: info\nx\n:::\n\nTail";
+    ChunkingVerifier::new(|| {
+        MdStream::new(Options::default())
+            .with_boundary_plugin(TagBoundaryPlugin::thinking())
+            .with_boundary_plugin(ContainerBoundaryPlugin::default())
+    })
+    .assert_invariant(text);
+}
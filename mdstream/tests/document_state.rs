@@ -1,4 +1,4 @@
-use mdstream::{Block, BlockId, BlockKind, BlockStatus, DocumentState, Update};
+use mdstream::{Block, BlockId, BlockKind, BlockStatus, DocumentState, SourceSpan, Update};
 
 #[test]
 fn document_state_applies_updates_in_order() {
@@ -10,6 +10,10 @@ fn document_state_applies_updates_in_order() {
             status: BlockStatus::Committed,
             kind: BlockKind::Paragraph,
             raw: "A\n\n".to_string(),
+            span: SourceSpan::default(),
+            fragment: None,
+            custom: None,
+            parent: None,
             display: None,
         }],
         pending: Some(Block {
@@ -17,10 +21,16 @@ fn document_state_applies_updates_in_order() {
             status: BlockStatus::Pending,
             kind: BlockKind::Paragraph,
             raw: "B".to_string(),
+            span: SourceSpan::default(),
+            fragment: None,
+            custom: None,
+            parent: None,
             display: Some("B_terminated".to_string()),
         }),
+        pending_delta: None,
         reset: false,
         invalidated: Vec::new(),
+        removed: Vec::new(),
     };
     let applied1 = state.apply(u1);
     assert!(!applied1.reset);
@@ -35,11 +45,17 @@ fn document_state_applies_updates_in_order() {
             status: BlockStatus::Committed,
             kind: BlockKind::Heading,
             raw: "# H\n".to_string(),
+            span: SourceSpan::default(),
+            fragment: None,
+            custom: None,
+            parent: None,
             display: None,
         }],
         pending: None,
+        pending_delta: None,
         reset: false,
         invalidated: vec![BlockId(1)],
+        removed: Vec::new(),
     };
     let applied2 = state.apply(u2);
     assert!(!applied2.reset);
@@ -61,6 +77,10 @@ fn document_state_reset_clears_previous_blocks() {
             status: BlockStatus::Committed,
             kind: BlockKind::Paragraph,
             raw: "old\n".to_string(),
+            span: SourceSpan::default(),
+            fragment: None,
+            custom: None,
+            parent: None,
             display: None,
         }],
         pending: Some(Block {
@@ -68,10 +88,16 @@ fn document_state_reset_clears_previous_blocks() {
             status: BlockStatus::Pending,
             kind: BlockKind::Paragraph,
             raw: "pending".to_string(),
+            span: SourceSpan::default(),
+            fragment: None,
+            custom: None,
+            parent: None,
             display: None,
         }),
+        pending_delta: None,
         reset: false,
         invalidated: Vec::new(),
+        removed: Vec::new(),
     });
 
     let applied = state.apply(Update {
@@ -80,11 +106,17 @@ fn document_state_reset_clears_previous_blocks() {
             status: BlockStatus::Committed,
             kind: BlockKind::Paragraph,
             raw: "X\n".to_string(),
+            span: SourceSpan::default(),
+            fragment: None,
+            custom: None,
+            parent: None,
             display: None,
         }],
         pending: None,
+        pending_delta: None,
         reset: true,
         invalidated: Vec::new(),
+        removed: Vec::new(),
     });
     assert!(applied.reset);
     assert_eq!(state.committed().len(), 1);
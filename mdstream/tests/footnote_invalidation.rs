@@ -0,0 +1,57 @@
+use mdstream::{BlockId, FootnotesMode, MdStream, Options};
+
+fn opts(footnotes: FootnotesMode) -> Options {
+    Options {
+        footnotes,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn emits_invalidated_when_footnote_definition_is_committed() {
+    let mut s = MdStream::new(opts(FootnotesMode::Invalidate));
+
+    s.append("See [^note] here.\n\nOther text.\n\n");
+    let u = s.append("[^Note]: The note\n");
+    assert_eq!(u.committed.len(), 1);
+    assert!(u.invalidated.is_empty(), "definition not committed yet");
+
+    let u = s.append("\nNext\n");
+    assert_eq!(u.committed.len(), 1);
+    assert_eq!(u.invalidated, vec![BlockId(1)]);
+}
+
+#[test]
+fn definitions_inside_code_are_ignored() {
+    let mut s = MdStream::new(opts(FootnotesMode::Invalidate));
+    let u = s.append("```\n[^1]\n```\n\nSee [^1].\n\n[^1]: x\n\n");
+    let usage = u
+        .committed
+        .iter()
+        .find(|b| b.raw.starts_with("See"))
+        .unwrap()
+        .id;
+    let u = s.append("Next\n");
+    assert_eq!(u.invalidated, vec![usage]);
+}
+
+#[test]
+fn single_block_mode_does_not_emit_invalidations() {
+    let mut s = MdStream::new(opts(FootnotesMode::SingleBlock));
+    let mut invalidated = Vec::new();
+    for chunk in ["See [^1].\n\n", "[^1]: x\n\n", "Next\n"] {
+        invalidated.extend(s.append(chunk).invalidated);
+    }
+    invalidated.extend(s.finalize().invalidated);
+    assert!(invalidated.is_empty());
+}
+
+#[test]
+fn truncating_a_definition_invalidates_its_usages() {
+    let mut s = MdStream::new(opts(FootnotesMode::Invalidate));
+    let text = "See [^1].\n\nMiddle\n\n[^1]: x\n\nNext\n";
+    s.append(text);
+    let u = s.truncate_to(text.find("[^1]:").unwrap()).unwrap();
+    assert!(!u.removed.contains(&BlockId(1)));
+    assert_eq!(u.invalidated, vec![BlockId(1)]);
+}
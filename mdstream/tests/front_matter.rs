@@ -0,0 +1,111 @@
+use mdstream::{
+    AnalyzedStream, Block, BlockKind, FrontMatterAnalyzer, FrontMatterFormat, MdStream, Options,
+};
+
+fn opts() -> Options {
+    Options {
+        front_matter: true,
+        ..Default::default()
+    }
+}
+
+fn run(opts: Options, text: &str, chunk: usize) -> Vec<Block> {
+    let mut s = MdStream::new(opts);
+    let mut out = Vec::new();
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = (i + chunk).min(bytes.len());
+        out.extend(s.append(&text[i..end]).committed);
+        i = end;
+    }
+    out.extend(s.finalize().committed);
+    out
+}
+
+fn kinds(blocks: &[Block]) -> Vec<(BlockKind, &str)> {
+    blocks.iter().map(|b| (b.kind, b.raw.as_str())).collect()
+}
+
+#[test]
+fn yaml_front_matter_stays_pending_until_closed() {
+    let mut s = MdStream::new(opts());
+    let u = s.append("---\ntitle: Hello\n\n# not a heading\n");
+    assert!(u.committed.is_empty());
+    let p = u.pending.expect("pending");
+    assert_eq!(p.kind, BlockKind::FrontMatter);
+    assert_eq!(p.display.as_deref(), Some(p.raw.as_str()));
+
+    let u = s.append("---\n# Title\n");
+    assert_eq!(
+        kinds(&u.committed),
+        vec![
+            (
+                BlockKind::FrontMatter,
+                "---\ntitle: Hello\n\n# not a heading\n---\n"
+            ),
+            (BlockKind::Heading, "# Title\n"),
+        ]
+    );
+}
+
+#[test]
+fn toml_front_matter_is_chunking_invariant() {
+    let text = "+++\ntitle = \"x\"\n---\n+++\nBody\n\n---\n";
+    let expected = run(opts(), text, text.len());
+    assert_eq!(
+        kinds(&expected),
+        vec![
+            (BlockKind::FrontMatter, "+++\ntitle = \"x\"\n---\n+++\n"),
+            (BlockKind::Paragraph, "Body\n\n"),
+            (BlockKind::ThematicBreak, "---\n"),
+        ]
+    );
+    for chunk in 1..8 {
+        assert_eq!(run(opts(), text, chunk), expected, "chunk={chunk}");
+    }
+}
+
+#[test]
+fn only_recognized_at_stream_start_and_when_enabled() {
+    let blocks = run(opts(), "Intro\n\n---\na: b\n---\n", 64);
+    assert!(blocks.iter().all(|b| b.kind != BlockKind::FrontMatter));
+
+    let blocks = run(Options::default(), "---\na: b\n---\n", 64);
+    assert_eq!(blocks[0].kind, BlockKind::ThematicBreak);
+}
+
+#[test]
+fn analyzer_exposes_key_value_pairs() {
+    let mut s = AnalyzedStream::new(opts(), FrontMatterAnalyzer);
+    let u = s.append("---\ntitle: \"Hello: world\"\ntags:\n  - a\nauthor: Ann # note\ndra");
+    let meta = u.pending_meta.expect("pending meta").meta;
+    assert_eq!(meta.format, FrontMatterFormat::Yaml);
+    assert!(!meta.closed);
+    assert_eq!(
+        meta.entries,
+        vec![
+            ("title".to_string(), "Hello: world".to_string()),
+            ("tags".to_string(), String::new()),
+            ("author".to_string(), "Ann".to_string()),
+        ]
+    );
+
+    let u = s.append("ft: true\n...\nBody\n");
+    let committed = &u.committed_meta[0];
+    assert!(committed.meta.closed);
+    assert_eq!(committed.meta.get("draft"), Some("true"));
+    assert_eq!(s.meta_for(committed.id), Some(&committed.meta));
+}
+
+#[test]
+fn analyzer_reads_toml_tables() {
+    let mut s = AnalyzedStream::new(opts(), FrontMatterAnalyzer);
+    s.append("+++\ntitle = 'T'\n[extra]\nkey = 1\n+++");
+    let u = s.finalize();
+    let meta = &u.committed_meta[0].meta;
+    assert_eq!(meta.format, FrontMatterFormat::Toml);
+    assert!(meta.closed);
+    assert_eq!(meta.get("title"), Some("T"));
+    assert_eq!(meta.get("extra.key"), Some("1"));
+}
@@ -0,0 +1,76 @@
+use mdstream::{BlockKind, MdStream, MdStreamBuilder, Options};
+
+fn display(u: &mdstream::Update) -> String {
+    let p = u.pending.as_ref().expect("pending");
+    assert_eq!(p.kind, BlockKind::HtmlBlock);
+    p.display.clone().unwrap_or_else(|| p.raw.clone())
+}
+
+fn closing() -> Options {
+    Options {
+        close_html_blocks: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn open_elements_are_closed_in_reverse_order() {
+    let mut s = MdStream::new(closing());
+    let u = s.append("<details><summary>Foo");
+    assert_eq!(display(&u), "<details><summary>Foo</summary></details>");
+
+    let u = s.append("</summary>\n\nBody text\n");
+    assert_eq!(
+        display(&u),
+        "<details><summary>Foo</summary>\n\nBody text\n</details>"
+    );
+
+    let u = s.append("</details>\n");
+    assert!(u.pending.is_none());
+    assert_eq!(u.committed.len(), 1);
+    assert_eq!(
+        u.committed[0].raw,
+        "<details><summary>Foo</summary>\n\nBody text\n</details>\n"
+    );
+    assert_eq!(u.committed[0].display, None);
+}
+
+#[test]
+fn trailing_partial_tag_is_hidden() {
+    let mut s = MdStream::new(closing());
+    let u = s.append("<div>\n<p>Hi <");
+    assert_eq!(display(&u), "<div>\n<p>Hi</p></div>");
+
+    let u = s.append("/p");
+    assert_eq!(display(&u), "<div>\n<p>Hi</p></div>");
+
+    let u = s.append(">\n<section class=\"a");
+    assert_eq!(display(&u), "<div>\n<p>Hi </p>\n</div>");
+
+    let u = s.append("\">");
+    assert_eq!(
+        display(&u),
+        "<div>\n<p>Hi </p>\n<section class=\"a\"></section></div>"
+    );
+}
+
+#[test]
+fn open_comments_are_closed_before_elements() {
+    let mut s = MdStream::new(closing());
+    let u = s.append("<div>\n<!-- note <b");
+    assert_eq!(display(&u), "<div>\n<!-- note <b--></div>");
+
+    let u = s.append(" -->\n<img src=x>");
+    assert_eq!(display(&u), "<div>\n<!-- note <b -->\n<img src=x></div>");
+}
+
+#[test]
+fn html_blocks_keep_raw_display_by_default() {
+    let mut s = MdStream::new(Options::default());
+    let u = s.append("<details><summary>Foo <di");
+    assert_eq!(display(&u), "<details><summary>Foo <di");
+
+    let mut s = MdStreamBuilder::commonmark_strict().build().unwrap();
+    let u = s.append("<details><summary>Foo");
+    assert_eq!(display(&u), "<details><summary>Foo");
+}
@@ -0,0 +1,109 @@
+use mdstream::{Block, BlockKind, MdStream, Options};
+
+fn run(text: &str, chunk: usize) -> Vec<Block> {
+    let mut s = MdStream::new(Options::default());
+    let mut out = Vec::new();
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = (i + chunk).min(bytes.len());
+        out.extend(s.append(&text[i..end]).committed);
+        i = end;
+    }
+    out.extend(s.finalize().committed);
+    out
+}
+
+fn kinds(blocks: &[Block]) -> Vec<(BlockKind, &str)> {
+    blocks.iter().map(|b| (b.kind, b.raw.as_str())).collect()
+}
+
+#[test]
+fn indented_code_after_blank_line_is_its_own_block() {
+    let blocks = run("Intro:\n\n    fn main() {}\n\n\n    more();\nAfter\n", 64);
+    assert_eq!(
+        kinds(&blocks),
+        vec![
+            (BlockKind::Paragraph, "Intro:\n\n"),
+            (
+                BlockKind::IndentedCode,
+                "    fn main() {}\n\n\n    more();\n"
+            ),
+            (BlockKind::Paragraph, "After\n"),
+        ]
+    );
+}
+
+#[test]
+fn indented_lines_do_not_interrupt_paragraphs() {
+    let blocks = run(
+        "Para\n    continued\n    # not a heading\n    - not a list\n",
+        64,
+    );
+    assert_eq!(
+        kinds(&blocks),
+        vec![(
+            BlockKind::Paragraph,
+            "Para\n    continued\n    # not a heading\n    - not a list\n"
+        )]
+    );
+}
+
+#[test]
+fn indented_code_does_not_hide_block_starters() {
+    let blocks = run(
+        "    # code\n    > code\n# Title\n\ttabbed\n```\nx\n```\n",
+        64,
+    );
+    assert_eq!(
+        kinds(&blocks),
+        vec![
+            (BlockKind::IndentedCode, "    # code\n    > code\n"),
+            (BlockKind::Heading, "# Title\n"),
+            (BlockKind::IndentedCode, "\ttabbed\n"),
+            (BlockKind::CodeFence, "```\nx\n```\n"),
+        ]
+    );
+}
+
+#[test]
+fn list_continuations_stay_in_the_list() {
+    let blocks = run(
+        "- item\n\n      code in item\n\n    para in item\n\n    code\n",
+        64,
+    );
+    assert_eq!(
+        kinds(&blocks),
+        vec![(
+            BlockKind::List,
+            "- item\n\n      code in item\n\n    para in item\n\n    code\n"
+        )]
+    );
+
+    let blocks = run("> quote\n\n    code\n", 64);
+    assert_eq!(
+        kinds(&blocks),
+        vec![
+            (BlockKind::BlockQuote, "> quote\n\n"),
+            (BlockKind::IndentedCode, "    code\n"),
+        ]
+    );
+}
+
+#[test]
+fn pending_indented_code_is_not_terminated() {
+    let mut s = MdStream::new(Options::default());
+    let u = s.append("Text\n\n    let s = \"**not bold");
+    let p = u.pending.expect("pending");
+    assert_eq!(p.kind, BlockKind::IndentedCode);
+    assert_eq!(p.display.as_deref(), Some(p.raw.as_str()));
+}
+
+#[test]
+fn indented_code_is_chunking_invariant() {
+    let text = "# Snippet\n    a = 1\n\n    b = 2\n  \n    c = 3\nDone\n\n- x\n\n      y\n";
+    let expected = run(text, text.len());
+    for chunk in 1..8 {
+        assert_eq!(run(text, chunk), expected, "chunk={chunk}");
+    }
+}
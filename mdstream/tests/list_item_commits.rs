@@ -0,0 +1,132 @@
+use mdstream::{Block, BlockKind, MdStream, Options};
+
+fn opts() -> Options {
+    Options {
+        commit_list_items: true,
+        ..Default::default()
+    }
+}
+
+fn run(text: &str, chunk: usize) -> Vec<Block> {
+    let mut s = MdStream::new(opts());
+    let mut out = Vec::new();
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = (i + chunk).min(bytes.len());
+        out.extend(s.append(&text[i..end]).committed);
+        i = end;
+    }
+    out.extend(s.finalize().committed);
+    out
+}
+
+/// `(list_id, ordered, start, index)`
+type ItemInfo = (u64, bool, Option<u64>, usize);
+
+fn item_summary(blocks: &[Block]) -> Vec<(String, Option<ItemInfo>)> {
+    blocks
+        .iter()
+        .map(|b| {
+            (
+                b.raw.clone(),
+                b.list_item()
+                    .map(|i| (i.list_id.0, i.ordered, i.start, i.index)),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn commits_each_top_level_item_when_next_sibling_arrives() {
+    let mut s = MdStream::new(opts());
+    let u = s.append("- one\n");
+    assert!(u.committed.is_empty());
+    let p = u.pending.expect("pending");
+    assert_eq!(p.kind, BlockKind::List);
+    assert_eq!(p.list_item().map(|i| i.index), Some(0));
+
+    let u = s.append("- two");
+    assert_eq!(u.committed.len(), 1);
+    assert_eq!(u.committed[0].raw, "- one\n");
+    let p = u.pending.expect("pending");
+    assert_eq!(p.raw, "- two");
+    assert_eq!(p.list_item().map(|i| i.index), Some(1));
+    assert_eq!(
+        p.list_item().map(|i| i.list_id),
+        Some(u.committed[0].id),
+        "items share the first item's id as list id"
+    );
+}
+
+#[test]
+fn nested_items_and_continuations_stay_in_their_item() {
+    let text = "- a\n  - a.1\n\n  more a\n- b\n  lazy b\n    - b.1\n- c\n\nAfter\n";
+    let blocks = run(text, 3);
+    assert_eq!(
+        item_summary(&blocks),
+        vec![
+            (
+                "- a\n  - a.1\n\n  more a\n".to_string(),
+                Some((1, false, None, 0))
+            ),
+            (
+                "- b\n  lazy b\n    - b.1\n".to_string(),
+                Some((1, false, None, 1))
+            ),
+            ("- c\n\n".to_string(), Some((1, false, None, 2))),
+            ("After\n".to_string(), None),
+        ]
+    );
+}
+
+#[test]
+fn ordered_lists_report_start_number() {
+    let blocks = run("3. x\n4. y\n\n10) z\n", 4);
+    assert_eq!(
+        item_summary(&blocks),
+        vec![
+            ("3. x\n".to_string(), Some((1, true, Some(3), 0))),
+            ("4. y\n\n".to_string(), Some((1, true, Some(3), 1))),
+            // A different delimiter starts a new list.
+            ("10) z\n".to_string(), Some((3, true, Some(10), 0))),
+        ]
+    );
+}
+
+#[test]
+fn lists_separated_by_other_blocks_get_new_ids() {
+    let blocks = run("- a\n- b\n\nPara\n\n- c\n* d\n", 5);
+    assert_eq!(
+        item_summary(&blocks),
+        vec![
+            ("- a\n".to_string(), Some((1, false, None, 0))),
+            ("- b\n\n".to_string(), Some((1, false, None, 1))),
+            ("Para\n\n".to_string(), None),
+            ("- c\n".to_string(), Some((4, false, None, 0))),
+            ("* d\n".to_string(), Some((5, false, None, 0))),
+        ]
+    );
+}
+
+#[test]
+fn item_commits_are_chunking_invariant() {
+    let text = "Intro\n\n1. first\n   continued\n2. second\n\n   para in second\n3. third\n   - nested\n\nDone\n";
+    let expected = run(text, text.len());
+    for chunk in 1..8 {
+        assert_eq!(run(text, chunk), expected, "chunk={chunk}");
+    }
+    assert_eq!(
+        expected.iter().filter(|b| b.list_item().is_some()).count(),
+        3
+    );
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut s = MdStream::new(Options::default());
+    let mut blocks = s.append("- a\n- b\n\nx\n").committed;
+    blocks.extend(s.finalize().committed);
+    assert_eq!(blocks[0].raw, "- a\n- b\n\n");
+    assert!(blocks.iter().all(|b| b.fragment.is_none()));
+}
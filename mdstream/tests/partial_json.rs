@@ -0,0 +1,222 @@
+use mdstream::{
+    AnalyzedStream, BlockKind, FnPendingTransformer, MdStream, Options, PartialJsonAnalyzer,
+    PartialJsonTransformer, PartialJsonValue, PendingTransformInput, TagBoundaryPlugin,
+    parse_partial_json, repair_partial_json,
+};
+
+#[test]
+fn repair_closes_open_strings_arrays_and_objects() {
+    let cases = [
+        (r#"{"a": "hel"#, r#"{"a": "hel"}"#),
+        (r#"{"a": [1, 2"#, r#"{"a": [1, 2]}"#),
+        (r#"{"a": [1, 2,"#, r#"{"a": [1, 2]}"#),
+        (r#"{"a": 1, "b"#, r#"{"a": 1}"#),
+        (r#"{"a": 1, "b":"#, r#"{"a": 1}"#),
+        (r#"{"a": tr"#, r#"{"a": true}"#),
+        (r#"{"a": nu"#, r#"{"a": null}"#),
+        (r#"{"a": -1.5e"#, r#"{"a": -1.5}"#),
+        (r#"{"a": -"#, r#"{}"#),
+        (r#"["x\"#, r#"["x"]"#),
+        (r#"["\u00"#, r#"[""]"#),
+        ("[[{\"k\": [", "[[{\"k\": []}]]"),
+        ("{", "{}"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(repair_partial_json(input), expected, "input: {input}");
+    }
+}
+
+#[test]
+fn repair_leaves_complete_or_empty_text_alone() {
+    for text in [
+        "",
+        "  \n",
+        r#"{"a": [1, {"b": null}]}"#,
+        "42",
+        "{} trailing",
+    ] {
+        assert_eq!(repair_partial_json(text), text);
+    }
+}
+
+#[test]
+fn partial_value_tree_records_completeness() {
+    let v = parse_partial_json(r#"{"city": "Paris", "days": [1, 2], "note": "sunn"#).unwrap();
+    assert!(!v.is_complete());
+    assert_eq!(v.get("city").and_then(|c| c.as_str()), Some("Paris"));
+    assert!(v.get("city").unwrap().is_complete());
+    assert!(v.get("days").unwrap().is_complete());
+    assert_eq!(
+        v.get("note"),
+        Some(&PartialJsonValue::String {
+            value: "sunn".to_string(),
+            complete: false,
+        })
+    );
+
+    let v = parse_partial_json(r#"{"n": 12, "unit"#).unwrap();
+    assert_eq!(v.get("n").and_then(|n| n.as_f64()), Some(12.0));
+    assert_eq!(v.get("unit"), None);
+
+    let v = parse_partial_json(r#"["aé😀"]"#).unwrap();
+    assert!(v.is_complete());
+    match v {
+        PartialJsonValue::Array { items, .. } => assert_eq!(items[0].as_str(), Some("aé😀")),
+        other => panic!("expected array, got {other:?}"),
+    }
+
+    assert_eq!(parse_partial_json("  "), None);
+    assert_eq!(parse_partial_json("not json"), None);
+}
+
+#[test]
+fn transformer_repairs_pending_json_fence() {
+    let mut s = MdStream::new(Options::default())
+        .with_pending_transformer(PartialJsonTransformer::default());
+
+    let u = s.append("```json\n{\"query\": \"weat");
+    let p = u.pending.unwrap();
+    assert_eq!(p.kind, BlockKind::CodeFence);
+    assert_eq!(p.display.as_deref(), Some("```json\n{\"query\": \"weat\"}"));
+
+    let u = s.append("her\", \"days\": [1,");
+    assert_eq!(
+        u.pending.unwrap().display.as_deref(),
+        Some("```json\n{\"query\": \"weather\", \"days\": [1]}")
+    );
+
+    let u = s.append(" 2]}\n```\n");
+    assert_eq!(u.committed.len(), 1);
+    assert_eq!(u.committed[0].display, None);
+}
+
+#[test]
+fn transformer_repairs_json_fence_through_append_ref() {
+    let mut s = MdStream::new(Options::default())
+        .with_pending_transformer(PartialJsonTransformer::default());
+
+    let u = s.append_ref("```json\n{\"query\": \"weat");
+    let p = u.pending.unwrap();
+    // The closing fence added for `append_ref` is kept after the repaired body.
+    assert_eq!(p.display, Some("```json\n{\"query\": \"weat\"}\n```\n"));
+
+    let u = s.append_ref("her\", \"days\": [1,");
+    assert_eq!(
+        u.pending.unwrap().display,
+        Some("```json\n{\"query\": \"weather\", \"days\": [1]}\n```\n")
+    );
+
+    let u = s.append_ref(" 2]}\n");
+    assert_eq!(
+        u.pending.unwrap().display,
+        Some("```json\n{\"query\": \"weather\", \"days\": [1, 2]}\n```\n")
+    );
+}
+
+#[test]
+fn transformer_keeps_output_of_earlier_transformers() {
+    let mut s = MdStream::new(Options::default())
+        .with_pending_transformer(FnPendingTransformer(|input: PendingTransformInput<'_>| {
+            Some(format!("{}<!-- streaming -->", input.display))
+        }))
+        .with_pending_transformer(PartialJsonTransformer::default());
+
+    let u = s.append("```json\n[1, 2");
+    assert_eq!(
+        u.pending.unwrap().display.as_deref(),
+        Some("```json\n[1, 2]<!-- streaming -->")
+    );
+}
+
+#[test]
+fn transformer_ignores_other_fences_and_complete_json() {
+    let mut s = MdStream::new(Options::default())
+        .with_pending_transformer(PartialJsonTransformer::default());
+    let u = s.append("```rust\nfn f() { [");
+    assert_eq!(
+        u.pending.unwrap().display.as_deref(),
+        Some("```rust\nfn f() { [")
+    );
+
+    let mut s = MdStream::new(Options::default())
+        .with_pending_transformer(PartialJsonTransformer::default());
+    let u = s.append("```json\n{\"a\": 1}\n");
+    assert_eq!(
+        u.pending.unwrap().display.as_deref(),
+        Some("```json\n{\"a\": 1}\n")
+    );
+}
+
+#[test]
+fn transformer_repairs_named_custom_blocks() {
+    let mut s = MdStream::new(Options::default())
+        .with_boundary_plugin(TagBoundaryPlugin::new("tool_call"))
+        .with_pending_transformer(PartialJsonTransformer::default().with_block_name("tool_call"));
+
+    let u = s.append("<tool_call>\n{\"name\": \"search\", \"arguments\": {\"q\": \"ru");
+    let p = u.pending.unwrap();
+    assert_eq!(p.kind, BlockKind::Custom);
+    assert_eq!(
+        p.display.as_deref(),
+        Some("<tool_call>\n{\"name\": \"search\", \"arguments\": {\"q\": \"ru\"}}")
+    );
+
+    let u = s.append("st\"}}\n</tool_call>\n");
+    assert_eq!(u.committed.len(), 1);
+    assert_eq!(u.committed[0].display, None);
+}
+
+#[test]
+fn custom_block_display_drops_markdown_repair() {
+    for (chunk, expected) in [
+        (
+            "<tool_call>\n{\"a\": \"x *y",
+            "<tool_call>\n{\"a\": \"x *y\"}",
+        ),
+        (
+            "<tool_call>\n{\"a\": [\"_q",
+            "<tool_call>\n{\"a\": [\"_q\"]}",
+        ),
+        (
+            "<tool_call>\n{\"a\": [\"[q",
+            "<tool_call>\n{\"a\": [\"[q\"]}",
+        ),
+    ] {
+        let mut s = MdStream::new(Options::default())
+            .with_boundary_plugin(TagBoundaryPlugin::new("tool_call"))
+            .with_pending_transformer(
+                PartialJsonTransformer::default().with_block_name("tool_call"),
+            );
+        let p = s.append(chunk).pending.unwrap();
+        assert_eq!(p.display.as_deref(), Some(expected), "chunk: {chunk}");
+    }
+}
+
+#[test]
+fn analyzer_reports_partial_values_for_fences_and_custom_blocks() {
+    let mut s = AnalyzedStream::new(
+        Options::default(),
+        PartialJsonAnalyzer::default().with_block_name("tool_call"),
+    );
+    s.inner_mut()
+        .push_boundary_plugin(TagBoundaryPlugin::new("tool_call"));
+
+    let u = s.append("```json\n[1, {\"a\": \"b");
+    let meta = u.pending_meta.unwrap().meta;
+    assert!(!meta.complete);
+    assert_eq!(meta.value, parse_partial_json("[1, {\"a\": \"b").unwrap());
+
+    let u = s.append("\"}]\n```\n\n<tool_call>\n{\"name\": \"run\"");
+    let committed = &u.committed_meta[0].meta;
+    assert!(committed.complete);
+    let pending = u.pending_meta.unwrap().meta;
+    assert!(!pending.complete);
+    assert_eq!(
+        pending.value.get("name").and_then(|n| n.as_str()),
+        Some("run")
+    );
+
+    let u = s.append("}\n</tool_call>\n\n```python\nx = [\n");
+    assert!(u.committed_meta[0].meta.complete);
+    assert!(u.pending_meta.is_none());
+}
@@ -0,0 +1,185 @@
+use mdstream::{Block, DocumentState, MdStream, Options, PendingDelta, TextDelta, Update};
+
+const DOC: &str = "# Title\n\nSome **bold text and [a link](http://example.com) and `code` here.\n\n- one\n- two with *emph\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n\n$$\nx^2\n$$\n\nTail with ~~strike";
+
+fn delta_opts(base: Options) -> Options {
+    Options {
+        pending_delta: true,
+        ..base
+    }
+}
+
+/// Feed `text` in `chunk`-byte pieces to a full and a delta stream; after every update the two
+/// consumer states must agree.
+fn assert_same_states(base: Options, text: &str, chunk: usize) -> usize {
+    let mut full = MdStream::new(base.clone());
+    let mut delta = MdStream::new(delta_opts(base));
+    let mut full_state = DocumentState::new();
+    let mut delta_state = DocumentState::new();
+    let mut deltas = 0;
+
+    let mut check = |fu: Update, du: Update, full_state: &mut DocumentState| {
+        assert_eq!(du.committed, fu.committed);
+        assert!(du.pending.is_none() || du.pending_delta.is_none());
+        deltas += usize::from(du.pending_delta.is_some());
+        full_state.apply(fu);
+        delta_state.apply(du);
+        assert_eq!(delta_state.pending(), full_state.pending(), "chunk={chunk}");
+        assert_eq!(delta_state.committed(), full_state.committed());
+    };
+
+    let mut i = 0;
+    while i < text.len() {
+        let mut end = (i + chunk).min(text.len());
+        while !text.is_char_boundary(end) {
+            end += 1;
+        }
+        check(
+            full.append(&text[i..end]),
+            delta.append(&text[i..end]),
+            &mut full_state,
+        );
+        i = end;
+    }
+    check(full.finalize(), delta.finalize(), &mut full_state);
+    deltas
+}
+
+#[test]
+fn deltas_rebuild_the_same_pending_block() {
+    for chunk in 1..12 {
+        let deltas = assert_same_states(Options::default(), DOC, chunk);
+        assert!(deltas > 0, "chunk={chunk}");
+    }
+}
+
+#[test]
+fn deltas_rebuild_the_same_pending_block_with_fragments() {
+    let base = Options {
+        commit_list_items: true,
+        commit_table_rows: true,
+        ..Default::default()
+    };
+    for chunk in [1, 3, 7] {
+        assert_same_states(base.clone(), DOC, chunk);
+    }
+}
+
+#[test]
+fn deltas_survive_footnote_resets() {
+    let text = "Intro paragraph\n\nSee [^1] for **more\n\n[^1]: The note\n\nAfter";
+    for chunk in 1..6 {
+        assert_same_states(Options::default(), text, chunk);
+    }
+}
+
+#[test]
+fn appending_plain_text_sends_an_append_delta() {
+    let mut s = MdStream::new(delta_opts(Options::default()));
+    let first = s.append("Hello");
+    assert_eq!(
+        first.pending.as_ref().map(|p| p.raw.as_str()),
+        Some("Hello")
+    );
+    assert!(first.pending_delta.is_none());
+
+    let u = s.append(" world");
+    assert!(u.pending.is_none());
+    let d = u.pending_delta.expect("delta");
+    assert_eq!(d.id, first.pending.as_ref().unwrap().id);
+    assert_eq!(d.raw, " world");
+    assert_eq!(d.display, TextDelta::Append(" world".to_string()));
+}
+
+#[test]
+fn rewritten_tail_sends_replace_from() {
+    let mut s = MdStream::new(delta_opts(Options::default()));
+    let u = s.append("Some **bo");
+    assert_eq!(
+        u.pending.and_then(|p| p.display).as_deref(),
+        Some("Some **bo**")
+    );
+
+    let d = s.append("ld").pending_delta.expect("delta");
+    assert_eq!(
+        d.display,
+        TextDelta::ReplaceFrom {
+            offset: 9,
+            text: "ld**".to_string()
+        }
+    );
+}
+
+#[test]
+fn a_new_pending_block_is_sent_in_full() {
+    let mut s = MdStream::new(delta_opts(Options::default()));
+    s.append("First");
+    let u = s.append("\n\nSecond");
+    assert_eq!(u.committed.len(), 1);
+    assert!(u.pending_delta.is_none());
+    assert_eq!(u.pending.map(|p| p.raw), Some("Second".to_string()));
+}
+
+#[test]
+fn truncate_and_append_ref_resend_the_full_block() {
+    let mut s = MdStream::new(delta_opts(Options::default()));
+    s.append("Hello");
+    s.append(" world");
+
+    let u = s.truncate_to(5).unwrap();
+    assert!(u.pending_delta.is_none());
+    assert_eq!(u.pending.map(|p| p.raw), Some("Hello".to_string()));
+    assert!(s.append("!").pending_delta.is_some());
+
+    s.append_ref("?");
+    let u = s.append(".");
+    assert!(u.pending_delta.is_none());
+    assert_eq!(u.pending.map(|p| p.raw), Some("Hello!?.".to_string()));
+}
+
+#[test]
+fn restored_stream_keeps_sending_deltas() {
+    let mut s = MdStream::new(delta_opts(Options::default()));
+    s.append("Hello");
+    let snapshot = s.checkpoint();
+    let want = s.append(" world");
+
+    let mut restored = MdStream::new(delta_opts(Options::default()));
+    restored.restore(snapshot).unwrap();
+    assert_eq!(restored.append(" world"), want);
+}
+
+#[test]
+fn mismatched_delta_clears_pending() {
+    let mut pending = Some(Block {
+        id: mdstream::BlockId(7),
+        status: mdstream::BlockStatus::Pending,
+        kind: mdstream::BlockKind::Paragraph,
+        raw: "x".to_string(),
+        span: Default::default(),
+        fragment: None,
+        custom: None,
+        parent: None,
+        display: Some("x".to_string()),
+    });
+    let mut update = Update::empty();
+    update.pending_delta = Some(PendingDelta {
+        id: mdstream::BlockId(8),
+        kind: mdstream::BlockKind::Paragraph,
+        span: Default::default(),
+        fragment: None,
+        raw: "y".to_string(),
+        display: TextDelta::Append("y".to_string()),
+    });
+    update.apply_to(&mut Vec::new(), &mut pending);
+    assert!(pending.is_none());
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut s = MdStream::new(Options::default());
+    s.append("Hello");
+    let u = s.append(" world");
+    assert!(u.pending_delta.is_none());
+    assert_eq!(u.pending.map(|p| p.raw), Some("Hello world".to_string()));
+}
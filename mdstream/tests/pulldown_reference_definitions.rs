@@ -1,8 +1,8 @@
 #![cfg(feature = "pulldown")]
 
 use mdstream::adapters::pulldown::{PulldownAdapter, PulldownAdapterOptions};
-use mdstream::{MdStream, Options, ReferenceDefinitionsMode};
-use pulldown_cmark::{Event, Tag};
+use mdstream::{FootnotesMode, MdStream, Options, ReferenceDefinitionsMode};
+use pulldown_cmark::{Event, Options as PulldownOptions, Tag};
 
 fn contains_link(events: &[Event<'static>]) -> bool {
     events
@@ -43,3 +43,91 @@ fn pulldown_adapter_reparses_invalidated_blocks_with_reference_definitions() {
         "block should be re-parsed with definitions"
     );
 }
+
+#[test]
+fn pulldown_adapter_forgets_definitions_from_removed_blocks() {
+    let opts = Options {
+        reference_definitions: ReferenceDefinitionsMode::Invalidate,
+        ..Default::default()
+    };
+
+    let mut s = MdStream::new(opts);
+    let mut a = PulldownAdapter::new(PulldownAdapterOptions::default());
+
+    a.apply_update(&s.append("See [ref].\n\nMiddle\n\n"));
+    a.apply_update(&s.append("[ref]: https://example.com\n\nNext\n"));
+    let block1_id = s.snapshot_blocks()[0].id;
+    assert!(contains_link(
+        a.committed_events(block1_id).expect("events")
+    ));
+
+    // Retract the definition.
+    let u = s.truncate_to("See [ref].\n\nMiddle\n\n".len()).unwrap();
+    assert!(!u.removed.is_empty());
+    a.apply_update(&u);
+
+    assert!(!contains_link(
+        a.committed_events(block1_id).expect("events")
+    ));
+    for id in &u.removed {
+        assert!(a.committed_events(*id).is_none());
+    }
+}
+
+#[test]
+fn pulldown_adapter_injects_multi_line_reference_definitions() {
+    let opts = Options {
+        reference_definitions: ReferenceDefinitionsMode::Invalidate,
+        ..Default::default()
+    };
+
+    let mut s = MdStream::new(opts);
+    let mut a = PulldownAdapter::new(PulldownAdapterOptions::default());
+
+    a.apply_update(&s.append("See [ref].\n\n"));
+    let u = s.append("[ref]:\n  <https://example.com/a b>\n  'a\n  title'\n\nNext\n");
+    a.apply_update(&u);
+
+    let block1_id = s.snapshot_blocks()[0].id;
+    assert!(u.invalidated.contains(&block1_id));
+    let events = a.committed_events(block1_id).expect("events");
+    assert!(events.iter().any(|e| matches!(
+        e,
+        Event::Start(Tag::Link { dest_url, title, .. })
+            if dest_url.as_ref() == "https://example.com/a b" && title.as_ref() == "a\ntitle"
+    )));
+}
+
+#[test]
+fn pulldown_adapter_resolves_footnotes_defined_in_later_blocks() {
+    let opts = Options {
+        footnotes: FootnotesMode::Invalidate,
+        ..Default::default()
+    };
+    let mut s = MdStream::new(opts);
+    let mut a = PulldownAdapter::new(PulldownAdapterOptions {
+        pulldown: PulldownOptions::ENABLE_FOOTNOTES,
+        ..Default::default()
+    });
+    let has_reference = |events: &[Event<'static>]| {
+        events
+            .iter()
+            .any(|e| matches!(e, Event::FootnoteReference(_)))
+    };
+
+    a.apply_update(&s.append("See [^1].\n\n[^1]: The note\n"));
+    let block1_id = s.snapshot_blocks()[0].id;
+    assert!(!has_reference(a.committed_events(block1_id).unwrap()));
+
+    let u = s.append("\nNext\n");
+    a.apply_update(&u);
+    assert!(u.invalidated.contains(&block1_id));
+    let events = a.committed_events(block1_id).unwrap();
+    assert!(has_reference(events));
+    assert!(
+        !events
+            .iter()
+            .any(|e| matches!(e, Event::Start(Tag::FootnoteDefinition(_)))),
+        "injected definitions must not produce events"
+    );
+}
@@ -95,3 +95,21 @@ fn reference_definitions_inside_code_fences_do_not_trigger_invalidations() {
     let u = s.append("```text\n[ref]: https://example.com\n```\n\nNext\n");
     assert!(u.invalidated.is_empty());
 }
+
+#[test]
+fn multi_line_reference_definitions_trigger_invalidations() {
+    let opts = Options {
+        reference_definitions: ReferenceDefinitionsMode::Invalidate,
+        ..Default::default()
+    };
+
+    let mut s = MdStream::new(opts);
+
+    s.append("See [a] and [b].\n\n");
+
+    // Destination on the next line, then an angle-bracket URL with a title spanning lines.
+    let u = s.append(
+        "[a]:\n  https://example.com/a\n[b]: <https://example.com/b> \"two\nline title\"\n\nNext\n",
+    );
+    assert_eq!(u.invalidated, vec![mdstream::BlockId(1)]);
+}
@@ -0,0 +1,110 @@
+use mdstream::{
+    BlockKind, IncompleteLinkPlaceholderTransformer, MdSession, Options, SessionError,
+    TagBoundaryPlugin,
+};
+
+#[test]
+fn routes_deltas_to_independent_streams() {
+    let mut session = MdSession::new(Options::default());
+    let a = session.append("a", "# A\n\nfirst").unwrap();
+    let b = session.append("b", "Other\n\nNext").unwrap();
+    assert_eq!(a.key, "a");
+    assert_eq!(a.update.committed[0].raw, "# A\n");
+    assert_eq!(b.key, "b");
+    assert_eq!(b.update.committed[0].raw, "Other\n\n");
+
+    session.append("a", " para\n").unwrap();
+    let doc = session.document(&"a").unwrap();
+    assert_eq!(doc.committed().len(), 1);
+    assert_eq!(doc.pending().unwrap().raw.trim(), "first para");
+    assert_eq!(session.len(), 2);
+}
+
+#[test]
+fn factories_give_each_stream_its_own_plugins() {
+    let mut session = MdSession::new(Options::default())
+        .with_boundary_plugin(TagBoundaryPlugin::thinking)
+        .with_pending_transformer(IncompleteLinkPlaceholderTransformer::default);
+
+    session.append(1, "<thinking>\nstill open\n").unwrap();
+    let u = session
+        .append(2, "Plain\n\n<thinking>\nx\n</thinking>\nSee [a](ht")
+        .unwrap();
+    assert_eq!(u.update.committed[1].kind, BlockKind::Custom);
+    assert_eq!(u.update.committed[1].raw, "<thinking>\nx\n</thinking>\n");
+    let display = u.update.pending.and_then(|p| p.display).unwrap();
+    assert!(display.contains("streamdown:incomplete-link"));
+
+    // Stream 1 is still inside its own tag block.
+    let p = session.document(&1).unwrap().pending().unwrap();
+    assert_eq!(p.raw, "<thinking>\nstill open\n");
+}
+
+#[test]
+fn finalized_messages_keep_state_but_reject_appends() {
+    let mut session = MdSession::new(Options::default());
+    session.append("m", "Hello").unwrap();
+    let u = session.finalize(&"m").unwrap();
+    assert_eq!(u.update.committed[0].raw, "Hello");
+    assert!(session.is_finalized(&"m"));
+    assert!(session.stream(&"m").is_none());
+    assert_eq!(session.document(&"m").unwrap().committed().len(), 1);
+
+    assert_eq!(session.append("m", "more"), Err(SessionError::Finalized));
+    assert_eq!(session.finalize(&"m"), Err(SessionError::Finalized));
+    assert_eq!(session.finalize(&"x"), Err(SessionError::UnknownKey));
+
+    let state = session.remove(&"m").unwrap();
+    assert_eq!(state.committed()[0].raw, "Hello");
+    assert!(session.is_empty());
+    assert_eq!(session.memory_usage(), 0);
+}
+
+#[test]
+fn memory_budget_evicts_oldest_finalized_messages() {
+    let text = "x".repeat(100);
+    let mut session = MdSession::new(Options::default()).with_memory_budget(750);
+    for key in 0..3 {
+        session.append(key, &text).unwrap();
+    }
+    // Pending messages count their buffer, raw and display text.
+    assert_eq!(session.memory_usage(), 900);
+    // Active streams are never evicted, even over budget.
+    assert_eq!(session.len(), 3);
+
+    assert!(session.finalize(&1).unwrap().evicted.is_empty());
+    assert!(session.finalize(&0).unwrap().evicted.is_empty());
+    assert_eq!(session.memory_usage(), 500);
+
+    let u = session.append(2, &text).unwrap();
+    assert_eq!(u.key, 2);
+    assert_eq!(u.evicted, vec![1]);
+    assert!(session.document(&1).is_none());
+    assert!(session.document(&0).is_some());
+    assert_eq!(session.memory_usage(), 700);
+
+    // An evicted key can be reused for a new message.
+    session.append(1, "again").unwrap();
+    assert!(!session.is_finalized(&1));
+}
+
+#[test]
+fn memory_usage_tracks_every_update() {
+    let text =
+        "# Title\n\nSome *text* here.\n\n- a\n- b\n\n```rust\nfn main() {}\n```\n\nEnd [link](http";
+    let mut session = MdSession::new(Options::default());
+    for (i, ch) in text.char_indices() {
+        session.append("m", &text[i..i + ch.len_utf8()]).unwrap();
+        let doc = session.document(&"m").unwrap();
+        let blocks: usize = doc
+            .blocks()
+            .map(|b| b.raw.len() + b.display.as_ref().map_or(0, String::len))
+            .sum();
+        let buffer = session.stream(&"m").unwrap().buffer().len();
+        assert_eq!(session.memory_usage(), blocks + buffer, "after {i}");
+    }
+    session.finalize(&"m").unwrap();
+    let doc = session.document(&"m").unwrap();
+    let blocks: usize = doc.blocks().map(|b| b.raw.len()).sum();
+    assert_eq!(session.memory_usage(), blocks);
+}
@@ -0,0 +1,89 @@
+use mdstream::{Block, MdStream, Options};
+
+fn assert_span_matches(doc: &str, b: &Block) {
+    assert_eq!(&doc[b.span.byte_range()], b.raw, "block {:?}", b.id);
+    let first_line = doc[..b.span.start].matches('\n').count();
+    assert_eq!(b.span.start_line, first_line, "block {:?}", b.id);
+    let lines = b.raw.matches('\n').count() + usize::from(!b.raw.ends_with('\n'));
+    assert_eq!(
+        b.span.end_line - b.span.start_line,
+        lines,
+        "block {:?}",
+        b.id
+    );
+}
+
+#[test]
+fn committed_and_pending_blocks_report_spans() {
+    let doc = "# Title\n\nPara one\nstill one\n\n```rs\nfn main() {}\n```\nTail";
+    let mut s = MdStream::new(Options::default());
+    let mut committed = Vec::new();
+    let mut last_pending = None;
+    for chunk in doc.split_inclusive('\n') {
+        let u = s.append(chunk);
+        committed.extend(u.committed);
+        last_pending = u.pending;
+    }
+
+    assert_eq!(committed.len(), 3);
+    for b in &committed {
+        assert_span_matches(doc, b);
+    }
+    assert_eq!(committed[0].span.line_range(), 0..1);
+    assert_eq!(committed[1].span.line_range(), 2..5);
+    assert_eq!(committed[2].span.line_range(), 5..8);
+
+    let p = last_pending.expect("pending");
+    assert_eq!(p.raw, "Tail");
+    assert_span_matches(doc, &p);
+    assert_eq!(p.span.end, doc.len());
+}
+
+#[test]
+fn pending_ref_span_excludes_trailing_empty_line() {
+    let mut s = MdStream::new(Options::default());
+    let u = s.append_ref("```\ncode\n");
+    let p = u.pending.expect("pending");
+    assert_eq!(p.span.byte_range(), 0..9);
+    assert_eq!(p.span.line_range(), 0..2);
+}
+
+#[test]
+fn spans_stay_absolute_after_buffer_compaction() {
+    let opts = Options {
+        max_buffer_bytes: Some(64),
+        ..Default::default()
+    };
+    let mut s = MdStream::new(opts);
+    let mut doc = String::new();
+    let mut committed = Vec::new();
+    for i in 0..50 {
+        let chunk = format!("Paragraph number {i}\n\n");
+        doc.push_str(&chunk);
+        committed.extend(s.append(&chunk).committed);
+    }
+    committed.extend(s.finalize().committed);
+
+    assert!(s.buffer().len() <= 64);
+    assert_eq!(committed.len(), 50);
+    for b in &committed {
+        assert_span_matches(&doc, b);
+    }
+    assert_eq!(committed[49].span.start_line, 98);
+}
+
+#[test]
+fn single_block_footnote_reset_spans_whole_document() {
+    let doc = "Intro\n\nSee [^1].\n\n[^1]: Note";
+    let mut s = MdStream::new(Options::default());
+    s.append("Intro\n\n");
+    let u = s.append("See [^1].\n\n[^1]: Note");
+    assert!(u.reset);
+    let p = u.pending.expect("pending");
+    assert_eq!(p.span.byte_range(), 0..doc.len());
+    assert_eq!(p.span.line_range(), 0..5);
+
+    let u = s.finalize();
+    assert_eq!(u.committed.len(), 1);
+    assert_span_matches(doc, &u.committed[0]);
+}
@@ -102,6 +102,19 @@ fn table_after_paragraph_is_separate_block() {
     );
 }
 
+#[test]
+fn table_directly_after_paragraph_keeps_its_header_line() {
+    let mut s = MdStream::new(Options::default());
+    let u = s.append("Intro\n| A | B |\n|---|---|\n| 1 | 2 |\n");
+    assert_eq!(u.committed.len(), 1);
+    assert_eq!(u.committed[0].raw, "Intro\n");
+    assert_eq!(
+        u.pending.as_ref().unwrap().raw,
+        "| A | B |\n|---|---|\n| 1 | 2 |\n"
+    );
+    assert_eq!(u.pending.unwrap().kind, mdstream::BlockKind::Table);
+}
+
 #[test]
 fn splits_streamdown_benchmark_simple_table() {
     let mut s = MdStream::new(Options::default());
@@ -184,6 +197,16 @@ fn treats_single_line_html_comment_as_html_block() {
     assert_eq!(u.pending.as_ref().unwrap().raw, "After");
 }
 
+#[test]
+fn tags_inside_a_single_line_html_comment_do_not_keep_the_block_open() {
+    let mut s = MdStream::new(Options::default());
+    let u = s.append("<!-- <div> -->\nAfter");
+    assert_eq!(u.committed.len(), 1);
+    assert_eq!(u.committed[0].kind, mdstream::BlockKind::HtmlBlock);
+    assert_eq!(u.committed[0].raw, "<!-- <div> -->\n");
+    assert_eq!(u.pending.as_ref().unwrap().raw, "After");
+}
+
 #[test]
 fn html_block_with_multiple_tags_on_one_line_is_a_single_block() {
     let mut s = MdStream::new(Options::default());
@@ -0,0 +1,239 @@
+use std::sync::{Arc, Mutex};
+
+use mdstream::{
+    Block, BlockId, BlockKind, FootnotesMode, MdStream, MdStreamBuilder, Options, PendingBlockRef,
+    ReferenceDefinitionsMode, StreamObserver,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Event {
+    Commit(BlockId, String),
+    Pending(BlockId, String),
+    Invalidate(Vec<BlockId>),
+    Remove(Vec<BlockId>),
+    Reset,
+    Finalize,
+}
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<Event>>>);
+
+impl Recorder {
+    fn take(&self) -> Vec<Event> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl StreamObserver for Recorder {
+    fn on_commit(&mut self, block: &Block) {
+        let event = Event::Commit(block.id, block.raw.clone());
+        self.0.lock().unwrap().push(event);
+    }
+
+    fn on_pending_changed(&mut self, pending: PendingBlockRef<'_>) {
+        let event = Event::Pending(pending.id, pending.display_or_raw().to_string());
+        self.0.lock().unwrap().push(event);
+    }
+
+    fn on_invalidate(&mut self, ids: &[BlockId]) {
+        self.0.lock().unwrap().push(Event::Invalidate(ids.to_vec()));
+    }
+
+    fn on_remove(&mut self, ids: &[BlockId]) {
+        self.0.lock().unwrap().push(Event::Remove(ids.to_vec()));
+    }
+
+    fn on_reset(&mut self) {
+        self.0.lock().unwrap().push(Event::Reset);
+    }
+
+    fn on_finalize(&mut self) {
+        self.0.lock().unwrap().push(Event::Finalize);
+    }
+}
+
+fn observed(opts: Options) -> (MdStream, Recorder) {
+    let recorder = Recorder::default();
+    let s = MdStream::new(opts).with_observer(recorder.clone());
+    (s, recorder)
+}
+
+#[test]
+fn commits_and_pending_changes_match_updates() {
+    let (mut s, rec) = observed(Options::default());
+
+    let u = s.append("# Title\n\nSome **bo");
+    assert_eq!(
+        rec.take(),
+        vec![
+            Event::Commit(BlockId(1), "# Title\n".to_string()),
+            Event::Pending(BlockId(3), "Some **bo**".to_string()),
+        ]
+    );
+    assert_eq!(
+        u.pending.and_then(|p| p.display).as_deref(),
+        Some("Some **bo**")
+    );
+
+    let u = s.finalize();
+    let events = rec.take();
+    assert_eq!(events.last(), Some(&Event::Finalize));
+    let commits: Vec<&Event> = events
+        .iter()
+        .filter(|e| matches!(e, Event::Commit(..)))
+        .collect();
+    assert_eq!(commits.len(), u.committed.len());
+
+    // Nothing left to finalize.
+    s.finalize();
+    assert!(rec.take().is_empty());
+}
+
+#[test]
+fn mermaid_fence_is_seen_as_soon_as_it_commits() {
+    let (mut s, rec) = observed(Options::default());
+    s.append("```mermaid\ngraph TD\n  A-->B\n");
+    assert!(!rec.take().iter().any(|e| matches!(e, Event::Commit(..))));
+    let u = s.append("```\n");
+    assert_eq!(u.committed.len(), 1);
+    assert_eq!(u.committed[0].kind, BlockKind::CodeFence);
+    assert!(rec.take().contains(&Event::Commit(
+        u.committed[0].id,
+        u.committed[0].raw.clone()
+    )));
+}
+
+#[test]
+fn invalidations_are_reported() {
+    let (mut s, rec) = observed(Options {
+        reference_definitions: ReferenceDefinitionsMode::Invalidate,
+        ..Default::default()
+    });
+    s.append("See [ref].\n\n");
+    let u = s.append("[ref]: https://example.com\n\nNext\n");
+    assert_eq!(u.invalidated, vec![BlockId(1)]);
+    let events = rec.take();
+    let definition = events
+        .iter()
+        .position(|e| matches!(e, Event::Commit(_, raw) if raw.starts_with("[ref]:")))
+        .expect("definition commit");
+    let invalidate = events
+        .iter()
+        .position(|e| *e == Event::Invalidate(vec![BlockId(1)]))
+        .expect("invalidation");
+    // The definition that caused the invalidation is seen first.
+    assert!(definition < invalidate, "{events:?}");
+}
+
+#[test]
+fn footnote_resets_and_manual_resets_are_reported() {
+    let (mut s, rec) = observed(Options {
+        footnotes: FootnotesMode::SingleBlock,
+        ..Default::default()
+    });
+    s.append("Intro\n\nMore\n\n");
+    rec.take();
+    let u = s.append("See [^1]\n");
+    assert!(u.reset);
+    assert_eq!(rec.take().first(), Some(&Event::Reset));
+
+    s.reset();
+    assert_eq!(rec.take(), vec![Event::Reset]);
+}
+
+#[test]
+fn truncate_reports_only_the_final_result() {
+    let (mut s, rec) = observed(Options::default());
+    s.append("Para one\n\nPara two\n\nPara three\n");
+    rec.take();
+
+    let u = s.truncate_to("Para one\n\nPara".len()).unwrap();
+    assert!(!u.removed.is_empty());
+    let mut expected = vec![Event::Remove(u.removed.clone())];
+    expected.extend(
+        u.committed
+            .iter()
+            .map(|b| Event::Commit(b.id, b.raw.clone())),
+    );
+    expected.push(Event::Pending(u.pending.unwrap().id, "Para".to_string()));
+    assert_eq!(rec.take(), expected);
+}
+
+#[test]
+fn builder_installs_observers() {
+    let rec = Recorder::default();
+    let mut s = MdStream::builder().observer(rec.clone()).build().unwrap();
+    s.append("Hi");
+    assert_eq!(
+        rec.take(),
+        vec![Event::Pending(BlockId(1), "Hi".to_string())]
+    );
+}
+
+#[test]
+fn truncate_at_the_end_reports_discarded_input() {
+    let (mut s, rec) = observed(Options::default());
+    s.append("Line\r");
+    rec.take();
+    let u = s.truncate_to("Line".len()).unwrap();
+    assert_eq!(u.pending.as_ref().unwrap().raw, "Line");
+    assert_eq!(
+        rec.take(),
+        vec![Event::Pending(u.pending.unwrap().id, "Line".to_string())]
+    );
+
+    let (mut s, rec) = observed(Options::default());
+    s.append_bytes("Caf\u{e9}".as_bytes().split_last().unwrap().1)
+        .unwrap();
+    rec.take();
+    s.truncate_to("Caf".len()).unwrap();
+    assert_eq!(rec.take().len(), 1);
+
+    // Nothing to discard: no notification.
+    s.truncate_to("Caf".len()).unwrap();
+    assert!(rec.take().is_empty());
+}
+
+struct Silent;
+
+impl StreamObserver for Silent {}
+
+#[test]
+fn observers_do_not_change_append_output() {
+    let chunks = [
+        "```rust\nfn x",
+        "\n",
+        "}\n",
+        "```\n\nSome **bo",
+        "ld** text",
+    ];
+    for builder in [MdStreamBuilder::new, MdStreamBuilder::commonmark_strict] {
+        let mut plain = builder().build().unwrap();
+        let mut silent = builder().observer(Silent).build().unwrap();
+        let rec = Recorder::default();
+        let mut recorded = builder().observer(rec.clone()).build().unwrap();
+        for chunk in chunks {
+            let expected = plain.append(chunk);
+            assert_eq!(silent.append(chunk), expected, "chunk {chunk:?}");
+            assert_eq!(recorded.append(chunk), expected, "chunk {chunk:?}");
+
+            // The observer sees the display the update carries.
+            let pending = expected.pending.unwrap();
+            let display = pending.display.unwrap_or(pending.raw);
+            assert_eq!(
+                rec.take().last(),
+                Some(&Event::Pending(pending.id, display))
+            );
+        }
+    }
+
+    let mut s = MdStreamBuilder::commonmark_strict()
+        .observer(Silent)
+        .build()
+        .unwrap();
+    let u = s.append("```rust\nfn x\n");
+    assert_eq!(
+        u.pending.unwrap().display.as_deref(),
+        Some("```rust\nfn x\n")
+    );
+}
@@ -0,0 +1,119 @@
+use mdstream::{BlockKind, MdStream, MdStreamBuilder, Options, PartialJsonTransformer};
+
+#[test]
+fn counts_bytes_lines_and_commits_per_kind() {
+    let mut s = MdStream::new(Options::default());
+    let text = "# Title\n\nPara one\n\n- a\n- b\n\n```\ncode\n```\n\nTail";
+    for chunk in text.as_bytes().chunks(5) {
+        s.append(std::str::from_utf8(chunk).unwrap());
+    }
+    s.finalize();
+
+    let stats = s.stats();
+    assert_eq!(stats.bytes_appended, text.len() as u64);
+    assert_eq!(stats.lines_processed, text.lines().count() as u64 - 1);
+    assert_eq!(stats.committed(BlockKind::Heading), 1);
+    assert_eq!(stats.committed(BlockKind::Paragraph), 2);
+    assert_eq!(stats.committed(BlockKind::List), 1);
+    assert_eq!(stats.committed(BlockKind::CodeFence), 1);
+    assert_eq!(stats.committed(BlockKind::Table), 0);
+    assert_eq!(stats.blocks_committed(), 5);
+}
+
+#[test]
+fn borrowed_code_fence_appends_use_the_incremental_display_path() {
+    let mut s = MdStream::new(Options::default());
+    s.append_ref("```rust\n");
+    for i in 0..50 {
+        s.append_ref(&format!("let x = {i};\n"));
+    }
+    let stats = s.stats();
+    assert!(stats.pending_display_incremental >= 49, "{stats:?}");
+    assert!(stats.pending_display_recomputes <= 2, "{stats:?}");
+    assert_eq!(stats.terminator_rescans, 0);
+}
+
+#[test]
+fn transformers_that_skip_code_keep_the_incremental_display_path() {
+    let mut s = MdStreamBuilder::streamdown().build().unwrap();
+    s.append_ref("```rust\n");
+    for i in 0..200 {
+        let u = s.append_ref(&format!("let x = {i};\n"));
+        assert!(u.pending.unwrap().display.unwrap().ends_with("\n```\n"));
+    }
+    let stats = s.stats();
+    assert!(stats.pending_display_incremental >= 199, "{stats:?}");
+    assert!(stats.pending_display_recomputes <= 2, "{stats:?}");
+
+    let mut s = MdStream::new(Options::default())
+        .with_pending_transformer(PartialJsonTransformer::default());
+    s.append_ref("```json\n");
+    for _ in 0..10 {
+        s.append_ref("[1, ");
+    }
+    let u = s.append_ref("[2");
+    assert_eq!(
+        u.pending.unwrap().display,
+        Some("```json\n[1, [1, [1, [1, [1, [1, [1, [1, [1, [1, [2]]]]]]]]]]]\n```\n")
+    );
+}
+
+#[test]
+fn truncation_replays_count_as_work() {
+    let text = "Para one\n\nPara two\n\nPara three\n";
+    let mut s = MdStream::new(Options::default());
+    s.append(text);
+    assert_eq!(s.stats().committed(BlockKind::Paragraph), 2);
+    let lines = s.stats().lines_processed;
+
+    let cut = "Para one\n\nPara".len();
+    let u = s.truncate_to(cut).unwrap();
+    assert!(!u.removed.is_empty());
+    // Cutting at the end changes nothing and is not counted.
+    s.truncate_to(cut).unwrap();
+    s.append(&text[cut..]);
+
+    let stats = s.stats();
+    assert_eq!(stats.truncations, 1);
+    assert!(stats.committed(BlockKind::Paragraph) > 2, "{stats:?}");
+    assert!(stats.lines_processed > lines, "{stats:?}");
+}
+
+#[test]
+fn paragraphs_rerun_the_terminator() {
+    let mut s = MdStream::new(Options::default());
+    for word in ["Some ", "**bold ", "text ", "here"] {
+        s.append(word);
+    }
+    let stats = s.stats();
+    assert_eq!(stats.pending_display_recomputes, 4);
+    assert_eq!(stats.terminator_rescans, 4);
+    assert_eq!(stats.pending_display_incremental, 0);
+}
+
+#[test]
+fn compactions_are_counted_and_reset_clears_stats() {
+    let mut s = MdStream::new(Options {
+        max_buffer_bytes: Some(64),
+        ..Default::default()
+    });
+    for i in 0..20 {
+        s.append(&format!("Paragraph number {i}\n\n"));
+    }
+    assert!(s.stats().compactions > 0);
+
+    s.reset();
+    assert_eq!(s.stats(), &Default::default());
+}
+
+#[cfg(feature = "timing")]
+#[test]
+fn timing_accumulates_terminator_and_transformer_time() {
+    let mut s = MdStream::streamdown_defaults();
+    for _ in 0..20 {
+        s.append("Some **bold [link](http://exa ");
+    }
+    let stats = s.stats();
+    assert!(stats.terminator_time > std::time::Duration::ZERO);
+    assert!(stats.transformer_time > std::time::Duration::ZERO);
+}
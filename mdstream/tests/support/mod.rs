@@ -38,6 +38,28 @@ pub fn collect_final_blocks_with_stream(
     out
 }
 
+/// Like `collect_final_blocks`, but feeds raw bytes through `append_bytes`.
+pub fn collect_final_blocks_from_bytes(
+    chunks: impl IntoIterator<Item = Vec<u8>>,
+    opts: Options,
+) -> Vec<(BlockKind, String)> {
+    let mut s = MdStream::new(opts);
+    let mut out = Vec::new();
+    for chunk in chunks {
+        let u = s.append_bytes(&chunk).expect("append_bytes");
+        if u.reset {
+            out.clear();
+        }
+        out.extend(u.committed.into_iter().map(|b| (b.kind, b.raw)));
+    }
+    let u = s.finalize_bytes().expect("finalize_bytes");
+    if u.reset {
+        out.clear();
+    }
+    out.extend(u.committed.into_iter().map(|b| (b.kind, b.raw)));
+    out
+}
+
 pub fn collect_final_raw_with_stream(
     chunks: impl IntoIterator<Item = String>,
     s: MdStream,
@@ -100,3 +122,24 @@ pub fn chunk_pseudo_random(
     }
     out
 }
+
+/// Like `chunk_pseudo_random`, but splits anywhere, including inside multi-byte characters.
+pub fn chunk_bytes_pseudo_random(
+    bytes: &[u8],
+    seed_label: &str,
+    trial: u64,
+    max_bytes: usize,
+) -> Vec<Vec<u8>> {
+    assert!(max_bytes > 0);
+    let mut state = fnv1a64(seed_label) ^ (trial.wrapping_mul(0x9e3779b97f4a7c15));
+
+    let mut out = Vec::new();
+    let mut start = 0usize;
+    while start < bytes.len() {
+        let want = (xorshift64(&mut state) as usize % max_bytes) + 1;
+        let end = (start + want).min(bytes.len());
+        out.push(bytes[start..end].to_vec());
+        start = end;
+    }
+    out
+}
@@ -0,0 +1,97 @@
+use mdstream::{BlockKind, MdStream, Options, TableRepairTransformer};
+
+fn stream() -> MdStream {
+    MdStream::new(Options::default()).with_pending_transformer(TableRepairTransformer)
+}
+
+fn pending_display(s: &mut MdStream, chunk: &str) -> (BlockKind, String) {
+    let u = s.append(chunk);
+    let p = u.pending.expect("pending");
+    (p.kind, p.display.unwrap_or(p.raw))
+}
+
+#[test]
+fn header_row_gets_a_delimiter_row() {
+    let mut s = stream();
+    let (kind, display) = pending_display(&mut s, "| Name | Age");
+    assert_eq!(kind, BlockKind::Paragraph);
+    assert_eq!(display, "| Name | Age\n| --- | --- |");
+
+    let (_, display) = pending_display(&mut s, " | City |\n");
+    assert_eq!(display, "| Name | Age | City |\n| --- | --- | --- |");
+}
+
+#[test]
+fn partial_delimiter_row_is_completed_with_typed_alignments() {
+    let mut s = stream();
+    let (kind, display) = pending_display(&mut s, "| a | b | c |\n| :-- | --:");
+    assert_eq!(kind, BlockKind::Paragraph);
+    assert_eq!(display, "| a | b | c |\n| :-- | --: | --- |");
+
+    let (_, display) = pending_display(&mut s, " | :");
+    assert_eq!(display, "| a | b | c |\n| :-- | --: | :-- |");
+}
+
+#[test]
+fn last_row_is_padded_to_the_column_count() {
+    let mut s = stream();
+    let (kind, display) = pending_display(
+        &mut s,
+        "| a | b | c |\n| --- | --- | --- |\n| 1 | 2 | 3 |\n| 4",
+    );
+    assert_eq!(kind, BlockKind::Table);
+    assert_eq!(
+        display,
+        "| a | b | c |\n| --- | --- | --- |\n| 1 | 2 | 3 |\n| 4 | | |"
+    );
+
+    let (_, display) = pending_display(&mut s, " | 5 |");
+    assert_eq!(
+        display,
+        "| a | b | c |\n| --- | --- | --- |\n| 1 | 2 | 3 |\n| 4 | 5 | |"
+    );
+
+    let (_, display) = pending_display(&mut s, "\n|");
+    assert!(display.ends_with("| 4 | 5 |\n| | | |"), "{display:?}");
+
+    // Complete rows are left alone.
+    let (_, display) = pending_display(&mut s, " x | y | z |\n");
+    assert!(display.ends_with("| x | y | z |\n"), "{display:?}");
+}
+
+#[test]
+fn other_paragraphs_are_left_alone() {
+    for text in [
+        "Plain text | with a pipe",
+        "|",
+        "| a |\n| b |",
+        "| a |\nsecond line",
+        "| a |\n| --- |\n| b |",
+    ] {
+        let mut s = stream();
+        let (_, display) = pending_display(&mut s, text);
+        assert_eq!(display, text);
+    }
+}
+
+#[cfg(feature = "pulldown")]
+#[test]
+fn repaired_display_parses_as_a_table_from_the_first_line() {
+    use pulldown_cmark::{Event, Options as PulldownOptions, Parser, Tag};
+
+    let text = "| Name | Age |\n| --- | --: |\n| Ada | 36 |\n| Alan | 41 |\n";
+    let mut s = stream();
+    let mut fed = String::new();
+    for ch in text.chars() {
+        fed.push(ch);
+        let u = s.append(&ch.to_string());
+        let Some(p) = u.pending else { continue };
+        if fed.trim_start_matches(['|', ' ']).is_empty() {
+            continue;
+        }
+        let display = p.display.unwrap_or(p.raw);
+        let is_table = Parser::new_ext(&display, PulldownOptions::ENABLE_TABLES)
+            .any(|e| matches!(e, Event::Start(Tag::Table(_))));
+        assert!(is_table, "not a table after {fed:?}: {display:?}");
+    }
+}
@@ -0,0 +1,149 @@
+use mdstream::{Block, BlockFragment, BlockKind, MdStream, Options, TableAlignment};
+
+fn opts() -> Options {
+    Options {
+        commit_table_rows: true,
+        ..Default::default()
+    }
+}
+
+fn run(text: &str, chunk: usize) -> Vec<Block> {
+    let mut s = MdStream::new(opts());
+    let mut out = Vec::new();
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = (i + chunk).min(bytes.len());
+        out.extend(s.append(&text[i..end]).committed);
+        i = end;
+    }
+    out.extend(s.finalize().committed);
+    out
+}
+
+/// `(raw, head?, table_id, columns, row)`
+type RowInfo = (String, bool, u64, usize, usize);
+
+fn table_summary(blocks: &[Block]) -> Vec<RowInfo> {
+    blocks
+        .iter()
+        .filter_map(|b| {
+            let t = b.table()?;
+            let head = matches!(b.fragment, Some(BlockFragment::TableHead(_)));
+            Some((b.raw.clone(), head, t.table_id.0, t.columns, t.row))
+        })
+        .collect()
+}
+
+#[test]
+fn head_commits_at_delimiter_row_with_alignments() {
+    let mut s = MdStream::new(opts());
+    let u = s.append("| a | b | c |\n");
+    assert!(u.committed.is_empty());
+
+    let u = s.append("| :-- | :-: | --: |\n| 1 ");
+    assert_eq!(u.committed.len(), 1);
+    let head = &u.committed[0];
+    assert_eq!(head.kind, BlockKind::Table);
+    assert_eq!(head.raw, "| a | b | c |\n| :-- | :-: | --: |\n");
+    assert_eq!(head.table().map(|t| (t.columns, t.row)), Some((3, 0)));
+    assert_eq!(
+        head.table_alignments(),
+        Some(vec![
+            TableAlignment::Left,
+            TableAlignment::Center,
+            TableAlignment::Right
+        ])
+    );
+
+    let p = u.pending.expect("pending");
+    assert_eq!(p.kind, BlockKind::Table);
+    assert_eq!(p.raw, "| 1 ");
+    assert!(matches!(p.fragment, Some(BlockFragment::TableRow(_))));
+    assert_eq!(p.table().map(|t| t.table_id), Some(head.id));
+}
+
+#[test]
+fn rows_commit_as_they_complete() {
+    let mut s = MdStream::new(opts());
+    s.append("| a | b |\n|---|---|\n");
+    let u = s.append("| 1 | 2 |\n| 3 |");
+    assert_eq!(u.committed.len(), 1);
+    assert_eq!(u.committed[0].raw, "| 1 | 2 |\n");
+    assert_eq!(u.committed[0].table().map(|t| t.row), Some(0));
+    assert_eq!(u.pending.and_then(|p| p.table().map(|t| t.row)), Some(1));
+    assert!(s.finalize().committed[0].table_alignments().is_none());
+}
+
+#[test]
+fn table_ends_at_blank_line_or_new_block() {
+    let text = "Intro\n| a | b |\n|---|---|\n| 1 | 2 |\n\n| x |\n|---|\n| y |\n# Next\n";
+    let blocks = run(text, text.len());
+    assert_eq!(
+        table_summary(&blocks),
+        vec![
+            ("| a | b |\n|---|---|\n".to_string(), true, 2, 2, 0),
+            ("| 1 | 2 |\n".to_string(), false, 2, 2, 0),
+            ("| x |\n|---|\n".to_string(), true, 5, 1, 0),
+            ("| y |\n".to_string(), false, 5, 1, 0),
+        ]
+    );
+    let others: Vec<(BlockKind, &str)> = blocks
+        .iter()
+        .filter(|b| b.table().is_none())
+        .map(|b| (b.kind, b.raw.as_str()))
+        .collect();
+    assert_eq!(
+        others,
+        vec![
+            (BlockKind::Paragraph, "Intro\n"),
+            (BlockKind::Heading, "# Next\n")
+        ]
+    );
+}
+
+#[test]
+fn mismatched_header_stays_a_whole_table() {
+    let blocks = run("| a | b |\n|---|\n| 1 |\n\nx\n", 4);
+    assert_eq!(blocks[0].kind, BlockKind::Table);
+    assert_eq!(blocks[0].raw, "| a | b |\n|---|\n| 1 |\n\n");
+    assert!(blocks[0].fragment.is_none());
+}
+
+#[test]
+fn row_commits_are_chunking_invariant() {
+    let text =
+        "Text before\n\n| h1 | h2 |\n|:---|---:|\n| a | b |\n| c | d |\n| e | f |\n\nAfter\n";
+    let expected = run(text, text.len());
+    for chunk in 1..9 {
+        assert_eq!(run(text, chunk), expected, "chunk={chunk}");
+    }
+    let rows: Vec<usize> = table_summary(&expected)
+        .into_iter()
+        .filter(|r| !r.1)
+        .map(|r| r.4)
+        .collect();
+    assert_eq!(rows, vec![0, 1, 2]);
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut s = MdStream::new(Options::default());
+    let mut blocks = s.append("| a |\n|---|\n| 1 |\n\nx\n").committed;
+    blocks.extend(s.finalize().committed);
+    assert_eq!(blocks[0].raw, "| a |\n|---|\n| 1 |\n\n");
+    assert!(blocks.iter().all(|b| b.fragment.is_none()));
+}
+
+#[test]
+fn parses_delimiter_rows() {
+    assert_eq!(
+        mdstream::parse_table_delimiter_row("|:-|-|--:|"),
+        Some(vec![
+            TableAlignment::Left,
+            TableAlignment::None,
+            TableAlignment::Right
+        ])
+    );
+    assert_eq!(mdstream::parse_table_delimiter_row("| a | - |"), None);
+}
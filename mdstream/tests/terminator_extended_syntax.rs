@@ -0,0 +1,142 @@
+use mdstream::MdStream;
+use mdstream::pending::{TerminatorOptions, terminate_markdown};
+
+fn remend(text: &str) -> String {
+    let opts = TerminatorOptions {
+        highlight: true,
+        subscript: true,
+        superscript: true,
+        wiki_links: true,
+        ..Default::default()
+    };
+    terminate_markdown(text, &opts)
+}
+
+/// Output without the extended handlers, for inputs they must leave alone.
+fn baseline(text: &str) -> String {
+    terminate_markdown(text, &TerminatorOptions::default())
+}
+
+#[test]
+fn highlight_formatting() {
+    assert_eq!(remend("Text with ==marked"), "Text with ==marked==");
+    assert_eq!(remend("==incomplete "), "==incomplete==");
+    assert_eq!(remend("==first== and ==second"), "==first== and ==second==");
+    assert_eq!(remend("==**bold"), "==**bold**==");
+
+    for text in [
+        "Text with ==marked== text",
+        "a == b",
+        "Heading\n==",
+        "x === y",
+        "==",
+        "`==code` ==",
+    ] {
+        assert_eq!(remend(text), baseline(text), "input: {text:?}");
+    }
+}
+
+#[test]
+fn subscript_formatting() {
+    assert_eq!(remend("H~2"), "H~2~");
+    assert_eq!(remend("H~2~O and CO~2"), "H~2~O and CO~2~");
+
+    for text in [
+        "H~2~O",
+        "~~strike~~",
+        "about ~ 100",
+        "a ~sub with spaces",
+        "`x~y`",
+    ] {
+        assert_eq!(remend(text), baseline(text), "input: {text:?}");
+    }
+    assert_eq!(remend("~~strike"), "~~strike~~");
+}
+
+#[test]
+fn superscript_formatting() {
+    assert_eq!(remend("E = mc^2"), "E = mc^2^");
+    assert_eq!(remend("x^2^ and y^3"), "x^2^ and y^3^");
+
+    for text in [
+        "x^2^",
+        "Note[^1]",
+        "a ^ b",
+        "$x^2",
+        "[link](https://example.com/a^b)",
+    ] {
+        assert_eq!(remend(text), baseline(text), "input: {text:?}");
+    }
+}
+
+#[test]
+fn nested_markers_close_innermost_first() {
+    assert_eq!(remend("==see x^2"), "==see x^2^==");
+    assert_eq!(remend("==H~2"), "==H~2~==");
+}
+
+#[test]
+fn wiki_link_formatting() {
+    assert_eq!(remend("See [[Home"), "See [[Home]]");
+    assert_eq!(
+        remend("See [[Home|the home page"),
+        "See [[Home|the home page]]"
+    );
+    assert_eq!(remend("See [[Home]"), "See [[Home]]");
+
+    for text in ["See [[Home]] now", "[[]]", "`[[code`", "[[a\nb", "[[a] b"] {
+        assert_eq!(remend(text), baseline(text), "input: {text:?}");
+    }
+}
+
+#[test]
+fn extended_syntaxes_are_off_by_default() {
+    for text in ["==mark", "H~2", "x^2"] {
+        assert_eq!(baseline(text), text);
+    }
+    assert_ne!(baseline("[[Page"), remend("[[Page"));
+}
+
+#[test]
+fn pending_display_uses_builder_toggles() {
+    let mut s = MdStream::builder()
+        .highlight(true)
+        .wiki_links(true)
+        .build()
+        .unwrap();
+    let u = s.append("Read ==this part");
+    assert_eq!(
+        u.pending.unwrap().display.as_deref(),
+        Some("Read ==this part==")
+    );
+    let u = s.append("== and [[Setup");
+    assert_eq!(
+        u.pending.unwrap().display.as_deref(),
+        Some("Read ==this part== and [[Setup]]")
+    );
+}
+
+#[test]
+fn markers_inside_paren_math_are_left_to_inline_math() {
+    let opts = TerminatorOptions {
+        superscript: true,
+        subscript: true,
+        inline_math: true,
+        ..Default::default()
+    };
+    let t = |text: &str| terminate_markdown(text, &opts);
+    assert_eq!(t(r"\(x^2"), r"\(x^2\)");
+    assert_eq!(t(r"\(a~1"), r"\(a~1\)");
+    assert_eq!(t(r"\(x^2\) and y^3"), r"\(x^2\) and y^3^");
+    assert_eq!(remend(r"\(x^2"), r"\(x^2");
+}
+
+#[test]
+fn subscript_inside_open_strikethrough() {
+    // `~b~~~` would be one run of three tildes, closing neither span.
+    assert_eq!(remend("~~a ~b"), "~~a ~b~~");
+    assert_eq!(remend("~~strike ~sub"), "~~strike ~sub~~");
+    assert_eq!(remend("~~a ~b~ c"), "~~a ~b~ c~~");
+    assert_eq!(remend("~~a~~ and ~b"), "~~a~~ and ~b~");
+    assert_eq!(remend("~b~ and ~~a"), "~b~ and ~~a~~");
+}
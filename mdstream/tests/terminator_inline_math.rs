@@ -0,0 +1,89 @@
+use mdstream::MdStream;
+use mdstream::pending::{TerminatorOptions, terminate_markdown};
+
+fn terminate(text: &str) -> String {
+    let opts = TerminatorOptions {
+        inline_math: true,
+        ..Default::default()
+    };
+    terminate_markdown(text, &opts)
+}
+
+#[test]
+fn unclosed_dollar_math_is_closed() {
+    assert_eq!(terminate("Energy $E = mc^"), "Energy $E = mc^$");
+    assert_eq!(terminate("$x^2 + "), "$x^2 +$");
+    assert_eq!(terminate("$a$ and $b"), "$a$ and $b$");
+    assert_eq!(terminate("$$block$$ and $x"), "$$block$$ and $x$");
+    assert_eq!(terminate(r"Price \$5 and $x"), r"Price \$5 and $x$");
+    assert_eq!(terminate("Sum: $a_1 + b_"), "Sum: $a_1 + b_$");
+
+    let text = "$x^2 + y^2 = z^2$";
+    assert_eq!(terminate(text), text);
+}
+
+#[test]
+fn unclosed_paren_math_is_closed() {
+    assert_eq!(terminate(r"where \(x + "), r"where \(x +\)");
+    let text = r"where \(x\) holds";
+    assert_eq!(terminate(text), text);
+}
+
+#[test]
+fn trailing_backslash_does_not_escape_the_closer() {
+    assert_eq!(terminate(r"price $x \"), "price $x$");
+    assert_eq!(terminate(r"where \(x \"), r"where \(x\)");
+    assert_eq!(terminate(r"$a \\"), r"$a \\$");
+    assert_eq!(terminate(r"$a \\\"), r"$a \\$");
+    assert_eq!(terminate(r"so $\"), r"so $\");
+}
+
+#[test]
+fn currency_is_not_math() {
+    for text in [
+        "It costs $5",
+        "Between $5 and $10",
+        "About $ 20",
+        "Price: $",
+        "US$5 or so",
+    ] {
+        assert_eq!(terminate(text), text);
+    }
+}
+
+#[test]
+fn code_spans_urls_and_paragraph_breaks_are_skipped() {
+    let text = "Run `echo $HOME` now";
+    assert_eq!(terminate(text), text);
+    assert_eq!(terminate("Run `echo $HOME"), "Run `echo $HOME`");
+
+    let text = "See [docs](https://example.com/$x) here";
+    assert_eq!(terminate(text), text);
+
+    let text = "$x\n\nNext paragraph";
+    assert_eq!(terminate(text), text);
+
+    let text = "```\n$x";
+    assert_eq!(terminate(text), text);
+}
+
+#[test]
+fn inline_math_is_off_by_default() {
+    assert_eq!(
+        terminate_markdown("$x + y", &TerminatorOptions::default()),
+        "$x + y"
+    );
+}
+
+#[test]
+fn pending_display_closes_inline_math() {
+    let mut s = MdStream::builder().inline_math(true).build().unwrap();
+    let u = s.append("Intro\n\nLet $f(x) = x");
+    assert_eq!(
+        u.pending.unwrap().display.as_deref(),
+        Some("Let $f(x) = x$")
+    );
+    let u = s.append("^2$ be given.");
+    let p = u.pending.unwrap();
+    assert_eq!(p.display.as_deref(), Some(p.raw.as_str()));
+}
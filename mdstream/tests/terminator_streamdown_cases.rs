@@ -173,6 +173,11 @@ fn streamdown_component_incomplete_markdown_disabled() {
         inline_code: false,
         strikethrough: false,
         katex_block: false,
+        inline_math: false,
+        highlight: false,
+        subscript: false,
+        superscript: false,
+        wiki_links: false,
         incomplete_link_url: TerminatorOptions::default().incomplete_link_url,
         window_bytes: TerminatorOptions::default().window_bytes,
     };
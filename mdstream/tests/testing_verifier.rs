@@ -0,0 +1,121 @@
+use mdstream::testing::{ChunkStrategy, ChunkingVerifier, MismatchStage};
+use mdstream::{
+    FnPendingTransformer, FootnotesMode, MdStream, MdStreamBuilder, Options, PendingTransformInput,
+    TagBoundaryPlugin,
+};
+
+const DOC: &str = "# Title 标题\n\nSome **bold and `code` here.\n\n- one\n- two\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n```rust\nfn main() {}\n```\n\n$$\nx^2\n$$\n\n> quote\n\nTail with [link](http://exa";
+
+#[test]
+fn strategies_cover_the_input() {
+    let text = "ab\ncé\n";
+    for strategy in [
+        ChunkStrategy::Bytes,
+        ChunkStrategy::Chars,
+        ChunkStrategy::Lines,
+        ChunkStrategy::Random {
+            seed: 7,
+            max_bytes: 3,
+        },
+        ChunkStrategy::EverySplit,
+    ] {
+        for chunks in strategy.chunkings(text) {
+            assert_eq!(chunks.concat(), text.as_bytes(), "{strategy}");
+        }
+    }
+    assert_eq!(ChunkStrategy::Bytes.chunkings(text)[0].len(), text.len());
+    assert_eq!(ChunkStrategy::Chars.chunkings(text)[0].len(), 6);
+    assert_eq!(
+        ChunkStrategy::Lines.chunkings(text)[0],
+        vec![b"ab\n".as_slice(), "cé\n".as_bytes()]
+    );
+    assert_eq!(
+        ChunkStrategy::EverySplit.chunkings(text).len(),
+        text.len() - 1
+    );
+    assert_eq!(
+        ChunkStrategy::Random {
+            seed: 3,
+            max_bytes: 4
+        }
+        .chunkings(DOC),
+        ChunkStrategy::Random {
+            seed: 3,
+            max_bytes: 4
+        }
+        .chunkings(DOC),
+    );
+}
+
+#[test]
+fn built_in_streams_are_chunking_invariant() {
+    ChunkingVerifier::with_options(Options::default())
+        .strategy(ChunkStrategy::EverySplit)
+        .assert_invariant(DOC);
+    ChunkingVerifier::with_options(Options {
+        footnotes: FootnotesMode::Invalidate,
+        commit_list_items: true,
+        commit_table_rows: true,
+        ..Default::default()
+    })
+    .assert_invariant(DOC);
+    ChunkingVerifier::new(|| {
+        MdStreamBuilder::streamdown()
+            .boundary_plugin(TagBoundaryPlugin::thinking())
+            .build()
+            .unwrap()
+    })
+    .strategies(ChunkStrategy::random(16, 8))
+    .assert_invariant("<thinking>\n\nhidden\n\n</thinking>\n\nAfter [x](http");
+}
+
+#[test]
+fn chunk_dependent_transformer_is_reported_with_a_diff() {
+    // Counts its calls, so the display depends on how many chunks were appended.
+    let make = || {
+        let mut calls = 0;
+        MdStream::new(Options::default()).with_pending_transformer(FnPendingTransformer(
+            move |input: PendingTransformInput<'_>| {
+                calls += 1;
+                Some(format!("{}\n<!-- {calls} -->", input.display))
+            },
+        ))
+    };
+    let mismatch = ChunkingVerifier::new(make)
+        .strategies([ChunkStrategy::Lines])
+        .verify("First\n\nSecond line\nthird")
+        .unwrap_err();
+
+    assert_eq!(mismatch.strategy, ChunkStrategy::Lines);
+    assert_eq!(mismatch.stage, MismatchStage::Pending);
+    assert_eq!(
+        mismatch.chunks,
+        vec!["First\n", "\n", "Second line\n", "third"]
+    );
+
+    let report = mismatch.to_string();
+    assert!(
+        report.contains("chunking `per line` (4 chunks) diverged"),
+        "{report}"
+    );
+    assert!(report.contains("pending block before finalize"), "{report}");
+    assert!(report.contains("-\"<!-- 1 -->\""), "{report}");
+    assert!(report.contains("+\"<!-- 4 -->\""), "{report}");
+    assert!(report.contains("  \"Second line\\n\""), "{report}");
+}
+
+#[test]
+#[should_panic(expected = "chunking `per char` (2 chunks) diverged from a single append")]
+fn assert_invariant_panics_with_the_report() {
+    ChunkingVerifier::new(|| {
+        let mut calls = 0;
+        MdStream::new(Options::default()).with_pending_transformer(FnPendingTransformer(
+            move |_: PendingTransformInput<'_>| {
+                calls += 1;
+                Some(calls.to_string())
+            },
+        ))
+    })
+    .strategies([ChunkStrategy::Chars])
+    .assert_invariant("ab");
+}
@@ -0,0 +1,201 @@
+use mdstream::{
+    BlockKind, DocumentState, FootnotesMode, MdStream, Options, ReferenceDefinitionsMode,
+    SourceSpan, TagBoundaryPlugin, TruncateError,
+};
+
+const DOC: &str = "# Title\n\nSee [docs][ref].\n\n- a\n- b\n\n<thinking>\nhmm\n</thinking>\n\n```rust\nfn main() {}\n```\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n[ref]: https://example.com\n\nTail **bold";
+const CONTINUATION: &str = "\n- c\n\n```\nnew code\n```\n\nDone.\n";
+
+fn new_stream(opts: Options) -> MdStream {
+    MdStream::new(opts).with_boundary_plugin(TagBoundaryPlugin::thinking())
+}
+
+type Summary = Vec<(BlockKind, String, SourceSpan)>;
+
+fn summarize(state: &DocumentState) -> Summary {
+    // Whitespace-only pending blocks depend on chunking and are never committed.
+    state
+        .blocks()
+        .filter(|b| !b.raw.trim().is_empty())
+        .map(|b| (b.kind, b.raw.clone(), b.span))
+        .collect()
+}
+
+fn feed(s: &mut MdStream, state: &mut DocumentState, text: &str, chunk: usize) {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = (i + chunk).min(bytes.len());
+        state.apply(s.append(&text[i..end]));
+        i = end;
+    }
+}
+
+fn expected(opts: &Options, text: &str) -> (Summary, Summary) {
+    let mut s = new_stream(opts.clone());
+    let mut state = DocumentState::new();
+    state.apply(s.append(text));
+    let streaming = summarize(&state);
+    state.apply(s.finalize());
+    (streaming, summarize(&state))
+}
+
+fn assert_truncate_matches_fresh_stream(opts: Options) {
+    for seen in (0..=DOC.len()).step_by(3) {
+        for cut in 0..=seen {
+            let mut s = new_stream(opts.clone());
+            let mut state = DocumentState::new();
+            feed(&mut s, &mut state, &DOC[..seen], 5);
+
+            state.apply(s.truncate_to(cut).expect("truncate"));
+            let (want_cut, _) = expected(&opts, &DOC[..cut]);
+            assert_eq!(summarize(&state), want_cut, "seen={seen} cut={cut}");
+
+            feed(&mut s, &mut state, CONTINUATION, 4);
+            state.apply(s.finalize());
+            let resumed = format!("{}{CONTINUATION}", &DOC[..cut]);
+            let (_, want_final) = expected(&opts, &resumed);
+            assert_eq!(summarize(&state), want_final, "seen={seen} cut={cut}");
+        }
+    }
+}
+
+#[test]
+fn truncate_matches_a_stream_that_never_saw_the_tail() {
+    assert_truncate_matches_fresh_stream(Options::default());
+}
+
+#[test]
+fn truncate_matches_in_invalidate_modes() {
+    assert_truncate_matches_fresh_stream(Options {
+        footnotes: FootnotesMode::Invalidate,
+        reference_definitions: ReferenceDefinitionsMode::Invalidate,
+        ..Default::default()
+    });
+}
+
+#[test]
+fn truncate_matches_with_item_and_row_commits() {
+    assert_truncate_matches_fresh_stream(Options {
+        commit_list_items: true,
+        commit_table_rows: true,
+        ..Default::default()
+    });
+}
+
+#[test]
+fn truncate_matches_with_container_scopes() {
+    assert_truncate_matches_fresh_stream(Options {
+        container_scopes: true,
+        ..Default::default()
+    });
+}
+
+#[test]
+fn truncate_lists_removed_ids_and_uses_fresh_ids() {
+    let mut s = MdStream::new(Options::default());
+    s.append("A\n\nB\n\nC\n\nD");
+    assert_eq!(s.snapshot_blocks().len(), 4);
+
+    let u = s.truncate_to(4).unwrap();
+    assert_eq!(u.removed, vec![mdstream::BlockId(2), mdstream::BlockId(3)]);
+    assert!(u.committed.is_empty());
+    let p = u.pending.expect("pending");
+    assert_eq!(p.raw, "B");
+    assert_eq!(p.id, mdstream::BlockId(5));
+    assert_eq!(s.snapshot_blocks()[0].id, mdstream::BlockId(1));
+    assert_eq!(s.buffer(), "A\n\nB");
+}
+
+#[test]
+fn truncate_after_finalize_reopens_the_stream() {
+    let mut s = MdStream::new(Options::default());
+    s.append("A\n\nB\n");
+    let f = s.finalize();
+    assert_eq!(f.committed.len(), 1);
+
+    let u = s.truncate_to(4).unwrap();
+    assert_eq!(u.removed, vec![f.committed[0].id]);
+    assert_ne!(u.pending.as_ref().map(|p| p.id), Some(f.committed[0].id));
+    assert_eq!(u.pending.as_ref().map(|p| p.raw.as_str()), Some("B"));
+
+    s.append("B\n");
+    let f2 = s.finalize();
+    assert_eq!(f2.committed.len(), 1);
+    assert_eq!(f2.committed[0].raw, "BB\n");
+}
+
+#[test]
+fn truncate_to_current_length_is_a_no_op() {
+    let mut s = MdStream::new(Options::default());
+    s.append("A\n\nB");
+    let u = s.truncate_to(4).unwrap();
+    assert!(u.committed.is_empty() && u.removed.is_empty());
+    assert_eq!(u.pending.map(|p| p.raw), Some("B".to_string()));
+    assert_eq!(s.buffer(), "A\n\nB");
+}
+
+#[test]
+fn truncate_rejects_invalid_offsets() {
+    let mut s = MdStream::new(Options::default());
+    s.append("héllo");
+    assert_eq!(
+        s.truncate_to(10),
+        Err(TruncateError::OutOfRange { offset: 10, len: 6 })
+    );
+    assert_eq!(
+        s.truncate_to(2),
+        Err(TruncateError::NotCharBoundary { offset: 2 })
+    );
+    assert_eq!(s.buffer(), "héllo");
+}
+
+#[test]
+fn truncate_into_compacted_text_fails() {
+    let mut s = MdStream::new(Options {
+        max_buffer_bytes: Some(32),
+        ..Default::default()
+    });
+    for i in 0..10 {
+        s.append(&format!("Paragraph {i}\n\n"));
+    }
+    assert_eq!(
+        s.truncate_to(5),
+        Err(TruncateError::Compacted { offset: 5 })
+    );
+
+    // The uncompacted tail can still be rewound.
+    let total: usize = (0..10).map(|i| format!("Paragraph {i}\n\n").len()).sum();
+    let u = s.truncate_to(total - 2).unwrap();
+    assert_eq!(u.pending.expect("pending").raw, "Paragraph 9");
+}
+
+#[test]
+fn truncate_out_of_single_block_footnote_mode_resets() {
+    let mut s = MdStream::new(Options::default());
+    s.append("Intro\n\nSee [^1].\n\n[^1]: Note\n");
+    assert_eq!(s.snapshot_blocks().len(), 1);
+
+    let u = s.truncate_to(7).unwrap();
+    assert!(u.reset);
+    let blocks: Vec<&str> = u.blocks().map(|b| b.raw.as_str()).collect();
+    assert_eq!(blocks, vec!["Intro\n\n"]);
+
+    let mut committed = s.append("Plain\n").committed;
+    committed.extend(s.finalize().committed);
+    let raws: Vec<&str> = committed.iter().map(|b| b.raw.as_str()).collect();
+    assert_eq!(raws, vec!["Intro\n\n", "Plain\n"]);
+}
+
+#[test]
+fn document_state_drops_removed_blocks() {
+    let mut s = MdStream::new(Options::default());
+    let mut state = DocumentState::new();
+    state.apply(s.append("A\n\nB\n\nC"));
+    assert_eq!(state.committed().len(), 2);
+
+    let applied = state.apply(s.truncate_to(2).unwrap());
+    assert_eq!(applied.removed.len(), 2);
+    assert!(state.committed().is_empty());
+    assert_eq!(state.pending().map(|p| p.raw.as_str()), Some("A\n"));
+}